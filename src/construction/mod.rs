@@ -0,0 +1,5 @@
+//! Construction: structures workers put up from items, and the blueprints they follow.
+
+pub mod structure;
+
+pub use structure::{BuildMaterial, Construction, Structure};
@@ -0,0 +1,121 @@
+use crate::items::ItemCategory;
+use crate::world::localmap::material_registry::MaterialRegistry;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+
+/// What can be built on a subpixel
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Structure {
+    Wall,
+    Floor,
+    Ramp,
+    Door,
+    Bridge,
+    /// Where workers sleep best
+    Bed,
+}
+
+impl Structure {
+    pub const ALL: [Structure; 6] = [
+        Structure::Wall,
+        Structure::Floor,
+        Structure::Ramp,
+        Structure::Door,
+        Structure::Bridge,
+        Structure::Bed,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Structure::Wall => "wall",
+            Structure::Floor => "floor",
+            Structure::Ramp => "ramp",
+            Structure::Door => "door",
+            Structure::Bridge => "bridge",
+            Structure::Bed => "bed",
+        }
+    }
+
+    /// The terrain a finished structure turns its subpixel into
+    pub fn material(self) -> TerrainMaterial {
+        match self {
+            Structure::Wall => TerrainMaterial::Wall,
+            Structure::Floor => TerrainMaterial::Floor,
+            Structure::Ramp => TerrainMaterial::Ramp,
+            Structure::Door => TerrainMaterial::Door,
+            Structure::Bridge => TerrainMaterial::Bridge,
+            Structure::Bed => TerrainMaterial::Bed,
+        }
+    }
+
+    /// Whether the structure can go up where `current` is: bridges span liquids,
+    /// everything else needs open, dry ground.
+    pub fn can_build_on(self, materials: &MaterialRegistry, current: TerrainMaterial) -> bool {
+        let props = materials.get(current);
+        match self {
+            Structure::Bridge => props.liquid,
+            _ => !props.solid && !props.liquid && current != self.material(),
+        }
+    }
+
+    /// The next structure in `ALL`, wrapping around
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+/// What a structure is made of
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BuildMaterial {
+    Wood,
+    Stone,
+}
+
+impl BuildMaterial {
+    pub const ALL: [BuildMaterial; 2] = [BuildMaterial::Wood, BuildMaterial::Stone];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BuildMaterial::Wood => "wood",
+            BuildMaterial::Stone => "stone",
+        }
+    }
+
+    /// Category of item a worker has to bring to the site
+    pub fn category(self) -> ItemCategory {
+        match self {
+            BuildMaterial::Wood => ItemCategory::Wood,
+            BuildMaterial::Stone => ItemCategory::Stone,
+        }
+    }
+
+    /// Frames of work to put a structure up once the item is there
+    pub fn build_time(self) -> u32 {
+        match self {
+            BuildMaterial::Wood => 30,
+            BuildMaterial::Stone => 60,
+        }
+    }
+
+    /// The next material in `ALL`, wrapping around
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+/// A structure and what it is made of, planned or built
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Construction {
+    pub structure: Structure,
+    pub material: BuildMaterial,
+}
+
+impl Construction {
+    pub fn new(structure: Structure, material: BuildMaterial) -> Self {
+        Self { structure, material }
+    }
+
+    /// E.g. "stone wall"
+    pub fn name(&self) -> String {
+        format!("{} {}", self.material.name(), self.structure.name())
+    }
+}
@@ -1,88 +1,223 @@
-use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::items::{ItemCategory, ItemId};
+use crate::jobs::{Job, JobKind};
+use crate::world::localmap::position::WorldPos;
 use crate::world::localmap::world::World;
-use super::Creature;
+use super::building::BUILD_REACH;
+use super::digging::DIG_REACH;
+use super::hauling::HAUL_REACH;
+use super::needs::{BED_REACH, NEED_REACH};
+use super::pathfinding::{find_path, walkable};
+use super::{Creature, Task};
+use std::collections::{HashMap, HashSet};
+
+/// Distance a creature walks per frame
+const STEP: f32 = 0.5;
+
+/// Items on the ground nobody has reserved, indexed once per frame so idle creatures
+/// looking for work don't scan every item for every job.
+#[derive(Default)]
+pub struct FreeItems {
+    /// Free items of each category on each level
+    counts: HashMap<(ItemCategory, i32), usize>,
+    ids: HashSet<ItemId>,
+}
+
+impl FreeItems {
+    pub fn index(world: &World) -> Self {
+        let mut free = Self::default();
+        for item in world.items.iter().filter(|item| item.reserved_by.is_none()) {
+            *free.counts.entry((world.item_kinds.category(&item.kind), item.z)).or_insert(0) += 1;
+            free.ids.insert(item.id);
+        }
+        free
+    }
+
+    fn any(&self, category: ItemCategory, z: i32) -> bool {
+        self.counts.get(&(category, z)).is_some_and(|&count| count > 0)
+    }
+
+    /// Takes an item out of the index once someone reserves it.
+    fn take(&mut self, id: ItemId, category: ItemCategory, z: i32) {
+        if self.ids.remove(&id)
+            && let Some(count) = self.counts.get_mut(&(category, z))
+        {
+            *count -= 1;
+        }
+    }
+}
 
 impl Creature {
-    fn is_walkable(mat: TerrainMaterial) -> bool {
-        matches!(mat, TerrainMaterial::Dirt | TerrainMaterial::Air)
+    /// Claims the most urgent job it knows how to do, the nearest among equally urgent ones,
+    /// and heads for it. `free` is this frame's index of unreserved items.
+    pub fn claim_job(&mut self, world: &mut World, free: &mut FreeItems) {
+        let here = WorldPos::from_f32(self.x, self.y, self.z);
+        let tick = world.tick;
+        let kinds = &world.item_kinds;
+        let carrying = self.carrying.as_ref().map(|item| kinds.category(&item.kind));
+        // Build jobs are only worth taking with something to build them from
+        let blueprints = &world.blueprints;
+        let has_materials = |job: &Job| {
+            blueprints.get(&job.pos).is_some_and(|construction| {
+                let category = construction.material.category();
+                carrying == Some(category) || free.any(category, self.z)
+            })
+        };
+        // Hauling needs free hands and the item still lying there
+        let can_haul = |job: &Job| carrying.is_none() && job.item.is_some_and(|item| free.ids.contains(&item));
+        let Some(id) = world.jobs.claim(self.id, here, tick, |job| match job.kind {
+            JobKind::Dig | JobKind::Chop => true,
+            JobKind::Build => has_materials(job),
+            JobKind::Haul => can_haul(job),
+        }) else {
+            return;
+        };
+        let Some(job) = world.jobs.get(id) else { return; };
+        self.task = match job.kind {
+            JobKind::Dig => Task::Dig,
+            JobKind::Chop => Task::Chop,
+            JobKind::Build => Task::Build,
+            JobKind::Haul => Task::Haul,
+        };
+        if let Some(item) = job.item.and_then(|item| world.items.iter_mut().find(|other| other.id == item)) {
+            item.reserved_by = Some(self.id);
+            free.take(item.id, world.item_kinds.category(&item.kind), item.z);
+        }
+        self.target = Some(job.pos);
+        self.job = Some(id);
+        self.path.clear();
     }
 
-    pub fn find_nearest_dig_target(&mut self, world: &World) {
-        let search_radius = 100;
-        let start_x = self.x as i32;
-        let start_y = self.y as i32;
-        let mut best_target: Option<(i32, i32)> = None;
-        let mut best_distance = f32::MAX;
-        for dx in -search_radius..=search_radius {
-            for dy in -search_radius..=search_radius {
-                let check_x = start_x + dx;
-                let check_y = start_y + dy;
-                let chunk_x = check_x.div_euclid(32 * 8);
-                let chunk_y = check_y.div_euclid(32 * 8);
-                let local_x = check_x.rem_euclid(32 * 8);
-                let local_y = check_y.rem_euclid(32 * 8);
-                let tile_x = local_x.div_euclid(8);
-                let tile_y = local_y.div_euclid(8);
-                let sub_x = local_x.rem_euclid(8);
-                let sub_y = local_y.rem_euclid(8);
-                if let Some(zlevel) = world.z_levels.get(0) {
-                    if let Some(chunk) = zlevel.chunks.get(&(chunk_x, chunk_y)) {
-                        if let Some(tile) = chunk
-                            .tiles
-                            .get(tile_x as usize)
-                            .and_then(|r| r.get(tile_y as usize))
-                        {
-                            let subpixel = &tile.subgrid[sub_x as usize][sub_y as usize];
-                            if subpixel.dig_target {
-                                let dx = self.x - check_x as f32;
-                                let dy = self.y - check_y as f32;
-                                let dist_sq = dx * dx + dy * dy;
-                                if dist_sq < best_distance {
-                                    best_distance = dist_sq;
-                                    best_target = Some((check_x, check_y));
-                                }
-                            }
-                        }
-                    }
+    /// Hands the claimed job back to the board for someone else, keeping its progress, and
+    /// puts down whatever the creature was carrying for it. Haul jobs are taken off instead,
+    /// as the item no longer lies where the job says; they are posted again from where it is.
+    pub fn drop_job(&mut self, world: &mut World) {
+        if let Some(id) = self.job.take() {
+            match world.jobs.get(id).and_then(|job| job.item) {
+                Some(item) => {
+                    world.jobs.withdraw_haul(item);
                 }
+                None => world.jobs.release(id),
             }
         }
-        self.target = best_target;
+        self.release_items(world);
+        if let Some(mut item) = self.carrying.take() {
+            (item.x, item.y, item.z) = (self.x, self.y, self.z);
+            world.items.push(item);
+        }
+        self.target = None;
+        self.path.clear();
     }
 
-    pub fn move_toward_target(&mut self, world: &World) {
-        if let Some((target_x, target_y)) = self.target {
-            let dx = target_x as f32 - self.x;
-            let dy = target_y as f32 - self.y;
-            let (step_x, step_y) = if dx.abs() > dy.abs() {
-                (dx.signum() * 0.5, 0.0)
-            } else {
-                (0.0, dy.signum() * 0.5)
-            };
-            let next_x = self.x + step_x;
-            let next_y = self.y + step_y;
-            let primary_terrain = world.get_material_at(next_x as i32, next_y as i32);
-            if Self::is_walkable(primary_terrain) {
-                self.x = next_x;
-                self.y = next_y;
-            } else {
-                // Try the other axis instead
-                let (alt_step_x, alt_step_y) = if step_x == 0.0 {
-                    (dx.signum() * 0.5, 0.0)
-                } else {
-                    (0.0, dy.signum() * 0.5)
-                };
-                let alt_x = self.x + alt_step_x;
-                let alt_y = self.y + alt_step_y;
-                let alt_terrain = world.get_material_at(alt_x as i32, alt_y as i32);
-                if Self::is_walkable(alt_terrain) {
-                    self.x = alt_x;
-                    self.y = alt_y;
-                } else {
-                    // Fully stuck
-                    self.target = None;
+    /// Cancels the claimed job for good, lifting the designation behind it; used when the
+    /// player calls it off from the Worker Info window.
+    pub fn cancel_job(&mut self, world: &mut World) {
+        let Some(job) = self.job.and_then(|id| world.jobs.get(id)) else { return; };
+        let (kind, pos, item) = (job.kind, job.pos, job.item);
+        match kind {
+            JobKind::Dig => {
+                world.cancel_dig(pos);
+            }
+            JobKind::Chop => {
+                world.cancel_chop(pos);
+            }
+            JobKind::Build => {
+                world.cancel_build(pos);
+            }
+            // The item is still loose, so it will be posted again
+            JobKind::Haul => {
+                if let Some(item) = item {
+                    world.jobs.withdraw_haul(item);
                 }
             }
         }
+        self.drop_job(world);
+        self.task = Task::Idle;
+        self.log(world.tick, format!("Was told to stop the {} job at ({}, {})", kind.name(), pos.x, pos.y));
+    }
+
+    /// Heads somewhere else, finding a new path only if the target moved.
+    pub fn set_target(&mut self, target: WorldPos) {
+        if self.target != Some(target) {
+            self.target = Some(target);
+            self.path.clear();
+        }
+    }
+
+    /// Lets go of the item the creature meant to pick up.
+    pub fn release_items(&self, world: &mut World) {
+        for item in world.items.iter_mut().filter(|item| item.reserved_by == Some(self.id)) {
+            item.reserved_by = None;
+        }
+    }
+
+    /// Whether the creature stands on `target`'s level, closer than `reach` to it.
+    pub(super) fn is_within(&self, target: WorldPos, reach: f32) -> bool {
+        let dx = self.x - target.x as f32;
+        let dy = self.y - target.y as f32;
+        self.z == target.z && dx * dx + dy * dy < reach * reach
+    }
+
+    /// How close the creature must stand to work on its target.
+    fn reach(&self, world: &World) -> f32 {
+        let Some(target) = self.target else { return DIG_REACH; };
+        match self.task {
+            Task::Idle | Task::Dig => DIG_REACH,
+            Task::Eat | Task::Drink => NEED_REACH,
+            Task::Sleep => BED_REACH,
+            Task::Build => BUILD_REACH,
+            Task::Haul => HAUL_REACH,
+            Task::Chop => world
+                .plants
+                .iter()
+                .find(|plant| WorldPos::new(plant.x, plant.y, plant.z) == target)
+                .map_or(DIG_REACH, |plant| plant.chop_reach()),
+        }
+    }
+
+    /// Walks along the cached path to the target, finding one first if there is none.
+    /// A job there is no way to is handed back as unreachable.
+    pub fn move_toward_target(&mut self, world: &mut World) {
+        let Some(target) = self.target else { return; };
+        if self.path.is_empty() {
+            let reach = self.reach(world);
+            if self.is_within(target, reach) {
+                return;
+            }
+            let start = WorldPos::from_f32(self.x, self.y, self.z);
+            match find_path(world, start, target, reach) {
+                Some(path) => self.path = path,
+                None => {
+                    self.log(world.tick, format!("Found no way to ({}, {})", target.x, target.y));
+                    // Put down what was carried and hand the job back; one still on the board
+                    // then waits a while before anyone tries it again
+                    let job = self.job;
+                    self.drop_job(world);
+                    if let Some(id) = job {
+                        world.jobs.give_up(id, world.tick);
+                    }
+                    return;
+                }
+            }
+        }
+        let Some(&next) = self.path.last() else { return; };
+        if !walkable(world, next.x, next.y, next.z) {
+            // Something got in the way; look for another way next frame
+            self.path.clear();
+            return;
+        }
+        // Walk to the cell's centre one axis at a time, so corners are walked around rather than
+        // cut; a step to another level is taken from the centre of the ramp
+        let (centre_x, centre_y) = (next.x as f32 + 0.5, next.y as f32 + 0.5);
+        let dx = centre_x - self.x;
+        let dy = centre_y - self.y;
+        if dx != 0.0 {
+            self.x += dx.clamp(-STEP, STEP);
+        } else {
+            self.y += dy.clamp(-STEP, STEP);
+        }
+        if self.x == centre_x && self.y == centre_y {
+            self.z = next.z;
+            self.path.pop();
+        }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,127 @@
+use crate::items::Item;
+use crate::particle::Particle;
+use crate::world::localmap::change::ChangeCause;
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::world::World;
+use macroquad::rand::gen_range;
+use std::collections::HashSet;
+use super::pathfinding::{walkable, SIDEWAYS};
+use super::{Creature, Skill, Task};
+
+/// How close a creature must stand to pick up an item or build at a site
+pub const BUILD_REACH: f32 = 2.0;
+
+impl Creature {
+    /// Works on a claimed build job: reserves an item of the blueprint's material (from a
+    /// stockpile if there is one), fetches it, carries it to the site and puts the structure
+    /// up once it has worked long enough. A solid structure waits until nobody in `standing`
+    /// (the cells creatures stand on) is in the way, the builder stepping aside if need be.
+    pub fn build_if_close(&mut self, world: &mut World, particles: &mut Vec<Particle>, standing: &HashSet<WorldPos>) {
+        if self.task != Task::Build {
+            return;
+        }
+        let Some(job) = self.job.and_then(|id| world.jobs.get(id)) else {
+            // The plan was taken back
+            self.drop_job(world);
+            return;
+        };
+        let site = job.pos;
+        let Some(&construction) = world.blueprints.get(&site) else {
+            self.drop_job(world);
+            return;
+        };
+        let category = construction.material.category();
+
+        if self.carrying.as_ref().is_none_or(|item| world.category(item) != category) {
+            let reserved = world.items.iter().position(|item| item.reserved_by == Some(self.id));
+            let index = match reserved {
+                Some(index) => index,
+                None => {
+                    let nearest = world
+                        .items
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, item)| world.category(item) == category && item.z == self.z && item.reserved_by.is_none())
+                        .min_by(|(_, a), (_, b)| {
+                            // Items already in a stockpile go first, then the nearest
+                            let key = |item: &Item| (!world.is_stored(item), (item.x - self.x).powi(2) + (item.y - self.y).powi(2));
+                            let ((stored_a, da), (stored_b, db)) = (key(a), key(b));
+                            stored_a.cmp(&stored_b).then(da.total_cmp(&db))
+                        })
+                        .map(|(index, _)| index);
+                    let Some(index) = nearest else {
+                        // Nothing left to build it from; someone may bring more later
+                        self.drop_job(world);
+                        return;
+                    };
+                    world.items[index].reserved_by = Some(self.id);
+                    index
+                }
+            };
+            let item = &world.items[index];
+            // Measured to the item's cell, which is what the creature walks to
+            let cell = WorldPos::from_f32(item.x, item.y, item.z);
+            if self.is_within(cell, BUILD_REACH) {
+                let mut item = world.items.swap_remove(index);
+                item.reserved_by = None;
+                // It won't be needing a stockpile any more
+                world.jobs.withdraw_haul(item.id);
+                if let Some(mut dropped) = self.carrying.replace(item) {
+                    (dropped.x, dropped.y, dropped.z) = (self.x, self.y, self.z);
+                    world.items.push(dropped);
+                }
+                self.set_target(site);
+            } else {
+                self.set_target(cell);
+            }
+            return;
+        }
+
+        self.set_target(site);
+        if !self.is_within(site, BUILD_REACH) {
+            return;
+        }
+        let Some(job) = self.job.and_then(|id| world.jobs.get_mut(id)) else { return; };
+        if job.progress < construction.material.build_time() {
+            job.progress += 1;
+            if gen_range(0, 100) < 10 {
+                particles.push(Particle {
+                    x: site.x as f32 + gen_range(0.0, 1.0),
+                    y: site.y as f32 + gen_range(0.0, 1.0),
+                    dx: gen_range(-0.5, 0.5),
+                    dy: gen_range(-1.0, -0.2),
+                    life: 20,
+                    color: world.materials.color(construction.structure.material(), site.x, site.y),
+                });
+            }
+            return;
+        }
+        // Water may have flooded the site since it was planned
+        let current = world.get_material_at(site.x, site.y, site.z);
+        if !construction.structure.can_build_on(&world.materials, current) {
+            world.cancel_build(site);
+            self.drop_job(world);
+            return;
+        }
+        let material = construction.structure.material();
+        if world.materials.is_solid(material) && standing.contains(&site) {
+            let here = WorldPos::from_f32(self.x, self.y, self.z);
+            if here == site && self.path.is_empty() {
+                let aside = SIDEWAYS
+                    .into_iter()
+                    .map(|(dx, dy)| site.offset(dx, dy, 0))
+                    .find(|cell| walkable(world, cell.x, cell.y, cell.z));
+                self.path.extend(aside);
+            }
+            return;
+        }
+        world.set_material_at(site.x, site.y, site.z, material, ChangeCause::Build);
+        world.constructions.insert(site, construction);
+        world.cancel_build(site);
+        self.finish_work(world.tick, Skill::Construction, format!("Built a {} at ({}, {})", construction.name(), site.x, site.y));
+        self.carrying = None;
+        self.job = None;
+        self.target = None;
+        self.path.clear();
+    }
+}
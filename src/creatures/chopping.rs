@@ -0,0 +1,93 @@
+use crate::particle::Particle;
+use crate::world::localmap::world::World;
+use macroquad::rand::gen_range;
+use super::{Creature, Skill, Task};
+
+impl Creature {
+    /// Fells the designated tree at the target once the creature has worked on it long enough.
+    pub fn chop_if_close(&mut self, world: &mut World, particles: &mut Vec<Particle>) {
+        if self.task != Task::Chop {
+            return;
+        }
+        let Some(target) = self.target else {
+            return;
+        };
+        let (target_x, target_y, z) = (target.x, target.y, target.z);
+        let job = self.job.and_then(|id| world.jobs.get_mut(id));
+        let plant = world
+            .plants
+            .iter_mut()
+            .find(|plant| plant.x == target_x && plant.y == target_y && plant.z == z && plant.chop && plant.can_chop());
+        let (Some(job), Some(plant)) = (job, plant) else {
+            // Someone else felled it, or the designation was lifted; either way the
+            // job has nothing left to do
+            if let Some(id) = self.job.take() {
+                world.jobs.remove(id);
+            }
+            self.target = None;
+            return;
+        };
+        if !self.is_within(target, plant.chop_reach()) {
+            return;
+        }
+        if job.progress < plant.species.chop_time() {
+            job.progress += 1;
+            if gen_range(0, 100) < 10 {
+                particles.push(Particle {
+                    x: target_x as f32 + gen_range(0.0, 1.0),
+                    y: target_y as f32 + gen_range(0.0, 1.0),
+                    dx: gen_range(-0.5, 0.5),
+                    dy: gen_range(-1.0, -0.2),
+                    life: 20,
+                    color: plant.species.color(),
+                });
+            }
+            return;
+        }
+        let wood = plant.fell();
+        if let Some(id) = self.job.take() {
+            world.jobs.remove(id);
+        }
+        for _ in 0..wood {
+            world.drop_item("wood", target_x as f32 + gen_range(-2.0, 2.0), target_y as f32 + gen_range(-2.0, 2.0), z);
+        }
+        self.finish_work(world.tick, Skill::Woodcutting, format!("Felled a tree at ({}, {})", target_x, target_y));
+        self.target = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::registry::{ItemRegistry, ITEMS_PATH};
+    use crate::plants::plant::{Plant, Species};
+    use crate::world::localmap::chunk::Chunk;
+    use crate::world::localmap::material_registry::{MaterialRegistry, MATERIALS_PATH};
+    use crate::world::localmap::position::WorldPos;
+    use macroquad::prelude::BROWN;
+    use std::sync::Arc;
+
+    #[test]
+    fn felled_tree_takes_its_job_off_the_board() {
+        let materials = MaterialRegistry::from_json(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap();
+        let item_kinds = ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap();
+        let mut world = World::new(Arc::new(materials), Arc::new(item_kinds));
+        world.insert_chunk(0, (0, 0), Chunk::new());
+        let pos = WorldPos::new(10, 10, 0);
+        world.plants.push(Plant::new(pos.x, pos.y, pos.z, Species::Oak, 1.0));
+        assert!(world.designate_chop(pos, 4));
+
+        let mut worker = Creature::new(0, 10.0, 12.0, 0, 2.0, BROWN);
+        worker.task = Task::Chop;
+        worker.target = Some(pos);
+        worker.job = world.jobs.claim(worker.id, pos, world.tick, |_| true);
+        assert!(worker.job.is_some());
+
+        // The tree goes before the worker is done with it
+        world.plants.clear();
+        worker.chop_if_close(&mut world, &mut Vec::new());
+        assert_eq!(worker.job, None);
+        assert_eq!(worker.target, None);
+        assert_eq!(world.jobs.iter().count(), 0);
+    }
+}
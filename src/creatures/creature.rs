@@ -1,23 +1,106 @@
+use crate::items::Item;
+use super::needs::Needs;
+use super::profile::{LogEntry, Race, Skills};
+use std::collections::VecDeque;
+use crate::jobs::{JobId, WorkerId};
+use crate::world::localmap::position::WorldPos;
 use macroquad::prelude::*;
 
+pub const MAX_HEALTH: f32 = 100.0;
+
+/// What the creature means to do once it reaches its target
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Task {
+    /// Nothing to do; looking for a job
+    Idle,
+    Dig,
+    Chop,
+    /// Fetch an item and put up the planned construction with it
+    Build,
+    /// Carry an item to a stockpile
+    Haul,
+    /// Walk to reserved food and eat it
+    Eat,
+    /// Walk to water and drink
+    Drink,
+    /// Sleep in a bed if it has one, on the ground otherwise
+    Sleep,
+}
+
+impl Task {
+    /// Every task, in declaration order, so `ALL[t as usize] == t`
+    pub const ALL: [Task; 8] = [
+        Task::Idle,
+        Task::Dig,
+        Task::Chop,
+        Task::Build,
+        Task::Haul,
+        Task::Eat,
+        Task::Drink,
+        Task::Sleep,
+    ];
+
+    /// E.g. "digging"
+    pub fn name(self) -> &'static str {
+        match self {
+            Task::Idle => "idle",
+            Task::Dig => "digging",
+            Task::Chop => "chopping",
+            Task::Build => "building",
+            Task::Haul => "hauling",
+            Task::Eat => "eating",
+            Task::Drink => "drinking",
+            Task::Sleep => "sleeping",
+        }
+    }
+}
+
 pub struct Creature {
+    /// Identifies the creature as a worker on the job board
+    pub id: WorkerId,
+    pub name: String,
+    pub race: Race,
     pub x: f32,
     pub y: f32,
+    /// Level the creature stands on
+    pub z: i32,
     pub size: f32,
     pub color: Color,
-    pub target: Option<(i32, i32)>,
-    pub dig_cooldown: u32,
+    pub target: Option<WorldPos>,
+    /// Cells still to walk through to reach the target, the next one last
+    pub path: Vec<WorldPos>,
+    pub task: Task,
+    /// The job the creature has claimed, if any; its progress is kept on the board
+    pub job: Option<JobId>,
+    /// Item in the creature's hands
+    pub carrying: Option<Item>,
+    pub health: f32,
+    pub needs: Needs,
+    pub skills: Skills,
+    /// What the creature did lately, oldest first
+    pub log: VecDeque<LogEntry>,
 }
 
 impl Creature {
-    pub fn new(x: f32, y: f32, size: f32, color: Color) -> Self {
+    pub fn new(id: WorkerId, x: f32, y: f32, z: i32, size: f32, color: Color) -> Self {
         Self {
+            id,
+            name: format!("Worker {}", id),
+            race: Race::Dwarf,
             x,
             y,
+            z,
             size,
             color,
             target: None,
-            dig_cooldown: 0,
+            path: Vec::new(),
+            task: Task::Idle,
+            job: None,
+            carrying: None,
+            health: MAX_HEALTH,
+            needs: Needs::default(),
+            skills: Skills::default(),
+            log: VecDeque::new(),
         }
     }
 } 
\ No newline at end of file
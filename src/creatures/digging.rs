@@ -1,32 +1,56 @@
 use crate::particle::Particle;
-use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::change::ChangeCause;
+use crate::world::localmap::position::WorldPos;
 use crate::world::localmap::world::World;
 use macroquad::prelude::*;
 use macroquad::rand::gen_range;
-use super::Creature;
+use std::collections::HashMap;
+use super::{Creature, Skill, Task};
+
+/// How close a creature must stand to dig at its target
+pub const DIG_REACH: f32 = 2.0;
+/// Subpixels of a material dug out for each item it drops
+const SUBPIXELS_PER_ITEM: u32 = 8;
 
 impl Creature {
     pub fn dig_if_close(&mut self, world: &mut World, particles: &mut Vec<Particle>) {
-        if let Some((target_x, target_y)) = self.target {
-            let dx = self.x - target_x as f32;
-            let dy = self.y - target_y as f32;
-            let dist_sq = dx * dx + dy * dy;
-            if dist_sq < 4.0 {
-                if self.dig_cooldown > 0 {
-                    self.dig_cooldown -= 1;
+        if self.task != Task::Dig {
+            return;
+        }
+        if let Some(target) = self.target {
+            let (target_x, target_y, z) = (target.x, target.y, target.z);
+            // Harder materials take longer to break
+            let materials = world.materials.clone();
+            let dig_time = materials.get(world.get_material_at(target_x, target_y, z)).dig_time;
+            let Some(job) = self.job.and_then(|id| world.jobs.get_mut(id)) else {
+                // Dug out by someone else, or the designation was taken back
+                self.target = None;
+                self.job = None;
+                return;
+            };
+            if self.is_within(target, DIG_REACH) {
+                if job.progress < dig_time {
+                    job.progress += 1;
                     return;
                 }
-                self.dig_cooldown = 10;
                 let radius = 2;
+                let mut dug: HashMap<&str, u32> = HashMap::new();
                 for dx in -radius..=radius {
                     for dy in -radius..=radius {
                         let px = target_x + dx;
                         let py = target_y + dy;
-                        if let Some(subpixel) = world.get_subpixel_mut(px, py) {
-                            if subpixel.material != TerrainMaterial::Air {
-                                subpixel.material = TerrainMaterial::Dirt;
-                            }
-                            subpixel.dig_target = false;
+                        // Solid material turns into whatever it leaves behind when dug
+                        let material = world.get_material_at(px, py, z);
+                        let props = materials.get(material);
+                        if props.solid
+                            && world.set_material_at(px, py, z, props.leaves, ChangeCause::Dig).is_some()
+                            && let Some(drops) = &props.drops
+                        {
+                            *dug.entry(drops.as_str()).or_insert(0) += 1;
+                        }
+                        // Digging clears every designation it reaches, finishing their jobs
+                        world.cancel_dig(WorldPos::new(px, py, z));
+                        if world.get_subpixel(px, py, z).is_some() {
                             // spawn a particle
                             if gen_range(0, 100) < 10 {
                                 // 10% chance per subpixel
@@ -36,13 +60,23 @@ impl Creature {
                                     dx: gen_range(-0.5, 0.5),
                                     dy: gen_range(-1.0, -0.2),
                                     life: 20,
-                                    color: GREEN,
+                                    color: materials.color(material, px, py),
                                 });
                             }
                         }
                     }
                 }
+                // Every few subpixels of a material make one item of what it drops
+                for (kind, count) in dug {
+                    for _ in 0..(count + SUBPIXELS_PER_ITEM / 2) / SUBPIXELS_PER_ITEM {
+                        let x = target_x as f32 + 0.5 + gen_range(-2.0, 2.0);
+                        let y = target_y as f32 + 0.5 + gen_range(-2.0, 2.0);
+                        world.drop_item(kind, x, y, z);
+                    }
+                }
+                self.finish_work(world.tick, Skill::Mining, format!("Dug out ({}, {})", target_x, target_y));
                 self.target = None;
+                self.job = None;
             }
         }
     }
@@ -1,10 +1,18 @@
 use macroquad::prelude::*;
 use super::Creature;
+use super::creature::MAX_HEALTH;
 
 impl Creature {
     pub fn draw(&self, camera_x: f32, camera_y: f32, zoom: f32) {
         let screen_x = (self.x - camera_x) * zoom;
         let screen_y = (self.y - camera_y) * zoom;
         draw_circle(screen_x, screen_y, self.size * zoom, self.color);
+        // Health bar once hurt
+        if self.health < MAX_HEALTH {
+            let width = self.size * 2.0 * zoom;
+            let top = screen_y - (self.size + 1.0) * zoom;
+            draw_rectangle(screen_x - width / 2.0, top, width, 0.5 * zoom, DARKGRAY);
+            draw_rectangle(screen_x - width / 2.0, top, width * self.health / MAX_HEALTH, 0.5 * zoom, GREEN);
+        }
     }
 } 
\ No newline at end of file
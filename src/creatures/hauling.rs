@@ -0,0 +1,72 @@
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::world::World;
+use super::{Creature, Skill, Task};
+
+/// How close a creature must stand to pick up or put down an item
+pub const HAUL_REACH: f32 = 2.0;
+
+impl Creature {
+    /// Works on a claimed haul job: picks the item up and carries it to an empty cell of a
+    /// stockpile that takes it, looking for another cell if that one filled up meanwhile.
+    pub fn haul_if_close(&mut self, world: &mut World) {
+        if self.task != Task::Haul {
+            return;
+        }
+        let Some(item_id) = self.job.and_then(|id| world.jobs.get(id)).and_then(|job| job.item) else {
+            // Taken off the board, e.g. the stockpile was removed
+            self.drop_job(world);
+            return;
+        };
+        let here = WorldPos::from_f32(self.x, self.y, self.z);
+
+        if self.carrying.as_ref().is_none_or(|item| item.id != item_id) {
+            let Some(item) = world.items.iter().find(|item| item.id == item_id) else {
+                // Used up by someone else
+                self.finish_haul(world);
+                return;
+            };
+            let cell = WorldPos::from_f32(item.x, item.y, item.z);
+            if !self.is_within(cell, HAUL_REACH) {
+                self.set_target(cell);
+                return;
+            }
+            let Some(cell) = world.free_stockpile_cell(item, here) else {
+                // Nowhere to put it after all
+                self.drop_job(world);
+                return;
+            };
+            let Some(mut item) = world.take_item(item_id) else { return; };
+            item.reserved_by = None;
+            self.carrying = Some(item);
+            self.set_target(cell);
+            return;
+        }
+
+        let Some(cell) = self.target else { return; };
+        if !self.is_within(cell, HAUL_REACH) {
+            return;
+        }
+        let Some(item) = self.carrying.as_ref() else { return; };
+        if !world.is_free_stockpile_cell(cell, item) {
+            match world.free_stockpile_cell(item, here) {
+                Some(cell) => self.set_target(cell),
+                None => self.drop_job(world),
+            }
+            return;
+        }
+        if let Some(mut item) = self.carrying.take() {
+            self.finish_work(world.tick, Skill::Hauling, format!("Stored {} at ({}, {})", item.kind, cell.x, cell.y));
+            (item.x, item.y, item.z) = (cell.x as f32 + 0.5, cell.y as f32 + 0.5, cell.z);
+            world.items.push(item);
+        }
+        self.finish_haul(world);
+    }
+
+    fn finish_haul(&mut self, world: &mut World) {
+        if let Some(id) = self.job.take() {
+            world.jobs.remove(id);
+        }
+        self.target = None;
+        self.path.clear();
+    }
+}
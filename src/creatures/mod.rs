@@ -1,8 +1,16 @@
-//! Creature system: struct, AI, digging, and rendering logic.
+//! Creature system: struct, AI, needs, skills, pathfinding, digging, chopping, and rendering logic.
 
 pub mod creature;
 pub mod ai;
 pub mod digging;
+pub mod chopping;
+pub mod building;
+pub mod hauling;
+pub mod needs;
+pub mod profile;
+pub mod pathfinding;
 pub mod draw;
 
-pub use creature::Creature; 
\ No newline at end of file
+pub use creature::{Creature, Task};
+pub use needs::{Need, ThoughtKind};
+pub use profile::{Race, Skill}; 
\ No newline at end of file
@@ -0,0 +1,455 @@
+use crate::construction::Structure;
+use crate::items::{Item, ItemCategory};
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::world::{World, SURFACE_Z};
+use std::collections::HashSet;
+use super::{Creature, Task};
+
+/// How full a need is when fully satisfied
+pub const MAX_NEED: f32 = 100.0;
+/// Below this a need is urgent enough to drop work for
+pub const URGENT_NEED: f32 = 25.0;
+/// How close a creature must stand to food or water to eat or drink it
+pub const NEED_REACH: f32 = 2.0;
+/// How close a creature must be to its bed to sleep in it
+pub const BED_REACH: f32 = 1.0;
+/// How far a creature looks for water
+const WATER_SEARCH_RADIUS: i32 = 48;
+/// How far a creature with no food around looks for a bush in fruit
+const FORAGE_RADIUS: i32 = 64;
+/// Ticks before looking again after finding nothing to eat, drink or reach
+const SEARCH_RETRY: u64 = 300;
+/// Ticks of sleep to go from exhausted to fully rested
+const SLEEP_TIME: f32 = 60.0 * 30.0;
+/// Share of the way to its target the mood moves each tick
+const MOOD_EASE: f32 = 0.002;
+/// Ticks between looks at a creature's surroundings
+const SURROUNDINGS_INTERVAL: u64 = 60;
+/// How far around itself a creature takes in its surroundings, in subpixels
+const SURROUNDINGS_RADIUS: i32 = 12;
+/// Mood lost for living below the surface
+const UNDERGROUND_MOOD: f32 = -5.0;
+/// Mood for each grown plant nearby, and the most plants can add
+const PLANT_MOOD: f32 = 1.0;
+const PLANTS_MOOD_MAX: f32 = 6.0;
+/// Mood for having water in view
+const WATER_MOOD: f32 = 3.0;
+/// Mood for each built subpixel nearby, and the most constructions can add
+const CONSTRUCTION_MOOD: f32 = 0.25;
+const CONSTRUCTIONS_MOOD_MAX: f32 = 5.0;
+/// Mood lost for each item lying about outside a stockpile, and the most clutter can take
+const CLUTTER_MOOD: f32 = -1.0;
+const CLUTTER_MOOD_MIN: f32 = -8.0;
+
+/// Something a creature has to see to now and then, or grow unhappy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Need {
+    Hunger,
+    Thirst,
+    Rest,
+}
+
+impl Need {
+    pub const ALL: [Need; 3] = [Need::Hunger, Need::Thirst, Need::Rest];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Need::Hunger => "hunger",
+            Need::Thirst => "thirst",
+            Need::Rest => "rest",
+        }
+    }
+
+    /// Ticks a fully satisfied need lasts before it runs out
+    fn lasts(self) -> f32 {
+        match self {
+            Need::Hunger => 60.0 * 60.0 * 6.0,
+            Need::Thirst => 60.0 * 60.0 * 4.0,
+            Need::Rest => 60.0 * 60.0 * 10.0,
+        }
+    }
+}
+
+/// Events a creature keeps thinking about for a while, for better or worse
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThoughtKind {
+    Ate,
+    Drank,
+    SleptInBed,
+    SleptOnGround,
+    Hurt,
+    SawDeath,
+}
+
+impl ThoughtKind {
+    /// Every kind, in declaration order, so `ALL[k as usize] == k`
+    pub const ALL: [ThoughtKind; 6] = [
+        ThoughtKind::Ate,
+        ThoughtKind::Drank,
+        ThoughtKind::SleptInBed,
+        ThoughtKind::SleptOnGround,
+        ThoughtKind::Hurt,
+        ThoughtKind::SawDeath,
+    ];
+
+    /// E.g. "ate a meal", to go after the creature's name
+    pub fn describe(self) -> &'static str {
+        match self {
+            ThoughtKind::Ate => "ate a meal",
+            ThoughtKind::Drank => "had a drink",
+            ThoughtKind::SleptInBed => "slept in a bed",
+            ThoughtKind::SleptOnGround => "slept on the ground",
+            ThoughtKind::Hurt => "was hurt",
+            ThoughtKind::SawDeath => "saw someone die",
+        }
+    }
+
+    /// How much the thought lifts or lowers the mood while it lasts
+    pub fn mood(self) -> f32 {
+        match self {
+            ThoughtKind::Ate => 5.0,
+            ThoughtKind::Drank => 3.0,
+            ThoughtKind::SleptInBed => 10.0,
+            ThoughtKind::SleptOnGround => -10.0,
+            ThoughtKind::Hurt => -15.0,
+            ThoughtKind::SawDeath => -25.0,
+        }
+    }
+
+    /// Ticks the thought lasts
+    fn duration(self) -> u64 {
+        match self {
+            ThoughtKind::Ate => 60 * 60 * 2,
+            ThoughtKind::Drank => 60 * 60,
+            ThoughtKind::SleptInBed | ThoughtKind::SleptOnGround => 60 * 60 * 4,
+            ThoughtKind::Hurt => 60 * 60 * 2,
+            ThoughtKind::SawDeath => 60 * 60 * 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Thought {
+    pub kind: ThoughtKind,
+    /// Tick the thought is forgotten at
+    pub until: u64,
+}
+
+/// How a creature is doing: each need from 0 (desperate) to `MAX_NEED`, and its mood.
+pub struct Needs {
+    pub hunger: f32,
+    pub thirst: f32,
+    pub rest: f32,
+    /// From -100 (miserable) to 100 (ecstatic); drifts toward what the needs, the
+    /// surroundings and recent thoughts make it
+    pub mood: f32,
+    /// Recent events still weighing on the mood, at most one of each kind
+    pub thoughts: Vec<Thought>,
+    /// What the creature makes of where it is, looked at every `SURROUNDINGS_INTERVAL` ticks
+    pub surroundings: f32,
+    /// Tick before which the creature won't look for food or water again after finding none
+    pub retry_at: u64,
+}
+
+impl Default for Needs {
+    fn default() -> Self {
+        Self {
+            hunger: MAX_NEED,
+            thirst: MAX_NEED,
+            rest: MAX_NEED,
+            mood: 0.0,
+            thoughts: Vec::new(),
+            surroundings: 0.0,
+            retry_at: 0,
+        }
+    }
+}
+
+impl Needs {
+    pub fn level(&self, need: Need) -> f32 {
+        match need {
+            Need::Hunger => self.hunger,
+            Need::Thirst => self.thirst,
+            Need::Rest => self.rest,
+        }
+    }
+
+    fn level_mut(&mut self, need: Need) -> &mut f32 {
+        match need {
+            Need::Hunger => &mut self.hunger,
+            Need::Thirst => &mut self.thirst,
+            Need::Rest => &mut self.rest,
+        }
+    }
+
+    /// The lowest need, if it is low enough to drop work for
+    pub fn urgent(&self) -> Option<Need> {
+        Need::ALL
+            .into_iter()
+            .filter(|&need| self.level(need) < URGENT_NEED)
+            .min_by(|&a, &b| self.level(a).total_cmp(&self.level(b)))
+    }
+
+    /// Remembers an event from `tick` on; thinking it again only makes it last longer.
+    pub fn think(&mut self, kind: ThoughtKind, tick: u64) {
+        self.thoughts.retain(|thought| thought.kind != kind);
+        self.thoughts.push(Thought { kind, until: tick + kind.duration() });
+    }
+
+    /// The mood the creature is heading for
+    pub fn mood_target(&self) -> f32 {
+        let thoughts: f32 = self.thoughts.iter().map(|thought| thought.kind.mood()).sum();
+        let needs: f32 = Need::ALL
+            .into_iter()
+            .map(|need| match self.level(need) {
+                level if level <= 0.0 => -30.0,
+                level if level < URGENT_NEED => -15.0,
+                _ => 0.0,
+            })
+            .sum();
+        (thoughts + needs + self.surroundings).clamp(-100.0, 100.0)
+    }
+
+    pub fn mood_name(&self) -> &'static str {
+        match self.mood {
+            mood if mood >= 50.0 => "ecstatic",
+            mood if mood >= 20.0 => "happy",
+            mood if mood > -20.0 => "content",
+            mood if mood > -50.0 => "unhappy",
+            _ => "miserable",
+        }
+    }
+
+    /// Runs the needs down by a tick (all but rest while asleep), forgets old thoughts and
+    /// moves the mood toward its target.
+    fn update(&mut self, tick: u64, asleep: bool) {
+        for need in Need::ALL {
+            if need == Need::Rest && asleep {
+                continue;
+            }
+            let level = self.level_mut(need);
+            *level = (*level - MAX_NEED / need.lasts()).max(0.0);
+        }
+        self.thoughts.retain(|thought| thought.until > tick);
+        self.mood += (self.mood_target() - self.mood) * MOOD_EASE;
+    }
+}
+
+impl Task {
+    /// Whether the task sees to a need rather than a job
+    pub fn is_need(self) -> bool {
+        matches!(self, Task::Eat | Task::Drink | Task::Sleep)
+    }
+}
+
+impl Creature {
+    /// Lets the needs run down and sees to them: an urgent need makes the creature drop its
+    /// job to eat the nearest food (stockpiled food first) or else berries off a bush, drink at the nearest water, or
+    /// sleep in a free bed, or on the ground if there is none. `beds_taken` holds the beds
+    /// other creatures are sleeping in or heading for.
+    pub fn tend_needs(&mut self, world: &mut World, beds_taken: &mut HashSet<WorldPos>) {
+        let asleep = self.is_asleep();
+        if (world.tick + self.id as u64).is_multiple_of(SURROUNDINGS_INTERVAL) {
+            self.needs.surroundings = self.surroundings_mood(world);
+        }
+        self.needs.update(world.tick, asleep);
+        match self.task {
+            Task::Eat => self.eat(world),
+            Task::Drink => self.drink(world),
+            Task::Sleep => self.sleep(world),
+            _ => {
+                if world.tick >= self.needs.retry_at
+                    && let Some(need) = self.needs.urgent()
+                {
+                    self.start_tending(need, world, beds_taken);
+                }
+            }
+        }
+    }
+
+    /// What the creature makes of where it is: grown plants, water and constructions nearby
+    /// cheer it up, while items left lying about and living below the surface bring it down.
+    fn surroundings_mood(&self, world: &World) -> f32 {
+        let here = WorldPos::from_f32(self.x, self.y, self.z);
+        let near = |pos: WorldPos| pos.z == here.z && pos.distance_sq(here) <= SURROUNDINGS_RADIUS * SURROUNDINGS_RADIUS;
+        let plants = world
+            .plants
+            .iter()
+            .filter(|plant| !plant.is_felled() && plant.growth >= 0.5 && near(WorldPos::new(plant.x, plant.y, plant.z)))
+            .count();
+        let constructions = world.constructions.keys().filter(|&&pos| near(pos)).count();
+        let clutter = world
+            .items
+            .iter()
+            .filter(|item| near(WorldPos::from_f32(item.x, item.y, item.z)) && !world.is_stored(item))
+            .count();
+        let water = world
+            .find_nearest(here, SURROUNDINGS_RADIUS, |_, subpixel| world.materials.is_drinkable(subpixel.material))
+            .is_some();
+
+        let mut mood = (plants as f32 * PLANT_MOOD).min(PLANTS_MOOD_MAX)
+            + (constructions as f32 * CONSTRUCTION_MOOD).min(CONSTRUCTIONS_MOOD_MAX)
+            + (clutter as f32 * CLUTTER_MOOD).max(CLUTTER_MOOD_MIN);
+        if water {
+            mood += WATER_MOOD;
+        }
+        if self.z < SURFACE_Z {
+            mood += UNDERGROUND_MOOD;
+        }
+        mood
+    }
+
+    /// Sleeping where it lies, or in the bed it reached
+    pub fn is_asleep(&self) -> bool {
+        self.task == Task::Sleep && self.target.is_none_or(|target| self.is_within(target, BED_REACH))
+    }
+
+    fn start_tending(&mut self, need: Need, world: &mut World, beds_taken: &mut HashSet<WorldPos>) {
+        // Hand the job back, and whatever was carried for it; with nothing to eat or drink
+        // around, the creature takes up work again next
+        self.drop_job(world);
+        let here = WorldPos::from_f32(self.x, self.y, self.z);
+        let target = match need {
+            Need::Hunger => match self.find_food(world) {
+                Some(index) => {
+                    let food = &mut world.items[index];
+                    food.reserved_by = Some(self.id);
+                    Some(WorldPos::from_f32(food.x, food.y, food.z))
+                }
+                // Failing food lying about, berries off the nearest bush in fruit
+                None => {
+                    let bush = world
+                        .plants
+                        .iter()
+                        .filter(|plant| plant.has_fruit())
+                        .map(|plant| WorldPos::new(plant.x, plant.y, plant.z))
+                        .filter(|&pos| pos.z == self.z && pos.distance_sq(here) <= FORAGE_RADIUS * FORAGE_RADIUS)
+                        .min_by_key(|&pos| pos.distance_sq(here));
+                    let Some(bush) = bush else {
+                        self.stop_tending(world, true);
+                        return;
+                    };
+                    Some(bush)
+                }
+            },
+            Need::Thirst => {
+                let water = world.find_nearest(here, WATER_SEARCH_RADIUS, |_, subpixel| world.materials.is_drinkable(subpixel.material));
+                let Some(water) = water else {
+                    self.stop_tending(world, true);
+                    return;
+                };
+                Some(water)
+            }
+            // Tired enough to lie down anywhere, but a free bed is better
+            Need::Rest => {
+                let bed = world
+                    .constructions
+                    .iter()
+                    .filter(|&(&pos, construction)| {
+                        construction.structure == Structure::Bed && pos.z == self.z && !beds_taken.contains(&pos)
+                    })
+                    .map(|(&pos, _)| pos)
+                    .min_by_key(|pos| pos.distance_sq(here));
+                if let Some(bed) = bed {
+                    beds_taken.insert(bed);
+                }
+                bed
+            }
+        };
+        self.task = match need {
+            Need::Hunger => Task::Eat,
+            Need::Thirst => Task::Drink,
+            Need::Rest => Task::Sleep,
+        };
+        self.target = target;
+    }
+
+    /// Index of the food the creature would eat: stockpiled food first, then the nearest.
+    fn find_food(&self, world: &World) -> Option<usize> {
+        world
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| world.category(item) == ItemCategory::Food && item.z == self.z && item.reserved_by.is_none())
+            .min_by(|(_, a), (_, b)| {
+                let key = |item: &Item| (!world.is_stored(item), (item.x - self.x).powi(2) + (item.y - self.y).powi(2));
+                let ((stored_a, da), (stored_b, db)) = (key(a), key(b));
+                stored_a.cmp(&stored_b).then(da.total_cmp(&db))
+            })
+            .map(|(index, _)| index)
+    }
+
+    fn eat(&mut self, world: &mut World) {
+        let food = world.items.iter().position(|item| item.reserved_by == Some(self.id));
+        let bush = self.target.and_then(|target| {
+            world.plants.iter().position(|plant| WorldPos::new(plant.x, plant.y, plant.z) == target && plant.has_fruit())
+        });
+        let Some(target) = self.target.filter(|_| food.is_some() || bush.is_some()) else {
+            // Eaten or picked by someone else, or there was no way to it
+            self.stop_tending(world, true);
+            return;
+        };
+        if !self.is_within(target, NEED_REACH) {
+            return;
+        }
+        let meal = if let Some(index) = food {
+            let food = world.items.swap_remove(index);
+            world.jobs.withdraw_haul(food.id);
+            food.kind
+        } else if let Some(index) = bush {
+            world.plants[index].pick();
+            "berries off a bush".to_string()
+        } else {
+            return;
+        };
+        self.log(world.tick, format!("Ate {}", meal));
+        self.needs.hunger = MAX_NEED;
+        self.needs.think(ThoughtKind::Ate, world.tick);
+        self.stop_tending(world, false);
+    }
+
+    fn drink(&mut self, world: &mut World) {
+        let Some(target) = self.target else {
+            // No way to the water
+            self.stop_tending(world, true);
+            return;
+        };
+        if !self.is_within(target, NEED_REACH) {
+            return;
+        }
+        self.needs.thirst = MAX_NEED;
+        self.needs.think(ThoughtKind::Drank, world.tick);
+        self.log(world.tick, "Had a drink".to_string());
+        self.stop_tending(world, false);
+    }
+
+    fn sleep(&mut self, world: &mut World) {
+        if !self.is_asleep() {
+            return;
+        }
+        self.needs.rest += MAX_NEED / SLEEP_TIME;
+        if self.needs.rest < MAX_NEED {
+            return;
+        }
+        self.needs.rest = MAX_NEED;
+        // The bed may have been dug out from under it meanwhile
+        let in_bed = self.target.is_some_and(|bed| world.get_material_at(bed.x, bed.y, bed.z) == TerrainMaterial::Bed);
+        let thought = if in_bed { ThoughtKind::SleptInBed } else { ThoughtKind::SleptOnGround };
+        self.needs.think(thought, world.tick);
+        self.log(world.tick, if in_bed { "Slept in a bed" } else { "Slept on the ground" }.to_string());
+        self.stop_tending(world, false);
+    }
+
+    /// Goes back to looking for work; after a failed attempt, the need waits a while before
+    /// the creature tries again.
+    fn stop_tending(&mut self, world: &mut World, failed: bool) {
+        if failed {
+            self.needs.retry_at = world.tick + SEARCH_RETRY;
+        }
+        self.release_items(world);
+        self.task = Task::Idle;
+        self.target = None;
+        self.path.clear();
+    }
+}
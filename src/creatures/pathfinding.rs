@@ -0,0 +1,237 @@
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::position::{ChunkPos, WorldPos};
+use crate::world::localmap::world::World;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Cells A* may expand before deciding a target can't be reached
+const SEARCH_BUDGET: usize = 20_000;
+/// How far beyond the box around start and goal a path may wander
+const WINDOW_MARGIN: i32 = 48;
+/// How many levels above and below start and goal a path may wander
+const WINDOW_Z_MARGIN: i32 = 2;
+
+pub const SIDEWAYS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+/// Every step a path can take: sideways on a level, or up and down at a ramp
+const STEPS: [(i32, i32, i32); 6] = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+/// Whether a creature can stand on the subpixel; unloaded ground never counts.
+pub fn walkable(world: &World, x: i32, y: i32, z: i32) -> bool {
+    world
+        .get_subpixel(x, y, z)
+        .is_some_and(|subpixel| world.materials.is_walkable(subpixel.material))
+}
+
+/// Cell state in a search window
+const UNKNOWN: u8 = 0;
+const WALKABLE: u8 = 1;
+const BLOCKED: u8 = 2;
+/// Walkable, and leads up to the level above
+const RAMP: u8 = 3;
+
+/// The part of the levels around start and goal a search looks at, with per-cell state
+/// in flat arrays.
+struct Window<'a> {
+    world: &'a World,
+    x0: i32,
+    y0: i32,
+    z0: i32,
+    width: usize,
+    height: usize,
+    depth: usize,
+    cells: Vec<u8>,
+    steps: Vec<u32>,
+    /// Index into `STEPS` of the step that reached each cell
+    came_by: Vec<u8>,
+    /// The chunk looked at last; neighbouring cells are nearly always in the same one
+    last_chunk: Option<(ChunkPos, Option<&'a Chunk>)>,
+}
+
+impl<'a> Window<'a> {
+    fn new(world: &'a World, start: WorldPos, goal: WorldPos) -> Self {
+        let x0 = start.x.min(goal.x) - WINDOW_MARGIN;
+        let y0 = start.y.min(goal.y) - WINDOW_MARGIN;
+        let z0 = start.z.min(goal.z) - WINDOW_Z_MARGIN;
+        let width = (start.x.max(goal.x) + WINDOW_MARGIN - x0 + 1) as usize;
+        let height = (start.y.max(goal.y) + WINDOW_MARGIN - y0 + 1) as usize;
+        let depth = (start.z.max(goal.z) + WINDOW_Z_MARGIN - z0 + 1) as usize;
+        let size = width * height * depth;
+        Self {
+            world,
+            x0,
+            y0,
+            z0,
+            width,
+            height,
+            depth,
+            cells: vec![UNKNOWN; size],
+            steps: vec![u32::MAX; size],
+            came_by: vec![0; size],
+            last_chunk: None,
+        }
+    }
+
+    fn index(&self, pos: WorldPos) -> Option<usize> {
+        let (lx, ly, lz) = (pos.x - self.x0, pos.y - self.y0, pos.z - self.z0);
+        (lx >= 0 && ly >= 0 && lz >= 0 && (lx as usize) < self.width && (ly as usize) < self.height && (lz as usize) < self.depth)
+            .then(|| (lz as usize * self.height + ly as usize) * self.width + lx as usize)
+    }
+
+    fn cell(&mut self, index: usize, pos: WorldPos) -> u8 {
+        if self.cells[index] == UNKNOWN {
+            let chunk_pos = pos.chunk();
+            let chunk = match self.last_chunk {
+                Some((cached, chunk)) if cached == chunk_pos => chunk,
+                _ => {
+                    let chunk = self.world.chunk_at(chunk_pos);
+                    self.last_chunk = Some((chunk_pos, chunk));
+                    chunk
+                }
+            };
+            let (lx, ly) = pos.in_chunk();
+            let materials = &self.world.materials;
+            self.cells[index] = match chunk.map(|chunk| chunk.material(lx, ly)) {
+                Some(material) if materials.leads_up(material) && materials.is_walkable(material) => RAMP,
+                Some(material) if materials.is_walkable(material) => WALKABLE,
+                _ => BLOCKED,
+            };
+        }
+        self.cells[index]
+    }
+
+    /// Whether a creature at `from` can take the step to `to`: onto walkable ground on the
+    /// same level, or between a ramp and the cell right above it.
+    fn can_step(&mut self, from: (usize, WorldPos), to: (usize, WorldPos)) -> bool {
+        let target = self.cell(to.0, to.1);
+        match to.1.z - from.1.z {
+            0 => target != BLOCKED,
+            1 => target != BLOCKED && self.cell(from.0, from.1) == RAMP,
+            _ => target == RAMP,
+        }
+    }
+}
+
+/// Finds a way over walkable subpixels to any cell on `goal`'s level whose centre is
+/// closer than `reach` to `goal`, with A*, climbing ramps up to a few levels beyond those
+/// of start and goal. Returns the cells to walk through with the next one last, or `None` if there is
+/// no way there within the search budget.
+pub fn find_path(world: &World, start: WorldPos, goal: WorldPos, reach: f32) -> Option<Vec<WorldPos>> {
+    let arrived = |pos: WorldPos| {
+        let (dx, dy) = (pos.x as f32 + 0.5 - goal.x as f32, pos.y as f32 + 0.5 - goal.y as f32);
+        pos.z == goal.z && dx * dx + dy * dy < reach * reach
+    };
+    // Any cell in reach is at most this many steps from the goal, which keeps the estimate admissible
+    let slack = (reach * std::f32::consts::SQRT_2).floor() as i32 + 1;
+    let estimate = |pos: WorldPos| {
+        (((pos.x - goal.x).abs() + (pos.y - goal.y).abs() - slack).max(0) + (pos.z - goal.z).abs()) as u32
+    };
+
+    let mut window = Window::new(world, start, goal);
+    let start_index = window.index(start)?;
+    window.steps[start_index] = 0;
+    let mut open = BinaryHeap::new();
+    // Ties go to the cell nearest the goal, so open ground doesn't flood the search
+    open.push(Reverse((estimate(start), estimate(start), (start.z, start.x, start.y))));
+    let mut expanded = 0;
+    while let Some(Reverse((total, left, (z, x, y)))) = open.pop() {
+        let cell = WorldPos::new(x, y, z);
+        let index = window.index(cell)?;
+        let steps = window.steps[index];
+        if total - left > steps {
+            continue; // Already reached more cheaply
+        }
+        if arrived(cell) {
+            let mut path = Vec::new();
+            let mut at = cell;
+            while at != start {
+                path.push(at);
+                let (dx, dy, dz) = STEPS[window.came_by[window.index(at)?] as usize];
+                at = at.offset(-dx, -dy, -dz);
+            }
+            return Some(path);
+        }
+        expanded += 1;
+        if expanded > SEARCH_BUDGET {
+            return None;
+        }
+        for (direction, (dx, dy, dz)) in STEPS.into_iter().enumerate() {
+            let next = cell.offset(dx, dy, dz);
+            let Some(next_index) = window.index(next) else { continue; };
+            if steps + 1 >= window.steps[next_index] || !window.can_step((index, cell), (next_index, next)) {
+                continue;
+            }
+            window.steps[next_index] = steps + 1;
+            window.came_by[next_index] = direction as u8;
+            let left = estimate(next);
+            open.push(Reverse((steps + 1 + left, left, (next.z, next.x, next.y))));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::registry::{ItemRegistry, ITEMS_PATH};
+    use crate::world::localmap::change::ChangeCause;
+    use crate::world::localmap::chunk::CHUNK_SIZE;
+    use crate::world::localmap::tile::TILE_SIZE;
+    use crate::world::localmap::material_registry::{MaterialRegistry, MATERIALS_PATH};
+    use crate::world::localmap::terrain_material::TerrainMaterial;
+    use std::sync::Arc;
+
+    /// One open chunk on each of levels 0 and 1.
+    fn world() -> World {
+        let materials = MaterialRegistry::from_json(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap();
+        let item_kinds = ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap();
+        let mut world = World::new(Arc::new(materials), Arc::new(item_kinds));
+        for z in 0..=1 {
+            world.insert_chunk(z, (0, 0), Chunk::new());
+        }
+        world
+    }
+
+    #[test]
+    fn walks_around_a_wall() {
+        let mut world = world();
+        for y in 0..6 {
+            world.set_material_at(5, y, 0, TerrainMaterial::Rock, ChangeCause::Paint);
+        }
+        let path = find_path(&world, WorldPos::new(2, 2, 0), WorldPos::new(8, 2, 0), 1.0).unwrap();
+        assert!(path.iter().all(|pos| pos.z == 0 && walkable(&world, pos.x, pos.y, 0)));
+        assert!(path.iter().any(|pos| pos.y >= 6));
+    }
+
+    #[test]
+    fn levels_without_a_ramp_are_not_connected() {
+        let world = world();
+        assert_eq!(find_path(&world, WorldPos::new(2, 2, 0), WorldPos::new(8, 2, 1), 1.0), None);
+    }
+
+    #[test]
+    fn climbs_over_a_wall_when_the_only_way_is_the_level_above() {
+        let mut world = world();
+        for y in 0..(CHUNK_SIZE * TILE_SIZE) as i32 {
+            world.set_material_at(5, y, 0, TerrainMaterial::Rock, ChangeCause::Paint);
+        }
+        world.set_material_at(3, 2, 0, TerrainMaterial::Ramp, ChangeCause::Paint);
+        world.set_material_at(7, 2, 0, TerrainMaterial::Ramp, ChangeCause::Paint);
+        let path = find_path(&world, WorldPos::new(2, 2, 0), WorldPos::new(8, 2, 0), 1.0).unwrap();
+        assert!(path.iter().any(|pos| pos.z == 1));
+        assert_eq!(path[0].z, 0);
+    }
+
+    #[test]
+    fn ramp_leads_up_and_down() {
+        let mut world = world();
+        world.set_material_at(5, 5, 0, TerrainMaterial::Ramp, ChangeCause::Paint);
+        let up = find_path(&world, WorldPos::new(2, 2, 0), WorldPos::new(8, 2, 1), 1.0).unwrap();
+        let climb = up.iter().position(|pos| pos.z == 0).unwrap();
+        assert_eq!(up[climb - 1], WorldPos::new(5, 5, 1));
+        assert_eq!(up[climb], WorldPos::new(5, 5, 0));
+
+        let down = find_path(&world, WorldPos::new(8, 2, 1), WorldPos::new(2, 2, 0), 1.0).unwrap();
+        assert!(down.contains(&WorldPos::new(5, 5, 0)));
+        assert_eq!(down[0].z, 0);
+    }
+}
@@ -0,0 +1,111 @@
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+use super::Creature;
+
+/// Entries kept in a creature's activity log
+pub const LOG_LENGTH: usize = 8;
+/// Experience needed for each skill level
+const XP_PER_LEVEL: u32 = 10;
+
+const NAME_STARTS: [&str; 12] = ["Ur", "Ka", "Dor", "Mo", "Bel", "Tho", "Ar", "Lo", "Ze", "Fi", "Gro", "Ses"];
+const NAME_ENDS: [&str; 10] = ["ist", "rin", "mek", "dan", "gor", "ith", "sa", "vol", "ra", "bek"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Race {
+    Dwarf,
+    Human,
+    Elf,
+}
+
+impl Race {
+    /// Every race, in declaration order, so `ALL[r as usize] == r`
+    pub const ALL: [Race; 3] = [Race::Dwarf, Race::Human, Race::Elf];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Race::Dwarf => "dwarf",
+            Race::Human => "human",
+            Race::Elf => "elf",
+        }
+    }
+
+    /// Colour creatures of the race are drawn in
+    pub fn color(self) -> Color {
+        match self {
+            Race::Dwarf => RED,
+            Race::Human => Color::from_rgba(70, 110, 220, 255),
+            Race::Elf => Color::from_rgba(60, 170, 80, 255),
+        }
+    }
+
+    pub fn random() -> Self {
+        Self::ALL[gen_range(0, Self::ALL.len())]
+    }
+}
+
+/// A name made of two random syllables, e.g. "Dormek"
+pub fn random_name() -> String {
+    let start = NAME_STARTS[gen_range(0, NAME_STARTS.len())];
+    let end = NAME_ENDS[gen_range(0, NAME_ENDS.len())];
+    format!("{}{}", start, end)
+}
+
+/// What a creature gets better at by finishing jobs of the matching kind
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Skill {
+    Mining,
+    Woodcutting,
+    Construction,
+    Hauling,
+}
+
+impl Skill {
+    pub const ALL: [Skill; 4] = [Skill::Mining, Skill::Woodcutting, Skill::Construction, Skill::Hauling];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Skill::Mining => "mining",
+            Skill::Woodcutting => "woodcutting",
+            Skill::Construction => "construction",
+            Skill::Hauling => "hauling",
+        }
+    }
+}
+
+/// Experience in each skill, in `Skill::ALL` order
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Skills {
+    pub xp: [u32; Skill::ALL.len()],
+}
+
+impl Skills {
+    pub fn train(&mut self, skill: Skill) {
+        self.xp[skill as usize] += 1;
+    }
+
+    pub fn level(&self, skill: Skill) -> u32 {
+        self.xp[skill as usize] / XP_PER_LEVEL
+    }
+}
+
+/// Something the creature did, for its activity log
+pub struct LogEntry {
+    pub tick: u64,
+    pub text: String,
+}
+
+impl Creature {
+    /// Notes what the creature did at `tick`, forgetting the oldest entry once the log is full.
+    pub fn log(&mut self, tick: u64, text: String) {
+        if self.log.len() == LOG_LENGTH {
+            self.log.pop_front();
+        }
+        self.log.push_back(LogEntry { tick, text });
+    }
+
+    /// Finishing a job: more experience, and a line in the log
+    pub fn finish_work(&mut self, tick: u64, skill: Skill, text: String) {
+        self.skills.train(skill);
+        self.log(tick, text);
+    }
+}
@@ -1,32 +1,55 @@
 use crate::creatures::Creature;
 use crate::game::state::GameState;
 use crate::gui::GuiState;
+use crate::jobs::JobKind;
 use crate::particle::Particle;
-use crate::player::actions::count_dig_jobs;
 use crate::renderer::camera::Camera;
 use crate::renderer::local_map_renderer::LocalMapRenderer;
 use crate::renderer::world_map_renderer::WorldMapRenderer;
-use crate::world::localmap::world::World;
+use crate::renderer::region_map_renderer::RegionMapRenderer;
+use crate::world::regionmap::RegionMap;
+use crate::worldgen::regionmap::RegionMapGenerator;
+use crate::items::ItemRegistry;
+use crate::world::localmap::material_registry::MaterialRegistry;
+use crate::world::localmap::world::{World, SURFACE_Z};
 use crate::world::worldmap::world_map::WorldMap;
+use crate::world::worldmap::city::SettlementTier;
 use crate::worldgen::localmap::builder::WorldGeneratorBuilder;
+use crate::worldgen::localmap::generator::WorldGenerator;
+use crate::worldgen::localmap::tile_info::WorldTileInfo;
+use crate::world::localmap::region_file::Stored;
+use crate::worldgen::localmap::streaming::ChunkStreamer;
+use crate::worldgen::localmap::vegetation::place_plants;
 use crate::worldgen::worldmap::WorldMapGenerator;
 use crate::gui::windows::city_info::portraits::CivPortraits;
 use crate::gui::windows::window_manager::WindowManager;
 use crate::input::manager::InputManager;
 use macroquad::prelude::*;
+use std::sync::Arc;
 
 use crate::game::views::GameView;
-use crate::game::input::{RenderMode, InputHandler};
-use crate::game::entities::{creatures, particles};
+use crate::game::input::{RenderMode, InputContext, InputHandler};
+use crate::game::input::local_map::center_camera;
+use crate::game::input::local_map::selection::handle_worker_selection;
+use crate::game::entities::{creatures, particles, plants};
+use crate::game::save;
 
 pub struct Game {
     world: World,
+    /// Generates and unloads chunks of the local map as the view moves
+    streamer: Option<ChunkStreamer>,
+    /// World map tile the local map was generated from
+    embark: (usize, usize),
+    materials: Arc<MaterialRegistry>,
+    item_kinds: Arc<ItemRegistry>,
     local_map_renderer: LocalMapRenderer,
     world_map_renderer: WorldMapRenderer,
     creatures: Vec<Creature>,
     particles: Vec<Particle>,
     gui: GuiState,
     world_map: WorldMap,
+    region_map: Option<RegionMap>,
+    region_map_renderer: RegionMapRenderer,
     render_mode: RenderMode,
     world_map_camera: Camera,
     input_handler: InputHandler,
@@ -50,15 +73,25 @@ impl Game {
         );
         let world_map = world_map_gen.generate();
         let portraits = Some(CivPortraits::load().await);
+        let materials = Arc::new(
+            MaterialRegistry::load().await.unwrap_or_else(|e| panic!("Failed to load materials: {}", e)),
+        );
+        let item_kinds = Arc::new(ItemRegistry::load().await.unwrap_or_else(|e| panic!("Failed to load items: {}", e)));
 
         Self {
-            world: World::new(),
+            world: World::new(materials.clone(), item_kinds.clone()),
+            streamer: None,
+            embark: (0, 0),
+            materials,
+            item_kinds,
             local_map_renderer: LocalMapRenderer::default(),
             world_map_renderer,
             creatures: Vec::new(),
             particles: Vec::new(),
             gui,
             world_map,
+            region_map: None,
+            region_map_renderer: RegionMapRenderer::new(),
             render_mode: RenderMode::WorldMap, // Start in world map mode
             world_map_camera: Camera::default(),
             input_handler: InputHandler::new(),
@@ -70,26 +103,146 @@ impl Game {
     }
 
     pub fn init(&mut self) {
-        // Generate the world
-        let generator = WorldGeneratorBuilder::new(42).build();
+        // Start on the first capital, or the map centre if there are no cities
+        let start = self.world_map.cities.iter()
+            .find(|c| c.tier == SettlementTier::Capital)
+            .or(self.world_map.cities.first())
+            .map(|c| (c.x, c.y))
+            .unwrap_or((self.world_map.width / 2, self.world_map.height / 2));
+        self.generate_local_map(start);
+    }
+
+    /// Generator and world data for the local map of a world map tile
+    fn local_generator(&self, (x, y): (usize, usize), seed: u32) -> (WorldGenerator, WorldTileInfo) {
+        let tile = WorldTileInfo::from_world_map(&self.world_map, x, y);
+        let generator = WorldGeneratorBuilder::new(seed, self.materials.clone())
+            .with_world_tile(tile.clone())
+            .build();
+        (generator, tile)
+    }
+
+    /// Replace the local map with one generated from the given world map tile
+    fn generate_local_map(&mut self, (x, y): (usize, usize)) {
+        let seed = self.window_manager.worldgen.seed;
+        let (generator, tile) = self.local_generator((x, y), seed);
+        println!("Generating local map for world tile ({}, {}): {:?}", x, y, tile.biome);
         let area = (-1..=1)
             .flat_map(|x| (-1..=1).map(move |y| (x, y)))
             .collect::<Vec<_>>();
+        // Let the old streamer finish writing before the new one clears the region files
+        self.streamer = None;
+        self.world = World::new(self.materials.clone(), self.item_kinds.clone());
         generator.generate_into_world(&mut self.world, &area);
+        for &pos in &area {
+            let plants = place_plants(&self.world, &tile, seed, pos);
+            self.world.plants.extend(plants);
+        }
+        // Everything beyond the embark area is streamed in as it comes into view
+        self.streamer = Some(ChunkStreamer::new(generator, tile, seed, &Stored::default()));
+        self.embark = (x, y);
+        self.local_map_renderer.current_z = SURFACE_Z;
 
-        // Spawn creatures
+        // Spawn creatures with the food they brought along
         self.creatures = creatures::spawn_creatures(&self.world, 10);
+        creatures::drop_supplies(&mut self.world, &self.creatures);
+        self.particles.clear();
+        self.gui.dug = 0;
+        self.input_handler.history.clear();
+        self.local_map_renderer.invalidate();
+        self.window_manager.worker_info.deselect();
+    }
+
+    /// Write the local map, its creatures and any chunks and plants unloaded to disk to the save file
+    fn save_local_map(&mut self) {
+        let seed = self.window_manager.worldgen.seed;
+        // Stop streaming so every unloaded column is on disk, then carry on from the same state
+        let stored = match self.streamer.take() {
+            Some(streamer) => streamer.finish().unwrap_or_else(|e| {
+                eprintln!("Could not read unloaded chunks: {}", e);
+                Stored::default()
+            }),
+            None => Stored::default(),
+        };
+        match save::write_save(seed, self.embark, &self.world, &self.creatures, &stored) {
+            Ok(()) => println!("Saved local map to {}", save::SAVE_PATH),
+            Err(e) => eprintln!("Could not save local map: {}", e),
+        }
+        let (generator, tile) = self.local_generator(self.embark, seed);
+        self.streamer = Some(ChunkStreamer::new(generator, tile, seed, &stored));
+    }
+
+    /// Replace the local map with the one in the save file
+    fn load_local_map(&mut self) {
+        let loaded = match save::read_save(self.materials.clone(), self.item_kinds.clone()) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("Could not load local map: {}", e);
+                return;
+            }
+        };
+        self.streamer = None;
+        let (generator, tile) = self.local_generator(loaded.embark, loaded.seed);
+        self.streamer = Some(ChunkStreamer::new(generator, tile, loaded.seed, &loaded.stored));
+        self.world = loaded.world;
+        self.creatures = loaded.creatures;
+        self.embark = loaded.embark;
+        self.particles.clear();
+        self.input_handler.history.clear();
+        self.local_map_renderer.invalidate();
+        self.window_manager.worker_info.deselect();
+        println!("Loaded local map from {}", save::SAVE_PATH);
     }
 
     fn update(&mut self) {
         // Update creatures if in local map mode
         if let RenderMode::LocalMap = self.render_mode {
             creatures::update_creatures(&mut self.creatures, &mut self.world, &mut self.particles);
+            self.world.tick += 1;
+            plants::update_plants(&mut self.world);
+            self.world.update_hauling();
+            self.world.step_fluids();
+            let landings = self.world.step_gravity(&mut self.particles);
+            creatures::apply_landings(&mut self.creatures, &mut self.world, &landings);
             particles::update_particles(&mut self.particles);
+
+            // Act on the Worker Info window: call off the worker's job, keep the camera on it
+            let info = &mut self.window_manager.worker_info;
+            let selected = info.selected_worker.and_then(|id| self.creatures.iter_mut().find(|c| c.id == id));
+            if let Some(creature) = selected {
+                if std::mem::take(&mut info.cancel_requested) {
+                    creature.cancel_job(&mut self.world);
+                }
+                if info.follow {
+                    self.local_map_renderer.current_z = creature.z;
+                    center_camera(&mut self.local_map_renderer, creature.x, creature.y, &self.world);
+                }
+            }
+
+            // Keep chunks loaded around the view and every creature
+            if let Some(streamer) = &mut self.streamer {
+                let (x, y, zoom) = (
+                    self.local_map_renderer.get_camera_x(),
+                    self.local_map_renderer.get_camera_y(),
+                    self.local_map_renderer.get_zoom(),
+                );
+                let mut focus = vec![(x, y, x + screen_width() / zoom, y + screen_height() / zoom)];
+                focus.extend(self.creatures.iter().map(|c| (c.x, c.y, c.x, c.y)));
+                streamer.update(&mut self.world, &focus);
+            }
         }
 
+        // Hand this frame's map changes to whoever follows them
+        let changes = self.world.take_changes();
+        self.gui.record_changes(&changes);
+        self.local_map_renderer.record_changes(&changes);
+        self.input_handler.history.record_changes(&changes);
+        creatures::invalidate_paths(&mut self.creatures, &self.world, &changes);
+
         // Update GUI
-        self.gui.dig_jobs = count_dig_jobs(&self.world);
+        self.gui.dig_jobs = self.world.jobs.count(JobKind::Dig);
+        self.gui.build_jobs = self.world.jobs.count(JobKind::Build);
+        self.gui.haul_jobs = self.world.jobs.count(JobKind::Haul);
+        self.gui.designation = self.input_handler.designation;
         self.gui.update(&self.world, self.render_mode, &self.input_manager);
         
         // World map regeneration logic
@@ -104,6 +257,32 @@ impl Game {
             );
             self.world_map = world_map_gen.generate();
             self.window_manager.worldgen.regenerate_requested = false;
+            // Any cached region belongs to the old world
+            self.region_map = None;
+        }
+
+        // Region map generation on demand
+        if let Some(tile) = self.input_handler.tile_requests.region.take() {
+            let region = RegionMapGenerator::new(self.window_manager.worldgen.seed)
+                .generate(&self.world_map, tile);
+            self.region_map_renderer.invalidate();
+            self.region_map_renderer.center_on(&region);
+            self.region_map = Some(region);
+            self.active_view = GameView::RegionMap;
+        }
+
+        // Quick save and load
+        if std::mem::take(&mut self.input_handler.save_requests.save) {
+            self.save_local_map();
+        }
+        if std::mem::take(&mut self.input_handler.save_requests.load) {
+            self.load_local_map();
+        }
+
+        // Embark: build the local map for the chosen world tile
+        if let Some(tile) = self.input_handler.tile_requests.embark.take() {
+            self.generate_local_map(tile);
+            self.active_view = GameView::LocalMap;
         }
     }
 
@@ -124,9 +303,10 @@ impl Game {
                 );
             }
             GameView::RegionMap => {
-                // Simple stub for region map view
-                clear_background(DARKGRAY);
-                draw_text("[Region Map View - TODO]", 100.0, 100.0, 32.0, WHITE);
+                match &self.region_map {
+                    Some(region) => self.region_map_renderer.draw(region, &self.world_map),
+                    None => self.active_view = GameView::WorldMap,
+                }
             }
             GameView::LocalMap => {
                 let state = GameState {
@@ -134,16 +314,29 @@ impl Game {
                     camera_y: self.local_map_renderer.get_camera_y(),
                     zoom:     self.local_map_renderer.get_zoom(),
                     z_levels: &self.world.z_levels,
+                    materials: &self.materials,
+                    gui: &self.gui,
                 };
                 
                 crate::game::views::local_map::render(
-                    &self.local_map_renderer,
+                    &mut self.local_map_renderer,
                     &state,
+                    &self.world,
                     &self.creatures,
                     &self.particles,
                     &mut self.window_manager,
                 );
             }
+            GameView::SiteInfo => {
+                if let Some(next_view) = crate::game::views::site_info::render(
+                    &self.world_map,
+                    &self.world_map_renderer,
+                    &self.world_map_camera,
+                    &mut self.window_manager,
+                ) {
+                    self.active_view = next_view;
+                }
+            }
             GameView::CityInfo => {
                 if let Some(portraits) = self.portraits.as_ref() {
                     if let Some(next_view) = crate::game::views::city_info::render(
@@ -176,20 +369,36 @@ impl Game {
             self.render_mode = match self.active_view {
                 GameView::LocalMap => RenderMode::LocalMap,
                 GameView::WorldMap => RenderMode::WorldMap,
+                GameView::RegionMap => RenderMode::RegionMap,
                 _ => self.render_mode, // Keep current mode for other views
             };
             
+            // Clicking a worker selects it rather than painting under it
+            let selected = self.render_mode == RenderMode::LocalMap
+                && handle_worker_selection(
+                    &self.input_manager,
+                    &self.local_map_renderer,
+                    &self.creatures,
+                    &mut self.window_manager.worker_info,
+                );
+
             // Handle input using the input manager
-            self.input_handler.handle_input(
-                &self.input_manager,
-                self.render_mode,
-                &mut self.active_view,
-                &mut self.window_manager,
-                &mut self.local_map_renderer,
-                &mut self.world,
-                &self.world_map,
-                &mut self.world_map_camera,
-            );
+            if !selected {
+                self.input_handler.handle_input(
+                    &self.input_manager,
+                    self.render_mode,
+                    InputContext {
+                        active_view: &mut self.active_view,
+                        window_manager: &mut self.window_manager,
+                        local_map_renderer: &mut self.local_map_renderer,
+                        world: &mut self.world,
+                        world_map: &self.world_map,
+                        world_map_camera: &mut self.world_map_camera,
+                        region_map_renderer: &mut self.region_map_renderer,
+                        region_map: self.region_map.as_ref(),
+                    },
+                );
+            }
             
             self.update();
             self.render();
@@ -1,41 +1,124 @@
-use crate::creatures::Creature;
+use crate::creatures::{Creature, Race, Task, ThoughtKind};
+use crate::creatures::ai::FreeItems;
+use crate::creatures::profile::random_name;
 use crate::particle::Particle;
-use crate::world::localmap::world::World;
+use crate::world::localmap::change::WorldChange;
+use crate::world::localmap::gravity::Landing;
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::world::{World, SURFACE_Z};
 use crate::world::localmap::terrain_material::TerrainMaterial;
 use macroquad::rand::gen_range;
-use macroquad::prelude::RED;
+use std::collections::HashSet;
+
+/// How far away a creature notices another one dying, if nothing solid is in the way
+const DEATH_SIGHT: f32 = 24.0;
+/// Food each worker brings along
+const SUPPLIES: [&str; 3] = ["bread", "cheese", "dried_meat"];
 
 pub fn find_spawn_point(world: &World) -> Option<(f32, f32)> {
-    let tries = 100;
-    for _ in 0..tries {
-        let x = gen_range(0, 256) as i32;
-        let y = gen_range(0, 256) as i32;
-        let material = world.get_material_at(x, y);
-        if material == TerrainMaterial::Dirt {
-            return Some((x as f32, y as f32));
-        }
-    }
-    None
+    let search_radius = 32;
+    let around = WorldPos::new(gen_range(0, 256), gen_range(0, 256), SURFACE_Z);
+    world
+        .find_nearest(around, search_radius, |_, subpixel| {
+            subpixel.material != TerrainMaterial::Air && world.materials.is_walkable(subpixel.material)
+        })
+        .map(|pos| (pos.x as f32, pos.y as f32))
 }
 
 pub fn spawn_creatures(world: &World, count: usize) -> Vec<Creature> {
     let mut creatures = Vec::new();
     
-    for _ in 0..count {
+    for id in 0..count {
         if let Some((spawn_x, spawn_y)) = find_spawn_point(world) {
-            creatures.push(Creature::new(spawn_x, spawn_y, 2.0, RED));
+            let race = Race::random();
+            let mut creature = Creature::new(id as u32, spawn_x, spawn_y, SURFACE_Z, 2.0, race.color());
+            creature.name = random_name();
+            creature.race = race;
+            creatures.push(creature);
         }
     }
     
     creatures
 }
 
+/// Drops the food the workers brought along next to each of them.
+pub fn drop_supplies(world: &mut World, creatures: &[Creature]) {
+    for creature in creatures {
+        for kind in SUPPLIES {
+            world.drop_item(kind, creature.x + gen_range(-2.0, 2.0), creature.y + gen_range(-2.0, 2.0), creature.z);
+        }
+    }
+}
+
 pub fn update_creatures(creatures: &mut [Creature], world: &mut World, particles: &mut Vec<Particle>) {
+    let mut beds_taken: HashSet<WorldPos> = creatures
+        .iter()
+        .filter(|c| c.task == Task::Sleep)
+        .filter_map(|c| c.target)
+        .collect();
+    let standing: HashSet<WorldPos> = creatures.iter().map(|c| WorldPos::from_f32(c.x, c.y, c.z)).collect();
+    // Indexed the first time someone looks for work this frame
+    let mut free_items = None;
     for creature in creatures {
-        if creature.target.is_none() {
-            creature.find_nearest_dig_target(world);
+        creature.tend_needs(world, &mut beds_taken);
+        if creature.job.is_none() && !creature.task.is_need() {
+            let free = free_items.get_or_insert_with(|| FreeItems::index(world));
+            creature.claim_job(world, free);
         }
         creature.move_toward_target(world);
         creature.dig_if_close(world, particles);
+        creature.chop_if_close(world, particles);
+        creature.build_if_close(world, particles, &standing);
+        creature.haul_if_close(world);
     }
-} 
\ No newline at end of file
+} 
+/// Drops cached paths that run over a subpixel nobody can walk on any more.
+pub fn invalidate_paths(creatures: &mut [Creature], world: &World, changes: &[WorldChange]) {
+    let blocked: HashSet<WorldPos> = changes
+        .iter()
+        .filter(|change| !world.materials.is_walkable(change.new))
+        .map(|change| change.pos)
+        .collect();
+    if blocked.is_empty() {
+        return;
+    }
+    for creature in creatures.iter_mut() {
+        if creature.path.iter().any(|pos| blocked.contains(pos)) {
+            creature.path.clear();
+        }
+    }
+}
+
+/// Hurts creatures caught under falling material; those with no health left die and
+/// leave their jobs to the others.
+pub fn apply_landings(creatures: &mut Vec<Creature>, world: &mut World, landings: &[Landing]) {
+    for landing in landings {
+        for creature in creatures.iter_mut().filter(|c| c.z == landing.z) {
+            let dx = creature.x - (landing.x as f32 + 0.5);
+            let dy = creature.y - (landing.y as f32 + 0.5);
+            if dx * dx + dy * dy <= creature.size * creature.size {
+                creature.health -= landing.damage;
+                creature.needs.think(ThoughtKind::Hurt, world.tick);
+                creature.log(world.tick, "Was hit by falling material".to_string());
+            }
+        }
+    }
+    let dead: Vec<(String, f32, f32, i32)> =
+        creatures.iter().filter(|c| c.health <= 0.0).map(|c| (c.name.clone(), c.x, c.y, c.z)).collect();
+    for creature in creatures.iter_mut().filter(|c| c.health <= 0.0) {
+        creature.drop_job(world);
+    }
+    creatures.retain(|c| c.health > 0.0);
+    // Those who saw it happen won't forget it soon
+    for (name, x, y, z) in &dead {
+        let at = WorldPos::from_f32(*x, *y, *z);
+        for creature in creatures.iter_mut().filter(|c| c.z == *z) {
+            if (creature.x - x).powi(2) + (creature.y - y).powi(2) <= DEATH_SIGHT * DEATH_SIGHT
+                && world.line_of_sight(WorldPos::from_f32(creature.x, creature.y, *z), at)
+            {
+                creature.needs.think(ThoughtKind::SawDeath, world.tick);
+                creature.log(world.tick, format!("Saw {} die", name));
+            }
+        }
+    }
+}
@@ -1,2 +1,3 @@
 pub mod creatures;
-pub mod particles; 
\ No newline at end of file
+pub mod particles;
+pub mod plants;
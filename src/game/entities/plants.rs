@@ -0,0 +1,23 @@
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::world::World;
+
+/// Frames between ground checks of any one plant
+const GROUND_CHECK_INTERVAL: u64 = 30;
+
+/// Grows every plant and drops those whose ground was dug away or buried. The ground is
+/// checked for a slice of the plants each frame, as there can be thousands of them.
+pub fn update_plants(world: &mut World) {
+    let mut plants = std::mem::take(&mut world.plants);
+    for plant in plants.iter_mut() {
+        plant.grow();
+    }
+    let slice = world.tick % GROUND_CHECK_INTERVAL;
+    plants.retain(|plant| {
+        if (plant.x + plant.y).rem_euclid(GROUND_CHECK_INTERVAL as i32) as u64 != slice {
+            return true;
+        }
+        let ground = world.get_material_at(plant.x, plant.y, plant.z);
+        ground != TerrainMaterial::Air && world.materials.is_walkable(ground)
+    });
+    world.plants = plants;
+}
@@ -1,8 +1,12 @@
 use macroquad::prelude::*;
 use crate::game::views::GameView;
-use crate::game::input::RenderMode;
+use crate::game::input::{RenderMode, SaveRequests, TileRequests};
+use crate::game::input::local_map::history::CommandHistory;
+use crate::game::input::local_map::designation::DesignationSettings;
 use crate::renderer::local_map_renderer::LocalMapRenderer;
 use crate::renderer::camera::Camera;
+use crate::renderer::region_map_renderer::RegionMapRenderer;
+use crate::world::regionmap::RegionMap;
 use crate::world::localmap::world::World;
 use crate::gui::windows::window_manager::WindowManager;
 use crate::world::worldmap::world_map::WorldMap;
@@ -15,10 +19,30 @@ use crate::game::views::{world_map as view_world_map, local_map as view_local_ma
 // Import the new input handler modules with different names
 use crate::game::input::local_map as input_local_map;
 use crate::game::input::world_map as input_world_map;
+use crate::game::input::region_map as input_region_map;
+
+/// Game state the input handler reads and changes while handling a frame's input
+pub struct InputContext<'a> {
+    pub active_view: &'a mut GameView,
+    pub window_manager: &'a mut WindowManager,
+    pub local_map_renderer: &'a mut LocalMapRenderer,
+    pub world: &'a mut World,
+    pub world_map: &'a WorldMap,
+    pub world_map_camera: &'a mut Camera,
+    pub region_map_renderer: &'a mut RegionMapRenderer,
+    pub region_map: Option<&'a RegionMap>,
+}
 
 pub struct InputHandler {
     previous_mouse_x: f32,
     previous_mouse_y: f32,
+    /// World tiles the player asked to open in the region or local map
+    pub tile_requests: TileRequests,
+    pub save_requests: SaveRequests,
+    /// Undoable designation and painting strokes on the local map
+    pub history: CommandHistory,
+    /// Priority and construction new designations are made with
+    pub designation: DesignationSettings,
 }
 
 impl InputHandler {
@@ -26,6 +50,10 @@ impl InputHandler {
         Self {
             previous_mouse_x: 0.0,
             previous_mouse_y: 0.0,
+            tile_requests: TileRequests::default(),
+            save_requests: SaveRequests::default(),
+            history: CommandHistory::default(),
+            designation: DesignationSettings::default(),
         }
     }
 
@@ -33,13 +61,19 @@ impl InputHandler {
         &mut self,
         input: &InputManager,
         render_mode: RenderMode,
-        active_view: &mut GameView,
-        window_manager: &mut WindowManager,
-        local_map_renderer: &mut LocalMapRenderer,
-        world: &mut World,
-        world_map: &WorldMap,
-        world_map_camera: &mut Camera,
+        ctx: InputContext,
     ) -> bool {
+        let InputContext {
+            active_view,
+            window_manager,
+            local_map_renderer,
+            world,
+            world_map,
+            world_map_camera,
+            region_map_renderer,
+            region_map,
+        } = ctx;
+
         // ESC key toggles main menu
         if input.key().pressed(KeyCode::Escape) {
             window_manager.main_menu.toggle_main();
@@ -56,6 +90,18 @@ impl InputHandler {
             return true;
         }
 
+        // Quick save and load of the local map
+        if render_mode == RenderMode::LocalMap {
+            if input.key().pressed(KeyCode::F5) {
+                self.save_requests.save = true;
+                return true;
+            }
+            if input.key().pressed(KeyCode::F9) {
+                self.save_requests.load = true;
+                return true;
+            }
+        }
+
         // Process mode-specific input based on the current render_mode
         let handled = match render_mode {
             RenderMode::LocalMap => {
@@ -65,7 +111,9 @@ impl InputHandler {
                     &mut self.previous_mouse_x,
                     &mut self.previous_mouse_y,
                     local_map_renderer,
-                    world
+                    world,
+                    &mut self.history,
+                    &mut self.designation,
                 )
             },
             RenderMode::WorldMap => {
@@ -76,7 +124,8 @@ impl InputHandler {
                     &mut self.previous_mouse_y,
                     world_map_camera, 
                     world_map,
-                    window_manager
+                    window_manager,
+                    &mut self.tile_requests,
                 );
                 if let Some(new_view) = view_changed {
                     *active_view = new_view;
@@ -84,6 +133,19 @@ impl InputHandler {
                 }
                 false
             }
+            RenderMode::RegionMap => {
+                match region_map {
+                    Some(region) => input_region_map::handle_input(
+                        input,
+                        &mut self.previous_mouse_x,
+                        &mut self.previous_mouse_y,
+                        region_map_renderer,
+                        region,
+                        &mut self.tile_requests,
+                    ),
+                    None => false,
+                }
+            }
         };
 
         handled
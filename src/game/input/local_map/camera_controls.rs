@@ -3,12 +3,13 @@ use crate::renderer::local_map_renderer::LocalMapRenderer;
 use crate::world::localmap::world::World;
 use crate::input::manager::InputManager;
 use crate::input::event::InputEvent;
+use crate::worldgen::localmap::tile_info::{EXPLORE_MAX, EXPLORE_MIN, WORLD_TILE_ORIGIN, WORLD_TILE_SUBPIXELS};
 use super::constants::*;
 
 /// Enforces camera boundaries to prevent moving too far off the map
 /// 
 /// Takes into account:
-/// - The map dimensions (from loaded chunks)
+/// - The explorable area (the embark tile and its neighbours; chunks stream in as you go)
 /// - The current zoom level
 /// - The screen dimensions
 /// 
@@ -19,127 +20,83 @@ pub fn enforce_camera_boundaries(
 ) -> bool {
     let original_x = local_map_renderer.get_camera_x();
     let original_y = local_map_renderer.get_camera_y();
-    
-    // Get the map dimensions from loaded chunks
-    if let Some(zlevel) = world.z_levels.get(0) {
-        if zlevel.chunks.is_empty() {
-            return false; // No chunks loaded, nothing to constrain to
-        }
-        
-        // gather all loaded chunk coordinates
-        let mut xs: Vec<i32> = zlevel.chunks.keys().map(|(cx, _)| *cx).collect();
-        let mut ys: Vec<i32> = zlevel.chunks.keys().map(|(_, cy)| *cy).collect();
-        
-        if !xs.is_empty() {
-            xs.sort_unstable();
-            ys.sort_unstable();
-
-            // compute world-unit extents
-            let min_cx = xs[0] as f32;
-            let max_cx = xs[xs.len()-1] as f32 + 1.0;
-            let min_cy = ys[0] as f32;
-            let max_cy = ys[ys.len()-1] as f32 + 1.0;
-
-            // Calculate world units per chunk
-            let subpx_per_chunk = CHUNK_SIZE * TILE_PX;
-            
-            // Map bounds in world units
-            let map_min_x = min_cx * subpx_per_chunk;
-            let map_max_x = max_cx * subpx_per_chunk;
-            let map_min_y = min_cy * subpx_per_chunk;
-            let map_max_y = max_cy * subpx_per_chunk;
-            
-            // Get the visible area in world units
-            let zoom = local_map_renderer.get_zoom();
-            let visible_width = screen_width() / zoom;
-            let visible_height = screen_height() / zoom;
-            
-            // Calculate the minimum and maximum allowed camera positions
-            // This ensures at least some part of the map is always visible
-            let min_x = map_min_x + MIN_CAMERA_DISTANCE;
-            let min_y = map_min_y + MIN_CAMERA_DISTANCE;
-            let max_x = map_max_x - visible_width + CAMERA_MARGIN;
-            let max_y = map_max_y - visible_height + CAMERA_MARGIN;
-            
-            // Apply constraints, making sure min values don't exceed max values
-            // This can happen with very high zoom levels or large screens
-            let constrained_min_x = min_x.min(max_x);
-            let constrained_min_y = min_y.min(max_y);
-            
-            // Clamp the camera position
-            let new_x = local_map_renderer.get_camera_x().clamp(constrained_min_x, max_x);
-            let new_y = local_map_renderer.get_camera_y().clamp(constrained_min_y, max_y);
-            
-            // Set the new position if it changed
-            if (new_x - original_x).abs() > f32::EPSILON || (new_y - original_y).abs() > f32::EPSILON {
-                // Use move_camera_delta to set the position relative to the current position
-                local_map_renderer.move_camera_delta(
-                    new_x - original_x,
-                    new_y - original_y
-                );
-                return true;
-            }
-        }
+
+    if world.z_level(local_map_renderer.current_z).is_none() {
+        return false; // Nothing generated yet, nothing to constrain to
     }
-    
+
+    // Map bounds in world units
+    let map_min = EXPLORE_MIN as f32;
+    let map_max = EXPLORE_MAX as f32;
+
+    // Get the visible area in world units
+    let zoom = local_map_renderer.get_zoom();
+    let visible_width = screen_width() / zoom;
+    let visible_height = screen_height() / zoom;
+
+    // Calculate the minimum and maximum allowed camera positions
+    // This ensures at least some part of the map is always visible
+    let min_x = map_min + MIN_CAMERA_DISTANCE;
+    let min_y = map_min + MIN_CAMERA_DISTANCE;
+    let max_x = map_max - visible_width + CAMERA_MARGIN;
+    let max_y = map_max - visible_height + CAMERA_MARGIN;
+
+    // Apply constraints, making sure min values don't exceed max values
+    // This can happen with very high zoom levels or large screens
+    let constrained_min_x = min_x.min(max_x);
+    let constrained_min_y = min_y.min(max_y);
+
+    // Clamp the camera position
+    let new_x = local_map_renderer.get_camera_x().clamp(constrained_min_x, max_x);
+    let new_y = local_map_renderer.get_camera_y().clamp(constrained_min_y, max_y);
+
+    // Set the new position if it changed
+    if (new_x - original_x).abs() > f32::EPSILON || (new_y - original_y).abs() > f32::EPSILON {
+        // Use move_camera_delta to set the position relative to the current position
+        local_map_renderer.move_camera_delta(
+            new_x - original_x,
+            new_y - original_y
+        );
+        return true;
+    }
+
     false
 }
 
-/// Handles camera centering (C key)
+/// Handles camera centering (C key) on the embark tile
 pub fn handle_center_camera(
     input: &InputManager,
     local_map_renderer: &mut LocalMapRenderer,
     world: &World,
 ) -> bool {
     if input.key().pressed(KeyCode::C) {
-        if let Some(zlevel) = world.z_levels.get(0) {
-            // gather all loaded chunk coordinates
-            let mut xs: Vec<i32> = zlevel.chunks.keys().map(|(cx, _)| *cx).collect();
-            let mut ys: Vec<i32> = zlevel.chunks.keys().map(|(_, cy)| *cy).collect();
-            
-            if !xs.is_empty() {
-                xs.sort_unstable();
-                ys.sort_unstable();
-
-                // compute world-unit extents
-                let min_cx = xs[0] as f32;
-                let max_cx = xs[xs.len()-1] as f32 + 1.0;
-                let min_cy = ys[0] as f32;
-                let max_cy = ys[ys.len()-1] as f32 + 1.0;
-
-                // Calculate world units per chunk
-                let subpx_per_chunk = CHUNK_SIZE * TILE_PX;
-                
-                // true map center in world-subpixels
-                let world_center_x = (min_cx + max_cx) * 0.5 * subpx_per_chunk;
-                let world_center_y = (min_cy + max_cy) * 0.5 * subpx_per_chunk;
-
-                // convert screen half-width to world units
-                let zoom = local_map_renderer.get_zoom();
-                let sw = screen_width();
-                let sh = screen_height();
-                let half_wu_x = (sw * 0.5) / zoom;
-                let half_wu_y = (sh * 0.5) / zoom;
-
-                // desired camera position
-                let desired_cam_x = world_center_x - half_wu_x;
-                let desired_cam_y = world_center_y - half_wu_y;
-
-                // apply delta
-                local_map_renderer.move_camera_delta(
-                    desired_cam_x - local_map_renderer.get_camera_x(),
-                    desired_cam_y - local_map_renderer.get_camera_y(),
-                );
-                
-                // Enforce boundaries after centering
-                enforce_camera_boundaries(local_map_renderer, world);
-                return true;
-            }
-        }
+        // true embark centre in world-subpixels
+        let center = (WORLD_TILE_ORIGIN + WORLD_TILE_SUBPIXELS / 2) as f32;
+        center_camera(local_map_renderer, center, center, world);
+        return true;
     }
     false
 }
 
+/// Handle moving the view up and down between levels (PageUp/PageDown keys)
+pub fn handle_z_level(
+    input: &InputManager,
+    local_map_renderer: &mut LocalMapRenderer,
+    world: &World,
+) -> bool {
+    let (top, bottom) = world.z_range();
+    let z = local_map_renderer.current_z;
+    let new_z = if input.key().pressed(KeyCode::PageUp) {
+        (z + 1).min(top)
+    } else if input.key().pressed(KeyCode::PageDown) {
+        (z - 1).max(bottom)
+    } else {
+        return false;
+    };
+    local_map_renderer.current_z = new_z;
+    new_z != z
+}
+
 /// Handle keyboard-based camera movement (WASD keys)
 pub fn handle_keyboard_movement(
     input: &InputManager,
@@ -1,9 +1,3 @@
-/// The size of a tile in pixels
-pub const TILE_PX: f32 = 8.0;
-
-/// The size of a chunk in tiles
-pub const CHUNK_SIZE: f32 = 32.0;
-
 /// The minimum zoom level
 pub const MIN_ZOOM: f32 = 1.0;
 
@@ -0,0 +1,71 @@
+use crate::construction::{BuildMaterial, Construction, Structure};
+use crate::input::manager::InputManager;
+use crate::items::ItemFilter;
+use crate::jobs::job::{DEFAULT_PRIORITY, MAX_PRIORITY, MIN_PRIORITY};
+use crate::world::localmap::position::WorldPos;
+use macroquad::prelude::KeyCode;
+
+/// What a mouse stroke on the local map does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Brush {
+    /// Plan the chosen construction (left button)
+    Build,
+    /// Turn ground into rock on the spot (Alt + left button)
+    Rock,
+    /// Mark subpixels for digging (right button)
+    Dig,
+    /// Mark trees for felling (Shift + right button)
+    Chop,
+    /// Mark the connected patch of the clicked material for digging (Alt + right button)
+    Vein,
+}
+
+/// What new designations are made with, picked from the keyboard
+#[derive(Clone, Copy, Debug)]
+pub struct DesignationSettings {
+    /// Priority new jobs get, from 1 (low) to 5 (urgent)
+    pub priority: u8,
+    /// What the build brush plans
+    pub construction: Construction,
+    /// What new stockpiles take
+    pub stockpile_filter: ItemFilter,
+    /// Corner of the stockpile being dragged out, if any
+    pub zone_start: Option<WorldPos>,
+}
+
+impl Default for DesignationSettings {
+    fn default() -> Self {
+        Self {
+            priority: DEFAULT_PRIORITY,
+            construction: Construction::new(Structure::Wall, BuildMaterial::Wood),
+            stockpile_filter: ItemFilter::ANY,
+            zone_start: None,
+        }
+    }
+}
+
+/// Handles the number keys 1-5, which set the priority of new designations, B and M, which
+/// cycle through the structures and materials to build, and F, which cycles what new
+/// stockpiles take
+pub fn handle_designation_keys(input: &InputManager, settings: &mut DesignationSettings) -> bool {
+    const KEYS: [KeyCode; 5] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5];
+    for (key, value) in KEYS.into_iter().zip(MIN_PRIORITY..=MAX_PRIORITY) {
+        if input.key().pressed(key) {
+            settings.priority = value;
+            return true;
+        }
+    }
+    if input.key().pressed(KeyCode::B) {
+        settings.construction.structure = settings.construction.structure.next();
+        return true;
+    }
+    if input.key().pressed(KeyCode::M) {
+        settings.construction.material = settings.construction.material.next();
+        return true;
+    }
+    if input.key().pressed(KeyCode::F) {
+        settings.stockpile_filter = settings.stockpile_filter.next();
+        return true;
+    }
+    false
+}
@@ -0,0 +1,148 @@
+use crate::construction::Construction;
+use crate::input::manager::InputManager;
+use crate::world::localmap::change::{ChangeCause, WorldChange};
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::world::World;
+use macroquad::prelude::KeyCode;
+
+/// Strokes kept for undoing; older ones are forgotten
+const MAX_HISTORY: usize = 100;
+
+/// What one mouse stroke did to the map, so it can be taken back and done again.
+#[derive(Default)]
+pub struct Command {
+    /// Priority the stroke's designations were made with
+    pub priority: u8,
+    /// Subpixels newly marked for digging
+    pub designated: Vec<WorldPos>,
+    /// Trees newly marked for felling, by position
+    pub chop: Vec<WorldPos>,
+    /// Constructions newly planned
+    pub planned: Vec<(WorldPos, Construction)>,
+    /// Subpixels painted over, with what they were before, as announced by the map
+    pub painted: Vec<WorldChange>,
+}
+
+impl Command {
+    pub fn is_empty(&self) -> bool {
+        self.designated.is_empty() && self.chop.is_empty() && self.planned.is_empty() && self.painted.is_empty()
+    }
+
+    fn extend(&mut self, other: Command) {
+        self.priority = other.priority;
+        self.designated.extend(other.designated);
+        self.chop.extend(other.chop);
+        self.planned.extend(other.planned);
+        self.painted.extend(other.painted);
+    }
+
+    /// Takes the stroke back. Anything changed since by something else is left as it is.
+    fn undo(&self, world: &mut World) {
+        for change in self.painted.iter().rev() {
+            let (x, y, z) = change.pos.into();
+            if world.get_material_at(x, y, z) == change.new {
+                world.set_material_at(x, y, z, change.old, ChangeCause::Undo);
+            }
+        }
+        // Creatures drop jobs whose designation is gone
+        for &pos in &self.designated {
+            world.cancel_dig(pos);
+        }
+        for &pos in &self.chop {
+            world.cancel_chop(pos);
+        }
+        for &(pos, _) in &self.planned {
+            world.cancel_build(pos);
+        }
+    }
+
+    fn redo(&self, world: &mut World) {
+        for change in &self.painted {
+            let (x, y, z) = change.pos.into();
+            if world.get_material_at(x, y, z) == change.old {
+                world.set_material_at(x, y, z, change.new, ChangeCause::Redo);
+            }
+        }
+        for &pos in &self.designated {
+            world.designate_dig(pos, self.priority);
+        }
+        for &pos in &self.chop {
+            world.designate_chop(pos, self.priority);
+        }
+        for &(pos, construction) in &self.planned {
+            world.designate_build(pos, construction, self.priority);
+        }
+    }
+}
+
+/// Designation and painting strokes on the local map, for Ctrl+Z / Ctrl+Y.
+#[derive(Default)]
+pub struct CommandHistory {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+    /// The stroke being drawn while a mouse button is held
+    stroke: Option<Command>,
+}
+
+impl CommandHistory {
+    /// Adds what painting did this frame to the current stroke.
+    pub fn record(&mut self, command: Command) {
+        if command.is_empty() {
+            return;
+        }
+        self.stroke.get_or_insert_with(Command::default).extend(command);
+    }
+
+    /// Adds the player's painting among a frame's map changes to the current stroke.
+    pub fn record_changes(&mut self, changes: &[WorldChange]) {
+        let mut painted = changes.iter().filter(|change| change.cause == ChangeCause::Paint).peekable();
+        if painted.peek().is_some() {
+            self.stroke.get_or_insert_with(Command::default).painted.extend(painted);
+        }
+    }
+
+    /// Finishes the current stroke, making it the next thing to undo.
+    pub fn end_stroke(&mut self) {
+        let Some(stroke) = self.stroke.take() else { return; };
+        self.undo.push(stroke);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, world: &mut World) {
+        self.end_stroke();
+        if let Some(command) = self.undo.pop() {
+            command.undo(world);
+            self.redo.push(command);
+        }
+    }
+
+    pub fn redo(&mut self, world: &mut World) {
+        self.end_stroke();
+        if let Some(command) = self.redo.pop() {
+            command.redo(world);
+            self.undo.push(command);
+        }
+    }
+
+    /// Forgets everything, e.g. when a different map is loaded.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Handles Ctrl+Z (undo) and Ctrl+Y (redo)
+pub fn handle_undo_redo(input: &InputManager, history: &mut CommandHistory, world: &mut World) -> bool {
+    let ctrl = input.key().held(KeyCode::LeftControl) || input.key().held(KeyCode::RightControl);
+    if ctrl && input.key().pressed(KeyCode::Z) {
+        history.undo(world);
+        return true;
+    }
+    if ctrl && input.key().pressed(KeyCode::Y) {
+        history.redo(world);
+        return true;
+    }
+    false
+}
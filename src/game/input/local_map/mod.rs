@@ -1,6 +1,9 @@
 mod camera_controls;
 mod mouse_controls;
 mod constants;
+pub mod history;
+pub mod designation;
+pub mod selection;
 
 use macroquad::prelude::*;
 use crate::renderer::local_map_renderer::LocalMapRenderer;
@@ -12,15 +15,19 @@ use camera_controls::{
     handle_center_camera,
     handle_keyboard_movement,
     handle_zoom,
+    handle_z_level,
     enforce_camera_boundaries,
     center_camera as internal_center_camera,
 };
+use history::{CommandHistory, handle_undo_redo};
+use designation::{DesignationSettings, handle_designation_keys};
 use mouse_controls::{
     DragState,
     handle_drag_start,
     handle_drag_movement,
     handle_drag_end,
     handle_mouse_painting,
+    handle_stockpile_zones,
 };
 
 /// Stores the state for local map input handling
@@ -50,6 +57,8 @@ pub fn handle_input(
     previous_mouse_y: &mut f32,
     local_map_renderer: &mut LocalMapRenderer,
     world: &mut World,
+    history: &mut CommandHistory,
+    designation: &mut DesignationSettings,
 ) -> bool {
     // Migrate to using proper state management
     let mut drag_state = DragState {
@@ -63,42 +72,67 @@ pub fn handle_input(
     
     let events = &input.events;
     
+    // A painting stroke ends when the buttons are let go
+    if !input.mouse().held(MouseButton::Left) && !input.mouse().held(MouseButton::Right) {
+        history.end_stroke();
+    }
+
     // Process input by priority
     let mut handled = false;
-    
+
+    // 0. Check for undo and redo
+    if !handled {
+        handled = handle_undo_redo(input, history, world);
+    }
+
     // 1. Check for center camera input
     if !handled {
         handled = handle_center_camera(input, local_map_renderer, world);
     }
     
-    // 2. Check for keyboard movement
+    // 2. Check for level change
+    if !handled {
+        handled = handle_z_level(input, local_map_renderer, world);
+    }
+    
+    // 3. Check for keyboard movement
     if !handled {
         handled = handle_keyboard_movement(input, local_map_renderer, world);
     }
     
-    // 3. Check for zooming
+    // 4. Check for zooming
     if !handled {
         handled = handle_zoom(input, local_map_renderer, world);
     }
     
-    // 4. Check for drag input
+    // 5. Check for drag input
     if !handled {
         handled = handle_drag_start(input, &mut drag_state);
     }
     
-    // 5. Process drag movement
+    // 6. Process drag movement
     if !handled {
         handled = handle_drag_movement(input, &mut drag_state, local_map_renderer, world);
     }
     
-    // 6. Check for drag end
+    // 7. Check for drag end
     if !handled {
         handled = handle_drag_end(input, &mut drag_state);
     }
     
-    // 7. Check for mouse painting/digging
+    // 8. Check for the priority and construction of new designations
+    if !handled {
+        handled = handle_designation_keys(input, designation);
+    }
+
+    // 9. Check for stockpile zones
+    if !handled {
+        handled = handle_stockpile_zones(input, local_map_renderer, world, designation);
+    }
+
+    // 10. Check for mouse painting/digging
     if !handled {
-        handled = handle_mouse_painting(input, local_map_renderer, world);
+        handled = handle_mouse_painting(input, local_map_renderer, world, history, designation);
     }
     
     // Final boundary check to ensure the camera is always within bounds
@@ -5,6 +5,9 @@ use crate::input::manager::InputManager;
 use crate::input::event::InputEvent;
 use crate::game::views::local_map as view_local_map;
 use super::camera_controls::enforce_camera_boundaries;
+use super::history::CommandHistory;
+use super::designation::{Brush, DesignationSettings};
+use crate::world::localmap::position::WorldPos;
 
 /// Stores the drag state for middle mouse button camera control
 pub struct DragState {
@@ -74,25 +77,76 @@ pub fn handle_drag_end(
     false
 }
 
-/// Handles mouse-based painting and digging
+/// Handles stockpile zones: Ctrl + left drag marks one out, Ctrl + right click removes the
+/// one under the cursor
+pub fn handle_stockpile_zones(
+    input: &InputManager,
+    local_map_renderer: &LocalMapRenderer,
+    world: &mut World,
+    designation: &mut DesignationSettings,
+) -> bool {
+    let (mouse_x, mouse_y) = input.state.mouse_position;
+    let cursor = WorldPos::from_f32(
+        local_map_renderer.get_camera_x() + mouse_x / local_map_renderer.get_zoom(),
+        local_map_renderer.get_camera_y() + mouse_y / local_map_renderer.get_zoom(),
+        local_map_renderer.current_z,
+    );
+    let ctrl = input.key().held(KeyCode::LeftControl) || input.key().held(KeyCode::RightControl);
+
+    if let Some(start) = designation.zone_start {
+        if !input.mouse().held(MouseButton::Left) {
+            designation.zone_start = None;
+            crate::player::actions::add_stockpile(world, start, cursor, designation.stockpile_filter, designation.priority);
+        }
+        return true;
+    }
+    if ctrl && input.mouse().pressed(MouseButton::Left) {
+        designation.zone_start = Some(cursor);
+        return true;
+    }
+    if ctrl && input.mouse().pressed(MouseButton::Right) {
+        crate::player::actions::remove_stockpile(world, cursor);
+        return true;
+    }
+    false
+}
+
+/// Handles mouse-based painting and digging; everything one press of the button does
+/// is recorded as a single undoable stroke
 pub fn handle_mouse_painting(
     input: &InputManager,
     local_map_renderer: &mut LocalMapRenderer,
     world: &mut World,
+    history: &mut CommandHistory,
+    designation: &DesignationSettings,
 ) -> bool {
     let (mouse_x, mouse_y) = input.state.mouse_position;
-    let mouse_left = input.mouse().held(MouseButton::Left);
-    let mouse_right = input.mouse().held(MouseButton::Right);
-    
-    if mouse_left || mouse_right {
-        view_local_map::paint_with_mouse(
+    let shift = input.key().held(KeyCode::LeftShift);
+    let alt = input.key().held(KeyCode::LeftAlt) || input.key().held(KeyCode::RightAlt);
+    let brush = if input.mouse().held(MouseButton::Left) {
+        Some(if alt { Brush::Rock } else { Brush::Build })
+    } else if input.mouse().held(MouseButton::Right) {
+        Some(if shift {
+            Brush::Chop
+        } else if alt {
+            Brush::Vein
+        } else {
+            Brush::Dig
+        })
+    } else {
+        None
+    };
+
+    if let Some(brush) = brush {
+        let command = view_local_map::paint_with_mouse(
             world,
             local_map_renderer,
             mouse_x,
             mouse_y,
-            mouse_left,
-            mouse_right
+            brush,
+            designation,
         );
+        history.record(command);
         return true;
     }
     
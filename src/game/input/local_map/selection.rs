@@ -0,0 +1,41 @@
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use crate::creatures::Creature;
+use crate::gui::windows::worker_info::WorkerInfoState;
+use crate::input::manager::InputManager;
+use crate::renderer::local_map_renderer::LocalMapRenderer;
+
+/// Handles clicks on workers: a plain left click on one on the shown level selects it and
+/// opens its Worker Info window instead of painting under it. Clicks on a window belong to
+/// the window, so they don't paint the map either.
+pub fn handle_worker_selection(
+    input: &InputManager,
+    local_map_renderer: &LocalMapRenderer,
+    creatures: &[Creature],
+    worker_info: &mut WorkerInfoState,
+) -> bool {
+    let (mouse_x, mouse_y) = input.state.mouse_position;
+    let mouse_down = [MouseButton::Left, MouseButton::Right]
+        .into_iter()
+        .any(|button| input.mouse().pressed(button) || input.mouse().held(button));
+    if mouse_down && root_ui().is_mouse_over(vec2(mouse_x, mouse_y)) {
+        return true;
+    }
+
+    let modified = [KeyCode::LeftControl, KeyCode::RightControl, KeyCode::LeftAlt, KeyCode::RightAlt]
+        .into_iter()
+        .any(|key| input.key().held(key));
+    if !input.mouse().pressed(MouseButton::Left) || modified {
+        return false;
+    }
+    let x = local_map_renderer.get_camera_x() + mouse_x / local_map_renderer.get_zoom();
+    let y = local_map_renderer.get_camera_y() + mouse_y / local_map_renderer.get_zoom();
+    let distance_sq = |c: &Creature| (c.x - x).powi(2) + (c.y - y).powi(2);
+    let clicked = creatures
+        .iter()
+        .filter(|c| c.z == local_map_renderer.current_z && distance_sq(c) <= c.size * c.size)
+        .min_by(|a, b| distance_sq(a).total_cmp(&distance_sq(b)));
+    let Some(creature) = clicked else { return false; };
+    worker_info.select(creature.id);
+    true
+}
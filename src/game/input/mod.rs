@@ -1,11 +1,30 @@
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
 pub enum RenderMode {
     WorldMap,
     LocalMap,
+    RegionMap,
+}
+
+/// World tiles the player asked to open from the map views
+#[derive(Default)]
+pub struct TileRequests {
+    /// Open the region map around this tile
+    pub region: Option<(usize, usize)>,
+    /// Generate the local map for this tile and switch to it
+    pub embark: Option<(usize, usize)>,
+}
+
+/// Local map quick save (F5) and load (F9) asked for by the player
+#[derive(Default)]
+pub struct SaveRequests {
+    pub save: bool,
+    pub load: bool,
 }
 
 pub mod handler;
 pub mod local_map;
 pub mod world_map;
+pub mod region_map;
 
-pub use handler::InputHandler; 
\ No newline at end of file
+pub use handler::{InputContext, InputHandler};
\ No newline at end of file
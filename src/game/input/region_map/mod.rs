@@ -0,0 +1,112 @@
+use macroquad::prelude::*;
+use crate::renderer::region_map_renderer::{RegionMapRenderer, RegionView, CELL_PX};
+use crate::world::regionmap::RegionMap;
+use crate::input::manager::InputManager;
+use crate::game::input::TileRequests;
+
+/// The minimum zoom level
+const MIN_ZOOM: f32 = 0.5;
+
+/// The maximum zoom level
+const MAX_ZOOM: f32 = 16.0;
+
+/// The speed of zooming
+const ZOOM_SPEED: f32 = 0.5;
+
+/// The base movement speed in cells (multiplied by frame time)
+const BASE_MOVE_SPEED: f32 = 200.0;
+
+/// Keeps at least part of the region on screen
+fn enforce_camera_boundaries(renderer: &mut RegionMapRenderer, region: &RegionMap) {
+    let scale = CELL_PX * renderer.camera.zoom;
+    let visible_w = screen_width() / scale;
+    let visible_h = screen_height() / scale;
+    let margin = 32.0;
+    let max_x = (region.width as f32 - visible_w + margin).max(-margin);
+    let max_y = (region.height as f32 - visible_h + margin).max(-margin);
+    renderer.camera.x = renderer.camera.x.clamp(-margin, max_x);
+    renderer.camera.y = renderer.camera.y.clamp(-margin, max_y);
+}
+
+/// Handles all input for the region map view
+///
+/// Returns true if input was handled
+pub fn handle_input(
+    input: &InputManager,
+    previous_mouse_x: &mut f32,
+    previous_mouse_y: &mut f32,
+    renderer: &mut RegionMapRenderer,
+    region: &RegionMap,
+    tile_requests: &mut TileRequests,
+) -> bool {
+    let state = &input.state;
+    let mut handled = false;
+
+    // View mode selection
+    for (key, view) in [
+        (KeyCode::Key1, RegionView::Terrain),
+        (KeyCode::Key2, RegionView::Elevation),
+        (KeyCode::Key3, RegionView::Political),
+    ] {
+        if input.key().pressed(key) {
+            renderer.set_view(view);
+            handled = true;
+        }
+    }
+
+    if input.key().pressed(KeyCode::C) {
+        renderer.center_on(region);
+        handled = true;
+    }
+
+    // Embark on the world tile under the cursor
+    if input.key().pressed(KeyCode::E) {
+        let (mx, my) = state.mouse_position;
+        let scale = CELL_PX * renderer.camera.zoom;
+        let cx = renderer.camera.x + mx / scale;
+        let cy = renderer.camera.y + my / scale;
+        if cx >= 0.0 && cy >= 0.0 && (cx as usize) < region.width && (cy as usize) < region.height {
+            tile_requests.embark = Some(region.cell_to_world_tile(cx as usize, cy as usize));
+            return true;
+        }
+    }
+
+    // Keyboard movement
+    let move_speed = BASE_MOVE_SPEED * get_frame_time() / renderer.camera.zoom.max(1.0);
+    if state.keys_down.contains(&KeyCode::W) { renderer.camera.move_delta(0.0, -move_speed); handled = true; }
+    if state.keys_down.contains(&KeyCode::S) { renderer.camera.move_delta(0.0, move_speed); handled = true; }
+    if state.keys_down.contains(&KeyCode::A) { renderer.camera.move_delta(-move_speed, 0.0); handled = true; }
+    if state.keys_down.contains(&KeyCode::D) { renderer.camera.move_delta(move_speed, 0.0); handled = true; }
+
+    // Zoom around the cursor
+    let wheel = state.mouse_scroll;
+    if wheel != 0.0 {
+        let old_scale = CELL_PX * renderer.camera.zoom;
+        let new_zoom = (renderer.camera.zoom + wheel.signum() * ZOOM_SPEED).clamp(MIN_ZOOM, MAX_ZOOM);
+        let (mx, my) = state.mouse_position;
+        let world_x = renderer.camera.x + mx / old_scale;
+        let world_y = renderer.camera.y + my / old_scale;
+        renderer.camera.set_zoom(new_zoom);
+        let new_scale = CELL_PX * new_zoom;
+        renderer.camera.x = world_x - mx / new_scale;
+        renderer.camera.y = world_y - my / new_scale;
+        handled = true;
+    }
+
+    // Middle mouse drag
+    let (mx, my) = state.mouse_position;
+    if input.mouse().pressed(MouseButton::Middle) {
+        *previous_mouse_x = mx;
+        *previous_mouse_y = my;
+        handled = true;
+    } else if input.mouse().held(MouseButton::Middle) {
+        let inv_scale = 1.0 / (CELL_PX * renderer.camera.zoom);
+        renderer.camera.move_delta(-(mx - *previous_mouse_x) * inv_scale, -(my - *previous_mouse_y) * inv_scale);
+        *previous_mouse_x = mx;
+        *previous_mouse_y = my;
+        handled = true;
+    }
+
+    enforce_camera_boundaries(renderer, region);
+    handled
+}
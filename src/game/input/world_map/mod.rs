@@ -9,6 +9,7 @@ use crate::world::worldmap::world_map::WorldMap;
 use crate::gui::windows::window_manager::WindowManager;
 use crate::game::views::{GameView, world_map as view_world_map};
 use crate::input::manager::InputManager;
+use crate::game::input::TileRequests;
 
 use camera_controls::{
     handle_center_camera,
@@ -22,6 +23,7 @@ use mouse_controls::{
     handle_drag_movement,
     handle_drag_end,
     handle_city_click,
+    handle_tile_requests,
 };
 
 /// Stores the state for world map input handling
@@ -47,6 +49,7 @@ pub fn handle_input(
     world_map_camera: &mut Camera,
     world_map: &WorldMap,
     window_manager: &mut WindowManager,
+    tile_requests: &mut TileRequests,
 ) -> Option<GameView> {
     // Migrate to using proper state management
     let mut drag_state = DragState {
@@ -93,7 +96,12 @@ pub fn handle_input(
         handled = handle_drag_end(input, &mut drag_state);
     }
     
-    // 7. Check for city clicks
+    // 7. Check for region map and embark requests
+    if !handled {
+        handled = handle_tile_requests(input, world_map, world_map_camera, tile_requests);
+    }
+
+    // 8. Check for city clicks
     if !handled {
         view_change = handle_city_click(input, world_map, world_map_camera, window_manager);
     }
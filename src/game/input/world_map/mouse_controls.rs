@@ -5,6 +5,7 @@ use crate::input::event::InputEvent;
 use crate::world::worldmap::world_map::WorldMap;
 use crate::gui::windows::window_manager::WindowManager;
 use crate::game::views::GameView;
+use crate::game::input::TileRequests;
 use crate::game::views::world_map as view_world_map;
 use super::constants::*;
 use super::camera_controls::enforce_camera_boundaries;
@@ -75,7 +76,7 @@ pub fn handle_drag_end(
     false
 }
 
-/// Handles left mouse click on cities
+/// Handles left mouse click on cities and sites
 pub fn handle_city_click(
     input: &InputManager,
     world_map: &WorldMap,
@@ -90,7 +91,48 @@ pub fn handle_city_click(
             mx,
             my,
             window_manager,
-        );
+        ).or_else(|| view_world_map::handle_site_click(
+            world_map,
+            world_map_camera,
+            mx,
+            my,
+            window_manager,
+        ));
     }
     None
-} 
\ No newline at end of file
+} 
+
+/// World tile under the cursor, if any
+fn hovered_tile(input: &InputManager, world_map: &WorldMap, world_map_camera: &Camera) -> Option<(usize, usize)> {
+    let (mx, my) = input.state.mouse_position;
+    let world_x = world_map_camera.x + mx / (TILE_PX * world_map_camera.zoom);
+    let world_y = world_map_camera.y + my / (TILE_PX * world_map_camera.zoom);
+    if world_x < 0.0 || world_y < 0.0 {
+        return None;
+    }
+    let (tx, ty) = (world_x as usize, world_y as usize);
+    (tx < world_map.width && ty < world_map.height).then_some((tx, ty))
+}
+
+/// Handles the region map (R key) and embark (E key) requests for the world tile under the cursor
+pub fn handle_tile_requests(
+    input: &InputManager,
+    world_map: &WorldMap,
+    world_map_camera: &Camera,
+    requests: &mut TileRequests,
+) -> bool {
+    let slot = if input.key().pressed(KeyCode::R) {
+        &mut requests.region
+    } else if input.key().pressed(KeyCode::E) {
+        &mut requests.embark
+    } else {
+        return false;
+    };
+    match hovered_tile(input, world_map, world_map_camera) {
+        Some(tile) => {
+            *slot = Some(tile);
+            true
+        }
+        None => false,
+    }
+}
@@ -3,6 +3,7 @@ pub mod state;
 pub mod input;
 pub mod views;
 pub mod entities;
+pub mod save;
 
 // Re-export important types for external use
 pub use core::Game;
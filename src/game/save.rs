@@ -0,0 +1,497 @@
+//! Saving and loading the local map together with its creatures.
+//!
+//! A save starts with the magic bytes `VXLM` and a format version, followed by the embark,
+//! the world (chunks via the compact chunk encoding, block counts, pending gravity work,
+//! plants, items, stockpiles, the job board, and planned and finished constructions), the creatures with their needs, skills and activity logs, and changed chunks and plants that had been unloaded to region
+//! files. Numbers are little-endian; see [`ByteWriter`] for how each is written.
+use crate::construction::{BuildMaterial, Construction, Structure};
+use crate::creatures::{Creature, Race, Task, ThoughtKind};
+use crate::creatures::needs::Thought;
+use crate::items::{Item, ItemFilter, ItemRegistry, Stockpile};
+use crate::jobs::{Job, JobKind, JobState};
+use crate::world::localmap::binary::{ByteReader, ByteWriter};
+use crate::world::localmap::change::BlockLedger;
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::chunk_codec::{decode_chunk, decode_plants, encode_chunk, encode_plants, read_plant, write_plant};
+use crate::world::localmap::material_registry::MaterialRegistry;
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::region_file::{ChunkKey, Stored};
+use crate::world::localmap::world::World;
+use crate::world::localmap::zlevel::ZLevel;
+use macroquad::prelude::Color;
+use std::io;
+use std::sync::Arc;
+
+pub const SAVE_PATH: &str = "saves/local_map.sav";
+const MAGIC: &[u8; 4] = b"VXLM";
+/// Bumped whenever the layout changes; saves of other versions are refused
+pub const SAVE_VERSION: u32 = 10;
+
+/// Everything needed to pick a local map up where it was left
+pub struct LocalSave {
+    /// Seed the local map was generated with
+    pub seed: u32,
+    /// World map tile the local map was generated from
+    pub embark: (usize, usize),
+    pub world: World,
+    pub creatures: Vec<Creature>,
+    /// Changed chunks and plants that had been unloaded to region files
+    pub stored: Stored,
+}
+
+fn write_chunk(out: &mut ByteWriter, (cx, cy, z): ChunkKey, chunk: &Chunk) {
+    out.i32(cx);
+    out.i32(cy);
+    out.i32(z);
+    out.block(&encode_chunk(chunk));
+}
+
+fn read_chunk(input: &mut ByteReader) -> Option<(ChunkKey, Chunk)> {
+    let key = (input.i32()?, input.i32()?, input.i32()?);
+    Some((key, decode_chunk(input.block()?)?))
+}
+
+fn write_world(out: &mut ByteWriter, world: &World) {
+    out.u64(world.tick);
+    out.u32(world.z_levels.len() as u32);
+    for level in &world.z_levels {
+        out.i32(level.z);
+        out.u32(level.chunks.len() as u32);
+        for (&(cx, cy), chunk) in &level.chunks {
+            write_chunk(out, (cx, cy, level.z), chunk);
+        }
+    }
+
+    out.u32(world.gravity_checks.len() as u32);
+    for &(x, y, z) in &world.gravity_checks {
+        out.i32(x);
+        out.i32(y);
+        out.i32(z);
+    }
+    out.u32(world.collapses.len() as u32);
+    for (&(x, y, z), &frames) in &world.collapses {
+        out.i32(x);
+        out.i32(y);
+        out.i32(z);
+        out.u32(frames);
+    }
+
+    out.u32(world.plants.len() as u32);
+    for plant in &world.plants {
+        write_plant(out, plant);
+    }
+    out.u32(world.next_item_id);
+    out.u32(world.items.len() as u32);
+    for item in &world.items {
+        out.u32(item.id);
+        out.str(&item.kind);
+        out.f32(item.x);
+        out.f32(item.y);
+        out.i32(item.z);
+    }
+    out.u32(world.stockpiles.len() as u32);
+    for stockpile in &world.stockpiles {
+        for corner in [stockpile.min, stockpile.max] {
+            out.i32(corner.x);
+            out.i32(corner.y);
+            out.i32(corner.z);
+        }
+        out.u8(stockpile.filter.bits());
+        out.u8(stockpile.priority);
+    }
+
+    out.u32(world.jobs.next_id());
+    let jobs: Vec<&Job> = world.jobs.iter().collect();
+    out.u32(jobs.len() as u32);
+    for job in jobs {
+        write_job(out, job);
+    }
+
+    for constructions in [&world.blueprints, &world.constructions] {
+        out.u32(constructions.len() as u32);
+        for (pos, construction) in constructions {
+            out.i32(pos.x);
+            out.i32(pos.y);
+            out.i32(pos.z);
+            out.u8(construction.structure as u8);
+            out.u8(construction.material as u8);
+        }
+    }
+}
+
+fn read_construction(input: &mut ByteReader) -> Option<(WorldPos, Construction)> {
+    let pos = WorldPos::new(input.i32()?, input.i32()?, input.i32()?);
+    let structure = *Structure::ALL.get(input.u8()? as usize)?;
+    let material = *BuildMaterial::ALL.get(input.u8()? as usize)?;
+    Some((pos, Construction::new(structure, material)))
+}
+
+fn write_job(out: &mut ByteWriter, job: &Job) {
+    out.u32(job.id);
+    out.u8(job.kind as u8);
+    out.i32(job.pos.x);
+    out.i32(job.pos.y);
+    out.i32(job.pos.z);
+    out.u8(job.priority);
+    out.bool(job.item.is_some());
+    out.u32(job.item.unwrap_or_default());
+    match job.state {
+        JobState::Open => out.u8(0),
+        JobState::Claimed(worker) => {
+            out.u8(1);
+            out.u32(worker);
+        }
+        JobState::Unreachable { since } => {
+            out.u8(2);
+            out.u64(since);
+        }
+    }
+    out.u32(job.progress);
+}
+
+fn read_job(input: &mut ByteReader) -> Option<Job> {
+    let id = input.u32()?;
+    let kind = *JobKind::ALL.get(input.u8()? as usize)?;
+    let pos = WorldPos::new(input.i32()?, input.i32()?, input.i32()?);
+    let mut job = Job::new(id, kind, pos, input.u8()?);
+    let has_item = input.bool()?;
+    let item = input.u32()?;
+    job.item = has_item.then_some(item);
+    job.state = match input.u8()? {
+        0 => JobState::Open,
+        1 => JobState::Claimed(input.u32()?),
+        2 => JobState::Unreachable { since: input.u64()? },
+        _ => return None,
+    };
+    job.progress = input.u32()?;
+    Some(job)
+}
+
+fn read_world(input: &mut ByteReader, materials: Arc<MaterialRegistry>, item_kinds: Arc<ItemRegistry>) -> Option<World> {
+    let mut world = World::new(materials, item_kinds);
+    world.tick = input.u64()?;
+    world.z_levels.clear();
+    for _ in 0..input.u32()? {
+        let mut level = ZLevel::new(input.i32()?);
+        for _ in 0..input.u32()? {
+            let ((cx, cy, _), chunk) = read_chunk(input)?;
+            level.chunks.insert((cx, cy), chunk);
+        }
+        world.z_levels.push(level);
+    }
+
+    world.block_counts = BlockLedger::count(&world.z_levels);
+    for _ in 0..input.u32()? {
+        world.gravity_checks.insert((input.i32()?, input.i32()?, input.i32()?));
+    }
+    for _ in 0..input.u32()? {
+        world.collapses.insert((input.i32()?, input.i32()?, input.i32()?), input.u32()?);
+    }
+
+    for _ in 0..input.u32()? {
+        world.plants.push(read_plant(input)?);
+    }
+    world.next_item_id = input.u32()?;
+    for _ in 0..input.u32()? {
+        let (id, kind) = (input.u32()?, input.str()?);
+        world.items.push(Item::new(id, &kind, input.f32()?, input.f32()?, input.i32()?));
+    }
+    for _ in 0..input.u32()? {
+        let min = WorldPos::new(input.i32()?, input.i32()?, input.i32()?);
+        let max = WorldPos::new(input.i32()?, input.i32()?, input.i32()?);
+        let filter = ItemFilter::from_bits(input.u8()?);
+        world.stockpiles.push(Stockpile::new(min, max, filter, input.u8()?));
+    }
+
+    world.jobs.set_next_id(input.u32()?);
+    for _ in 0..input.u32()? {
+        world.jobs.insert(read_job(input)?);
+    }
+
+    for _ in 0..input.u32()? {
+        let (pos, construction) = read_construction(input)?;
+        world.blueprints.insert(pos, construction);
+    }
+    for _ in 0..input.u32()? {
+        let (pos, construction) = read_construction(input)?;
+        world.constructions.insert(pos, construction);
+    }
+    Some(world)
+}
+
+fn write_creature(out: &mut ByteWriter, creature: &Creature) {
+    out.u32(creature.id);
+    out.str(&creature.name);
+    out.u8(creature.race as u8);
+    out.f32(creature.x);
+    out.f32(creature.y);
+    out.i32(creature.z);
+    out.f32(creature.size);
+    for channel in [creature.color.r, creature.color.g, creature.color.b, creature.color.a] {
+        out.f32(channel);
+    }
+    out.bool(creature.target.is_some());
+    let target = creature.target.unwrap_or(WorldPos::new(0, 0, 0));
+    out.i32(target.x);
+    out.i32(target.y);
+    out.i32(target.z);
+    out.u8(creature.task as u8);
+    out.bool(creature.job.is_some());
+    out.u32(creature.job.unwrap_or_default());
+    out.bool(creature.carrying.is_some());
+    if let Some(item) = &creature.carrying {
+        out.u32(item.id);
+        out.str(&item.kind);
+    }
+    out.f32(creature.health);
+    let needs = &creature.needs;
+    for level in [needs.hunger, needs.thirst, needs.rest, needs.mood] {
+        out.f32(level);
+    }
+    out.u64(needs.retry_at);
+    out.u32(needs.thoughts.len() as u32);
+    for thought in &needs.thoughts {
+        out.u8(thought.kind as u8);
+        out.u64(thought.until);
+    }
+    for xp in creature.skills.xp {
+        out.u32(xp);
+    }
+    out.u32(creature.log.len() as u32);
+    for entry in &creature.log {
+        out.u64(entry.tick);
+        out.str(&entry.text);
+    }
+}
+
+fn read_creature(input: &mut ByteReader) -> Option<Creature> {
+    let id = input.u32()?;
+    let name = input.str()?;
+    let race = *Race::ALL.get(input.u8()? as usize)?;
+    let (x, y, z, size) = (input.f32()?, input.f32()?, input.i32()?, input.f32()?);
+    let color = Color::new(input.f32()?, input.f32()?, input.f32()?, input.f32()?);
+    let mut creature = Creature::new(id, x, y, z, size, color);
+    (creature.name, creature.race) = (name, race);
+    let has_target = input.bool()?;
+    let target = WorldPos::new(input.i32()?, input.i32()?, input.i32()?);
+    creature.target = has_target.then_some(target);
+    creature.task = *Task::ALL.get(input.u8()? as usize)?;
+    let has_job = input.bool()?;
+    let job = input.u32()?;
+    creature.job = has_job.then_some(job);
+    if input.bool()? {
+        let id = input.u32()?;
+        creature.carrying = Some(Item::new(id, &input.str()?, x, y, z));
+    }
+    creature.health = input.f32()?;
+    let needs = &mut creature.needs;
+    (needs.hunger, needs.thirst, needs.rest, needs.mood) = (input.f32()?, input.f32()?, input.f32()?, input.f32()?);
+    needs.retry_at = input.u64()?;
+    for _ in 0..input.u32()? {
+        let kind = *ThoughtKind::ALL.get(input.u8()? as usize)?;
+        needs.thoughts.push(Thought { kind, until: input.u64()? });
+    }
+    for xp in &mut creature.skills.xp {
+        *xp = input.u32()?;
+    }
+    for _ in 0..input.u32()? {
+        let tick = input.u64()?;
+        creature.log(tick, input.str()?);
+    }
+    Some(creature)
+}
+
+/// Encodes a local map save.
+pub fn encode_save(
+    seed: u32,
+    embark: (usize, usize),
+    world: &World,
+    creatures: &[Creature],
+    stored: &Stored,
+) -> Vec<u8> {
+    let mut out = ByteWriter::default();
+    out.bytes.extend_from_slice(MAGIC);
+    out.u32(SAVE_VERSION);
+    out.u32(seed);
+    out.u32(embark.0 as u32);
+    out.u32(embark.1 as u32);
+    write_world(&mut out, world);
+    out.u32(creatures.len() as u32);
+    for creature in creatures {
+        write_creature(&mut out, creature);
+    }
+    out.u32(stored.chunks.len() as u32);
+    for (key, chunk) in &stored.chunks {
+        write_chunk(&mut out, *key, chunk);
+    }
+    out.u32(stored.plants.len() as u32);
+    for ((cx, cy), plants) in &stored.plants {
+        out.i32(*cx);
+        out.i32(*cy);
+        out.block(&encode_plants(plants));
+    }
+    out.bytes
+}
+
+/// Decodes a local map save written by [`encode_save`].
+pub fn decode_save(bytes: &[u8], materials: Arc<MaterialRegistry>, item_kinds: Arc<ItemRegistry>) -> io::Result<LocalSave> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let (magic, rest) = bytes.split_first_chunk::<4>().ok_or_else(|| invalid("not a local map save".into()))?;
+    if magic != MAGIC {
+        return Err(invalid("not a local map save".into()));
+    }
+    let mut input = ByteReader::new(rest);
+    let version = input.u32().ok_or_else(|| invalid("truncated save".into()))?;
+    if version != SAVE_VERSION {
+        return Err(invalid(format!("save version {} is not supported (expected {})", version, SAVE_VERSION)));
+    }
+    let body = || {
+        let seed = input.u32()?;
+        let embark = (input.u32()? as usize, input.u32()? as usize);
+        let world = read_world(&mut input, materials, item_kinds)?;
+        let creatures = (0..input.u32()?).map(|_| read_creature(&mut input)).collect::<Option<Vec<_>>>()?;
+        let chunks = (0..input.u32()?).map(|_| read_chunk(&mut input)).collect::<Option<Vec<_>>>()?;
+        let plants = (0..input.u32()?)
+            .map(|_| Some(((input.i32()?, input.i32()?), decode_plants(input.block()?)?)))
+            .collect::<Option<Vec<_>>>()?;
+        let stored = Stored { chunks, plants };
+        Some(LocalSave { seed, embark, world, creatures, stored })
+    };
+    let save = body().ok_or_else(|| invalid("corrupt or truncated save".into()))?;
+    if !input.is_empty() {
+        return Err(invalid("unexpected data after the end of the save".into()));
+    }
+    Ok(save)
+}
+
+pub fn write_save(
+    seed: u32,
+    embark: (usize, usize),
+    world: &World,
+    creatures: &[Creature],
+    stored: &Stored,
+) -> io::Result<()> {
+    if let Some(dir) = std::path::Path::new(SAVE_PATH).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(SAVE_PATH, encode_save(seed, embark, world, creatures, stored))
+}
+
+pub fn read_save(materials: Arc<MaterialRegistry>, item_kinds: Arc<ItemRegistry>) -> io::Result<LocalSave> {
+    decode_save(&std::fs::read(SAVE_PATH)?, materials, item_kinds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::construction::Structure;
+    use crate::creatures::Task;
+    use crate::items::ItemCategory;
+    use crate::items::registry::ITEMS_PATH;
+    use crate::jobs::job::DEFAULT_PRIORITY;
+    use crate::plants::{Plant, Species};
+    use crate::world::localmap::material_registry::MATERIALS_PATH;
+    use crate::world::localmap::subpixel::Subpixel;
+    use crate::world::localmap::terrain_material::TerrainMaterial;
+
+    fn registries() -> (Arc<MaterialRegistry>, Arc<ItemRegistry>) {
+        let materials = MaterialRegistry::from_json(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap();
+        let item_kinds = ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap();
+        (Arc::new(materials), Arc::new(item_kinds))
+    }
+
+    #[test]
+    fn a_local_map_survives_a_round_trip() {
+        let (materials, item_kinds) = registries();
+        let mut world = World::new(materials.clone(), item_kinds.clone());
+        world.tick = 1234;
+        for z in [-1, 0] {
+            let mut chunk = Chunk::new();
+            for x in 0..32 {
+                chunk.set(x, 5, Subpixel { material: TerrainMaterial::Rock, dig_target: x % 3 == 0, level: 0 });
+            }
+            chunk.set(9, 9, Subpixel { material: TerrainMaterial::Water, dig_target: false, level: 5 });
+            world.insert_chunk(z, (0, z), chunk);
+        }
+        world.gravity_checks.insert((1, 2, 0));
+        world.collapses.insert((3, 4, 0), 7);
+        world.plants.push(Plant::new(20, 20, 0, Species::Oak, 0.75));
+        let wood = world.drop_item("wood", 10.5, 12.0, 0);
+        world.stockpiles.push(Stockpile::new(
+            WorldPos::new(0, 0, 0),
+            WorldPos::new(4, 6, 0),
+            ItemFilter::only(ItemCategory::Wood),
+            4,
+        ));
+        let dig = world.jobs.post(JobKind::Dig, WorldPos::new(3, 5, 0), DEFAULT_PRIORITY);
+        world.jobs.post_haul(wood, WorldPos::new(1, 1, 0), 2);
+        world.jobs.get_mut(dig).unwrap().state = JobState::Claimed(1);
+        world.jobs.get_mut(dig).unwrap().progress = 40;
+        world.blueprints.insert(WorldPos::new(6, 6, 0), Construction::new(Structure::Wall, BuildMaterial::Stone));
+        world.constructions.insert(WorldPos::new(7, 6, 0), Construction::new(Structure::Bed, BuildMaterial::Wood));
+
+        let mut creature = Creature::new(1, 3.5, 4.5, 0, 1.0, Color::new(0.1, 0.2, 0.3, 1.0));
+        creature.task = Task::Dig;
+        creature.job = Some(dig);
+        creature.target = Some(WorldPos::new(3, 5, 0));
+        creature.needs.hunger = 0.4;
+        creature.needs.thoughts.push(Thought { kind: ThoughtKind::Ate, until: 2000 });
+        creature.skills.xp[0] = 55;
+        creature.log(1200, "Started digging".to_string());
+
+        let stored = Stored {
+            chunks: vec![((5, 5, 0), Chunk::new())],
+            plants: vec![((5, 5), vec![(Plant::new(170, 170, 0, Species::Pine, 1.0), 0)])],
+        };
+        let bytes = encode_save(42, (3, 4), &world, std::slice::from_ref(&creature), &stored);
+        let save = decode_save(&bytes, materials, item_kinds).unwrap();
+
+        assert_eq!((save.seed, save.embark), (42, (3, 4)));
+        let loaded = &save.world;
+        assert_eq!(loaded.tick, 1234);
+        for z in [-1, 0] {
+            let (a, b) = (world.z_level(z).unwrap(), loaded.z_level(z).unwrap());
+            assert_eq!(encode_chunk(&a.chunks[&(0, z)]), encode_chunk(&b.chunks[&(0, z)]));
+        }
+        for material in TerrainMaterial::ALL {
+            assert_eq!(world.block_counts.get(material), loaded.block_counts.get(material), "{:?}", material);
+        }
+        assert!(loaded.gravity_checks.contains(&(1, 2, 0)));
+        assert_eq!(loaded.collapses[&(3, 4, 0)], 7);
+        assert_eq!((loaded.plants.len(), loaded.plants[0].growth), (1, 0.75));
+        let item = &loaded.items[0];
+        assert_eq!((item.id, item.kind.as_str(), item.x, item.y, item.z), (wood, "wood", 10.5, 12.0, 0));
+        assert_eq!(loaded.next_item_id, world.next_item_id);
+        let stockpile = &loaded.stockpiles[0];
+        assert_eq!((stockpile.min, stockpile.max), (WorldPos::new(0, 0, 0), WorldPos::new(4, 6, 0)));
+        assert_eq!((stockpile.filter, stockpile.priority), (ItemFilter::only(ItemCategory::Wood), 4));
+        assert_eq!(loaded.jobs.next_id(), world.jobs.next_id());
+        for job in world.jobs.iter() {
+            let other = loaded.jobs.get(job.id).unwrap();
+            assert_eq!((other.kind, other.pos, other.item), (job.kind, job.pos, job.item));
+            assert_eq!((other.priority, other.state, other.progress), (job.priority, job.state, job.progress));
+        }
+        assert_eq!(loaded.blueprints, world.blueprints);
+        assert_eq!(loaded.constructions, world.constructions);
+
+        let other = &save.creatures[0];
+        assert_eq!((other.id, &other.name, other.race), (creature.id, &creature.name, creature.race));
+        assert_eq!((other.x, other.y, other.z, other.color), (creature.x, creature.y, creature.z, creature.color));
+        assert_eq!((other.task, other.job, other.target), (creature.task, creature.job, creature.target));
+        assert_eq!((other.needs.hunger, other.needs.thoughts.len()), (0.4, 1));
+        assert_eq!(other.skills.xp, creature.skills.xp);
+        assert_eq!(other.log.back().map(|entry| entry.text.as_str()), Some("Started digging"));
+
+        assert_eq!(save.stored.chunks.len(), 1);
+        assert_eq!(save.stored.plants[0].1[0].0.species, Species::Pine);
+    }
+
+    #[test]
+    fn saves_of_other_versions_are_refused() {
+        let (materials, item_kinds) = registries();
+        let world = World::new(materials.clone(), item_kinds.clone());
+        let mut bytes = encode_save(1, (0, 0), &world, &[], &Stored::default());
+        bytes[4] = bytes[4].wrapping_add(1);
+        assert!(decode_save(&bytes, materials, item_kinds).is_err());
+    }
+}
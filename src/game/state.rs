@@ -1,3 +1,5 @@
+use crate::gui::GuiState;
+use crate::world::localmap::material_registry::MaterialRegistry;
 use crate::world::localmap::zlevel::ZLevel;
 
 /// An immutable snapshot of all data needed for rendering.
@@ -6,5 +8,7 @@ pub struct GameState<'a> {
     pub camera_y: f32,
     pub zoom: f32,
     pub z_levels: &'a [ZLevel],
-    // In the future, you can add creatures, particles, gui, etc.
+    pub materials: &'a MaterialRegistry,
+    pub gui: &'a GuiState,
+    // In the future, you can add creatures, particles, etc.
 } 
\ No newline at end of file
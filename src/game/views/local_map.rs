@@ -1,12 +1,19 @@
+use crate::game::input::local_map::designation::{Brush, DesignationSettings};
+use crate::game::input::local_map::history::Command;
 use crate::game::state::GameState;
+use crate::items::Stockpile;
+use crate::jobs::JobBoard;
 use crate::renderer::local_map_renderer::LocalMapRenderer;
-use crate::world::localmap::world::World;
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::world::{World, SURFACE_Z};
 use crate::particle::Particle;
 use macroquad::prelude::*;
+use std::collections::BTreeMap;
 
 pub fn render(
-    local_map_renderer: &LocalMapRenderer,
+    local_map_renderer: &mut LocalMapRenderer,
     state: &GameState,
+    world: &World,
     creatures: &[crate::creatures::Creature],
     particles: &[Particle],
     window_manager: &mut crate::gui::windows::window_manager::WindowManager,
@@ -14,13 +21,91 @@ pub fn render(
     // Render local map
     local_map_renderer.draw(state);
     
-    // Draw creatures
-    for creature in creatures {
+    // Draw plants and items on the shown level, skipping plants off screen
+    let (view_x, view_y) = (local_map_renderer.get_camera_x(), local_map_renderer.get_camera_y());
+    let view_w = screen_width() / local_map_renderer.get_zoom();
+    let view_h = screen_height() / local_map_renderer.get_zoom();
+    let on_screen = |x: i32, y: i32| {
+        let (x, y) = (x as f32, y as f32);
+        x > view_x - 8.0 && x < view_x + view_w + 8.0 && y > view_y - 8.0 && y < view_y + view_h + 8.0
+    };
+    for plant in world.plants.iter().filter(|p| p.z == local_map_renderer.current_z && on_screen(p.x, p.y)) {
+        plant.draw(
+            local_map_renderer.get_camera_x(),
+            local_map_renderer.get_camera_y(),
+            local_map_renderer.get_zoom(),
+        );
+    }
+    // Stockpile zones under the items they hold, and the one being dragged out
+    let zoom = local_map_renderer.get_zoom();
+    let zone_rect = |min: WorldPos, max: WorldPos| {
+        (
+            (min.x as f32 - view_x) * zoom,
+            (min.y as f32 - view_y) * zoom,
+            (max.x - min.x + 1) as f32 * zoom,
+            (max.y - min.y + 1) as f32 * zoom,
+        )
+    };
+    for stockpile in world.stockpiles.iter().filter(|s| s.min.z == local_map_renderer.current_z) {
+        let (x, y, w, h) = zone_rect(stockpile.min, stockpile.max);
+        draw_rectangle(x, y, w, h, Color::new(0.9, 0.8, 0.3, 0.15));
+        draw_rectangle_lines(x, y, w, h, 2.0, Color::new(0.9, 0.8, 0.3, 0.8));
+    }
+    if let Some(start) = state.gui.designation.zone_start {
+        let (mouse_x, mouse_y) = mouse_position();
+        let cursor = WorldPos::from_f32(view_x + mouse_x / zoom, view_y + mouse_y / zoom, start.z);
+        let pending = Stockpile::new(start, cursor, state.gui.designation.stockpile_filter, state.gui.designation.priority);
+        let (x, y, w, h) = zone_rect(pending.min, pending.max);
+        draw_rectangle_lines(x, y, w, h, 2.0, WHITE);
+    }
+
+    for item in world.items.iter().filter(|i| i.z == local_map_renderer.current_z) {
+        item.draw(
+            local_map_renderer.get_camera_x(),
+            local_map_renderer.get_camera_y(),
+            local_map_renderer.get_zoom(),
+            world.item_kinds.color(&item.kind),
+        );
+    }
+
+    // Planned constructions, faintly in the colour they will have
+    for (&pos, construction) in &world.blueprints {
+        if pos.z != local_map_renderer.current_z || !on_screen(pos.x, pos.y) {
+            continue;
+        }
+        let sx = (pos.x as f32 - view_x) * local_map_renderer.get_zoom();
+        let sy = (pos.y as f32 - view_y) * local_map_renderer.get_zoom();
+        let size = local_map_renderer.get_zoom();
+        let color = world.materials.color(construction.structure.material(), pos.x, pos.y);
+        draw_rectangle(sx, sy, size, size, Color { a: 0.4, ..color });
+    }
+
+    // Designations nobody could find a way to
+    let unreachable_overlay = Color::new(1.0, 0.6, 0.0, 0.7);
+    for job in world.jobs.iter().filter(|job| JobBoard::is_unreachable(job, world.tick)) {
+        let pos = job.pos;
+        if pos.z != local_map_renderer.current_z || !on_screen(pos.x, pos.y) {
+            continue;
+        }
+        let sx = (pos.x as f32 - view_x) * local_map_renderer.get_zoom();
+        let sy = (pos.y as f32 - view_y) * local_map_renderer.get_zoom();
+        let size = local_map_renderer.get_zoom();
+        draw_rectangle(sx, sy, size, size, unreachable_overlay);
+    }
+
+    // Draw creatures on the shown level, ringing the one the Worker Info window is about
+    let selected = window_manager.worker_info.selected_worker;
+    for creature in creatures.iter().filter(|c| c.z == local_map_renderer.current_z) {
         creature.draw(
             local_map_renderer.get_camera_x(),
             local_map_renderer.get_camera_y(),
             local_map_renderer.get_zoom(),
         );
+        if selected == Some(creature.id) {
+            let sx = (creature.x - view_x) * zoom;
+            let sy = (creature.y - view_y) * zoom;
+            draw_circle_lines(sx, sy, (creature.size + 0.75) * zoom, 2.0, WHITE);
+        }
     }
     
     // Draw particles
@@ -32,8 +117,83 @@ pub fn render(
         draw_circle(sx, sy, 0.2 * local_map_renderer.get_zoom(), YELLOW);
     }
     
+    // Current level indicator
+    let z = local_map_renderer.current_z;
+    let label = if z == SURFACE_Z { "Surface".to_string() } else { format!("{} below surface", -z) };
+    draw_text(&format!("Z {} ({}) - PageUp/PageDown", z, label), 10.0, screen_height() - 10.0, 20.0, WHITE);
+    if state.gui.show_ui {
+        let jobs = format!(
+            "Dig jobs: {} - dug {} - build jobs: {} - building {} (B/M) - haul jobs: {} - stockpiles take {} (F) - priority {} (1-5)",
+            state.gui.dig_jobs,
+            state.gui.dug,
+            state.gui.build_jobs,
+            state.gui.designation.construction.name(),
+            state.gui.haul_jobs,
+            state.gui.designation.stockpile_filter.name(),
+            state.gui.designation.priority
+        );
+        draw_text(&jobs, 10.0, screen_height() - 54.0, 20.0, WHITE);
+        draw_stockpile_list(world);
+    }
+
+    // Material under the cursor
+    let (mouse_x, mouse_y) = mouse_position();
+    let hover_x = (local_map_renderer.get_camera_x() + mouse_x / local_map_renderer.get_zoom()).floor() as i32;
+    let hover_y = (local_map_renderer.get_camera_y() + mouse_y / local_map_renderer.get_zoom()).floor() as i32;
+    if let Some(subpixel) = world.get_subpixel(hover_x, hover_y, z) {
+        let props = state.materials.get(subpixel.material);
+        let hover = WorldPos::new(hover_x, hover_y, z);
+        let mut info = match world.constructions.get(&hover) {
+            Some(construction) => format!("Built {}", construction.name()),
+            None => props.name.clone(),
+        };
+        if props.solid {
+            info += &format!(" - hardness {}", props.hardness);
+        } else if props.liquid {
+            info += " - liquid";
+        }
+        if let Some(drops) = &props.drops {
+            info += &format!(", drops {}", drops);
+        }
+        if props.flammability > 0.0 {
+            info += ", flammable";
+        }
+        info += &format!(" ({} loaded)", world.block_counts.get(subpixel.material));
+        if let Some(planned) = world.blueprints.get(&hover) {
+            info += &format!(", {} planned", planned.name());
+        }
+        draw_text(&info, 10.0, screen_height() - 32.0, 20.0, WHITE);
+    }
+
     // Draw worker info window
-    crate::gui::windows::worker_info::draw_worker_info_window(&mut window_manager.worker_info);
+    crate::gui::windows::worker_info::draw_worker_info_window(&mut window_manager.worker_info, creatures, world);
+}
+
+/// Lists every stockpile with how full it is and what it holds, in the top right corner.
+fn draw_stockpile_list(world: &World) {
+    let mut y = 20.0;
+    for (i, stockpile) in world.stockpiles.iter().enumerate() {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for item in world.items.iter().filter(|item| stockpile.holds(item, world.category(item))) {
+            *counts.entry(item.kind.as_str()).or_default() += 1;
+        }
+        let used: usize = counts.values().sum();
+        let mut line = format!(
+            "Stockpile {} ({}, z {}): {}/{}",
+            i + 1,
+            stockpile.filter.name(),
+            stockpile.min.z,
+            used,
+            stockpile.capacity()
+        );
+        if !counts.is_empty() {
+            let held: Vec<String> = counts.iter().map(|(kind, count)| format!("{} {}", kind, count)).collect();
+            line += &format!(" - {}", held.join(", "));
+        }
+        let width = measure_text(&line, None, 18, 1.0).width;
+        draw_text(&line, screen_width() - width - 10.0, y, 18.0, WHITE);
+        y += 20.0;
+    }
 }
 
 pub fn paint_with_mouse(
@@ -41,16 +201,21 @@ pub fn paint_with_mouse(
     local_map_renderer: &LocalMapRenderer,
     mouse_x: f32,
     mouse_y: f32,
-    left_button: bool,
-    right_button: bool,
-) {
+    brush: Brush,
+    designation: &DesignationSettings,
+) -> Command {
     let mouse_world_x = local_map_renderer.get_camera_x() + mouse_x / local_map_renderer.get_zoom();
     let mouse_world_y = local_map_renderer.get_camera_y() + mouse_y / local_map_renderer.get_zoom();
+    let (x, y, z) = (mouse_world_x as i32, mouse_world_y as i32, local_map_renderer.current_z);
+    let priority = designation.priority;
 
-    if left_button {
-        crate::player::actions::paint_rock(world, mouse_world_x as i32, mouse_world_y as i32);
-    }
-    if right_button {
-        crate::player::actions::paint_dig_target(world, mouse_world_x as i32, mouse_world_y as i32);
+    let mut command = Command { priority, ..Command::default() };
+    match brush {
+        Brush::Build => command.planned = crate::player::actions::plan_construction(world, x, y, z, designation.construction, priority),
+        Brush::Rock => crate::player::actions::paint_rock(world, x, y, z),
+        Brush::Dig => command.designated = crate::player::actions::paint_dig_target(world, x, y, z, priority),
+        Brush::Chop => command.chop = crate::player::actions::designate_chop(world, x, y, z, priority),
+        Brush::Vein => command.designated = crate::player::actions::designate_vein(world, x, y, z, priority),
     }
-} 
\ No newline at end of file
+    command
+}
@@ -5,10 +5,12 @@ pub enum GameView {
     WorldMap,
     LocalMap,
     CityInfo,
+    SiteInfo,
     RegionMap,
 }
 
 pub mod world_map;
 pub mod local_map;
 pub mod city_info;
+pub mod site_info;
 pub mod main_menu; 
\ No newline at end of file
@@ -0,0 +1,38 @@
+use crate::gui::windows::site_info::site_info_window;
+use crate::gui::windows::window_manager::WindowManager;
+use crate::world::worldmap::world_map::WorldMap;
+use crate::renderer::world_map_renderer::WorldMapRenderer;
+use crate::renderer::camera::Camera;
+
+pub fn render(
+    world_map: &WorldMap,
+    world_map_renderer: &WorldMapRenderer,
+    world_map_camera: &Camera,
+    window_manager: &mut WindowManager,
+) -> Option<super::GameView> {
+    // Draw world map in background
+    world_map_renderer.draw_world_map_with_view(
+        world_map,
+        world_map_camera,
+        crate::renderer::world_map_renderer::MapView::Biome,
+        world_map.sea_level,
+    );
+
+    crate::gui::windows::worldgen::draw_worldgen_window(&mut window_manager.worldgen);
+
+    let site_info_state = &mut window_manager.site_info;
+    let site_option = site_info_state.selected_site.clone();
+
+    if let Some(site) = site_option {
+        site_info_window(site_info_state, &site, world_map);
+
+        // If the site info window was closed, return to WorldMap view
+        if !site_info_state.show {
+            return Some(super::GameView::WorldMap);
+        }
+    } else {
+        return Some(super::GameView::WorldMap);
+    }
+
+    None
+}
@@ -43,4 +43,29 @@ pub fn handle_city_click(
         return Some(super::GameView::CityInfo);
     }
     None
-} 
\ No newline at end of file
+} 
+
+pub fn handle_site_click(
+    world_map: &WorldMap,
+    world_map_camera: &Camera,
+    mouse_x: f32,
+    mouse_y: f32,
+    window_manager: &mut WindowManager,
+) -> Option<super::GameView> {
+    const TILE_PX: f32 = 8.0;
+    let world_x = world_map_camera.x + mouse_x / (TILE_PX * world_map_camera.zoom);
+    let world_y = world_map_camera.y + mouse_y / (TILE_PX * world_map_camera.zoom);
+
+    // Find site under cursor (within a radius)
+    let site_radius = 0.5; // in world units
+    if let Some(site) = world_map.sites.iter().find(|site| {
+        let dx = site.x as f32 + 0.5 - world_x;
+        let dy = site.y as f32 + 0.5 - world_y;
+        (dx * dx + dy * dy).sqrt() < site_radius
+    }).cloned() {
+        window_manager.site_info.selected_site = Some(site);
+        window_manager.site_info.show = true;
+        return Some(super::GameView::SiteInfo);
+    }
+    None
+}
\ No newline at end of file
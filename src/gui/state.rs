@@ -1,3 +1,5 @@
+use crate::world::localmap::change::{ChangeCause, WorldChange};
+use crate::game::input::local_map::designation::DesignationSettings;
 use crate::world::localmap::world::World;
 use crate::game::input::RenderMode;
 use crate::input::manager::InputManager;
@@ -8,6 +10,12 @@ pub struct GuiState {
     pub show_ui: bool,
     pub paused: bool,
     pub dig_jobs: usize,
+    pub build_jobs: usize,
+    pub haul_jobs: usize,
+    /// Priority and construction new designations are made with
+    pub designation: DesignationSettings,
+    /// Subpixels dug out since the map was entered
+    pub dug: usize,
 }
 
 impl GuiState {
@@ -16,9 +24,18 @@ impl GuiState {
             show_ui: true,
             paused: false,
             dig_jobs: 0,
+            build_jobs: 0,
+            haul_jobs: 0,
+            designation: DesignationSettings::default(),
+            dug: 0,
         }
     }
 
+    /// Tallies what changed on the map this frame.
+    pub fn record_changes(&mut self, changes: &[WorldChange]) {
+        self.dug += changes.iter().filter(|change| change.cause == ChangeCause::Dig).count();
+    }
+
     pub fn update(&mut self, world: &World, render_mode: RenderMode, input: &InputManager) {
         if input.key().pressed(KeyCode::Tab) {
             self.show_ui = !self.show_ui;
@@ -5,12 +5,13 @@ use crate::world::worldmap::civilization::Civilization;
 use crate::world::worldmap::biome::BiomeId;
 use crate::gui::windows::city_info::portraits::CivPortraits;
 use crate::world::worldmap::world_map::WorldMap;
+use crate::world::worldmap::transport::TravelMode;
 use crate::gui::windows::city_info::state::CityInfoState;
 
 pub fn city_info_window(state: &mut CityInfoState, city: &City, portraits: &CivPortraits, world_map: &WorldMap) {
     if !state.show { return; }
     let win_pos = vec2(900.0, 80.0);
-    let win_size = vec2(420.0, 340.0);
+    let win_size = vec2(420.0, 460.0);
     city_info_window_at(state, city, portraits, win_pos, win_size, world_map);
 }
 
@@ -41,6 +42,15 @@ pub fn city_info_window_at(state: &mut CityInfoState, city: &City, portraits: &C
         ui.label(None, &format!("City: {}", city.name));
         ui.label(None, &format!("Population: {}", city.population));
         ui.label(None, &format!("Coordinates: ({}, {})", city.x, city.y));
+        ui.label(None, &format!("Tier: {:?}", city.tier));
+        ui.label(None, &format!("Founded: year {}", city.founding_year));
+        let mut features = Vec::new();
+        if city.is_port { features.push("Port"); }
+        if city.is_river_town { features.push("River town"); }
+        if city.is_fortress { features.push("Fortress"); } else if city.has_walls { features.push("Walled"); }
+        if !features.is_empty() {
+            ui.label(None, &format!("Features: {}", features.join(", ")));
+        }
         ui.separator();
         // Civilization info
         let civ = city.civ;
@@ -52,12 +62,17 @@ pub fn city_info_window_at(state: &mut CityInfoState, city: &City, portraits: &C
         let biome_names: Vec<String> = biomes.iter().map(|b| format!("{:?}", b)).collect();
         ui.label(None, &format!("Preferred Biomes: {}", biome_names.join(", ")));
         // --- Civilization extended info ---
-        if let Some(civ_instance) = &world_map.civilization_map[city.x][city.y] {
-            ui.separator();
-            ui.label(None, &format!("Alignment: {:?}", civ_instance.culture.alignment));
-            ui.label(None, &format!("Tradition: {}", civ_instance.culture.tradition));
-            ui.label(None, &format!("Religion: {}", civ_instance.culture.religion));
-            ui.label(None, &format!("Trait: {:?}", civ_instance.culture.trait_));
+        let culture = &city.governor.culture;
+        ui.separator();
+        ui.label(None, &format!("Alignment: {:?}", culture.alignment));
+        ui.label(None, &format!("Tradition: {}", culture.tradition));
+        ui.label(None, &format!("Religion: {}", culture.religion));
+        ui.label(None, &format!("Trait: {:?}", culture.trait_));
+        // Notable buildings
+        ui.separator();
+        ui.label(None, "Notable Buildings:");
+        for building in &city.buildings {
+            ui.label(None, &format!("  {}", building.name()));
         }
         // Show relations to other civs
         ui.separator();
@@ -80,6 +95,28 @@ pub fn city_info_window_at(state: &mut CityInfoState, city: &City, portraits: &C
                 }
             }
         }
+        // Trade partners reachable over the transport network
+        ui.separator();
+        ui.label(None, "Routes:");
+        for route in &world_map.trade_routes {
+            let other = if route.from == (city.x, city.y) {
+                route.to
+            } else if route.to == (city.x, city.y) {
+                route.from
+            } else {
+                continue;
+            };
+            let name = world_map.cities.iter()
+                .find(|c| (c.x, c.y) == other)
+                .map(|c| c.name.as_str())
+                .unwrap_or("Unknown");
+            let describe = |mode| match world_map.transport.shortest_route((city.x, city.y), other, mode) {
+                Some(r) => format!("{} tiles, cost {:.1}", r.path.len(), r.cost),
+                None => "no route".to_string(),
+            };
+            ui.label(None, &format!("  {}: {}", name, describe(TravelMode::Any)));
+            ui.label(None, &format!("    by road: {} - by water: {}", describe(TravelMode::Land), describe(TravelMode::Water)));
+        }
         ui.separator();
         // Close button
         if ui.button(None, "Close") {
@@ -1,6 +1,7 @@
 pub mod window_state;
 pub mod main_menu;
 pub mod city_info;
+pub mod site_info;
 pub mod window_manager;
 pub mod worldgen;
 pub mod worker_info;
@@ -0,0 +1,28 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+use crate::world::worldmap::site::Site;
+use crate::world::worldmap::world_map::WorldMap;
+use crate::gui::windows::site_info::state::SiteInfoState;
+
+pub fn site_info_window(state: &mut SiteInfoState, site: &Site, world_map: &WorldMap) {
+    if !state.show { return; }
+    let win_pos = vec2(900.0, 80.0);
+    let win_size = vec2(420.0, 240.0);
+    root_ui().window(hash!("site_info_window"), win_pos, win_size, |ui| {
+        ui.label(None, &site.name);
+        ui.label(None, &format!("Type: {:?}", site.kind));
+        ui.label(None, &format!("Coordinates: ({}, {})", site.x, site.y));
+        ui.label(None, &format!("Biome: {:?}", world_map.biomes[site.x][site.y]));
+        match site.civ {
+            Some(civ) => ui.label(None, &format!("Held by: {:?}", civ)),
+            None => ui.label(None, "Held by: nobody (wilderness)"),
+        }
+        ui.separator();
+        ui.label(None, &site.description);
+        ui.label(None, &format!("Local map: {:?}", site.local_hint));
+        ui.separator();
+        if ui.button(None, "Close") {
+            state.show = false;
+        }
+    });
+}
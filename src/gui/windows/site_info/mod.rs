@@ -0,0 +1,5 @@
+pub mod state;
+pub mod core;
+
+pub use state::*;
+pub use core::*;
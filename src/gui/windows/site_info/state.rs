@@ -0,0 +1,23 @@
+use crate::gui::windows::window_state::WindowState;
+use crate::world::worldmap::site::Site;
+
+pub struct SiteInfoState {
+    pub show: bool,
+    pub selected_site: Option<Site>,
+}
+
+impl SiteInfoState {
+    pub fn new() -> Self {
+        Self {
+            show: false,
+            selected_site: None,
+        }
+    }
+}
+
+impl WindowState for SiteInfoState {
+    fn is_visible(&self) -> bool { self.show }
+    fn show(&mut self) { self.show = true; }
+    fn hide(&mut self) { self.show = false; }
+    fn toggle(&mut self) { self.show = !self.show; }
+}
@@ -1,5 +1,6 @@
 use crate::gui::windows::main_menu::MainMenuState;
 use crate::gui::windows::city_info::CityInfoState;
+use crate::gui::windows::site_info::SiteInfoState;
 use crate::gui::windows::worldgen::WorldGenWindowState;
 use crate::gui::windows::worldgen::draw_worldgen_window;
 use crate::gui::windows::worker_info::WorkerInfoState;
@@ -10,6 +11,7 @@ use crate::gui::windows::window_state::WindowState;
 pub struct WindowManager {
     pub main_menu: MainMenuState,
     pub city_info: CityInfoState,
+    pub site_info: SiteInfoState,
     pub worldgen: WorldGenWindowState,
     pub worker_info: WorkerInfoState,
     // Add other window states here as needed
@@ -20,6 +22,7 @@ impl WindowManager {
         Self {
             main_menu: MainMenuState::new(),
             city_info: CityInfoState::new(),
+            site_info: SiteInfoState::new(),
             worldgen: WorldGenWindowState::new(),
             worker_info: WorkerInfoState::new(),
             // Initialize other windows here
@@ -1,11 +1,80 @@
 use macroquad::prelude::*;
 use macroquad::ui::{hash, root_ui};
+use crate::creatures::creature::MAX_HEALTH;
+use crate::creatures::{Creature, Need, Skill};
 use crate::gui::windows::worker_info::state::WorkerInfoState;
+use crate::world::localmap::world::World;
 
-pub fn draw_worker_info_window(state: &mut WorkerInfoState) {
+pub fn draw_worker_info_window(state: &mut WorkerInfoState, creatures: &[Creature], world: &World) {
+    if !state.show { return; }
+    let Some(creature) = state.selected_worker.and_then(|id| creatures.iter().find(|c| c.id == id)) else {
+        // Nobody selected, or they died
+        state.deselect();
+        return;
+    };
     let win_pos = vec2(40.0, 40.0);
-    let win_size = vec2(300.0, 200.0);
+    let win_size = vec2(380.0, 480.0);
     root_ui().window(hash!("worker_info_window"), win_pos, win_size, |ui| {
-        ui.label(None, "[Worker Info Window - TODO]");
+        ui.label(None, &format!("{}, {}", creature.name, creature.race.name()));
+        ui.label(None, &format!("Health: {:.0}/{:.0}", creature.health, MAX_HEALTH));
+        ui.separator();
+
+        ui.label(None, &format!("Doing: {}", creature.task.name()));
+        match creature.job.and_then(|id| world.jobs.get(id)) {
+            Some(job) => ui.label(
+                None,
+                &format!("Job: {} at ({}, {}), priority {}, {} done", job.kind.name(), job.pos.x, job.pos.y, job.priority, job.progress),
+            ),
+            None => ui.label(None, "Job: none"),
+        }
+        match creature.target {
+            Some(target) => ui.label(None, &format!("Heading for ({}, {}, {}), {} steps to go", target.x, target.y, target.z, creature.path.len())),
+            None => ui.label(None, "Heading nowhere"),
+        }
+        ui.separator();
+
+        let needs = &creature.needs;
+        ui.label(None, &format!("Mood: {} ({:.0}), surroundings {:+.0}", needs.mood_name(), needs.mood, needs.surroundings));
+        let levels: Vec<String> = Need::ALL.iter().map(|&need| format!("{} {:.0}", need.name(), needs.level(need))).collect();
+        ui.label(None, &levels.join(", "));
+        if !needs.thoughts.is_empty() {
+            let thoughts: Vec<&str> = needs.thoughts.iter().map(|thought| thought.kind.describe()).collect();
+            ui.label(None, &format!("Recently {}", thoughts.join(", ")));
+        }
+        ui.separator();
+
+        for skill in Skill::ALL {
+            ui.label(None, &format!("{}: level {} ({} xp)", skill.name(), creature.skills.level(skill), creature.skills.xp[skill as usize]));
+        }
+        ui.separator();
+
+        match &creature.carrying {
+            Some(item) => ui.label(None, &format!("Carrying: {}", item.kind)),
+            None => ui.label(None, "Carrying: nothing"),
+        }
+        ui.separator();
+
+        ui.label(None, "Recent activity:");
+        if creature.log.is_empty() {
+            ui.label(None, "  nothing yet");
+        }
+        for entry in creature.log.iter().rev() {
+            let ago = world.tick.saturating_sub(entry.tick) / 60;
+            ui.label(None, &format!("  {}s ago: {}", ago, entry.text));
+        }
+        ui.separator();
+
+        let follow = if state.follow { "Stop following" } else { "Follow" };
+        if ui.button(None, follow) {
+            state.follow = !state.follow;
+        }
+        ui.same_line(0.0);
+        if creature.job.is_some() && ui.button(None, "Cancel job") {
+            state.cancel_requested = true;
+        }
+        ui.same_line(0.0);
+        if ui.button(None, "Close") {
+            state.deselect();
+        }
     });
-} 
\ No newline at end of file
+}
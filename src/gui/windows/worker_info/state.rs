@@ -1,6 +1,13 @@
+use crate::jobs::WorkerId;
+
 pub struct WorkerInfoState {
     pub show: bool,
-    pub selected_worker: Option<usize>, // Placeholder for worker ID or struct
+    /// The worker the window is about
+    pub selected_worker: Option<WorkerId>,
+    /// Keep the camera on the selected worker
+    pub follow: bool,
+    /// Set by the Cancel job button; the game calls the job off on its next update
+    pub cancel_requested: bool,
 }
 
 impl WorkerInfoState {
@@ -8,10 +15,30 @@ impl WorkerInfoState {
         Self {
             show: false,
             selected_worker: None,
+            follow: false,
+            cancel_requested: false,
         }
     }
     pub fn is_visible(&self) -> bool { self.show }
     pub fn show(&mut self) { self.show = true; }
     pub fn hide(&mut self) { self.show = false; }
     pub fn toggle(&mut self) { self.show = !self.show; }
-} 
\ No newline at end of file
+
+    /// Opens the window on a worker; following stops if it was someone else.
+    pub fn select(&mut self, worker: WorkerId) {
+        if self.selected_worker != Some(worker) {
+            self.follow = false;
+            self.cancel_requested = false;
+        }
+        self.selected_worker = Some(worker);
+        self.show = true;
+    }
+
+    /// Closes the window and lets go of the worker.
+    pub fn deselect(&mut self) {
+        self.selected_worker = None;
+        self.follow = false;
+        self.cancel_requested = false;
+        self.show = false;
+    }
+}
@@ -0,0 +1,74 @@
+//! Items lying on the local map, dropped by felled trees and mined rock or brought along as
+//! supplies, and the stockpile zones workers haul them to.
+
+pub mod registry;
+pub mod stockpile;
+
+pub use registry::ItemRegistry;
+pub use stockpile::{ItemFilter, Stockpile};
+
+use crate::jobs::WorkerId;
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+pub type ItemId = u32;
+
+/// Broad kinds of items, for stockpile filters and building materials
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum ItemCategory {
+    Stone,
+    Ore,
+    Soil,
+    Wood,
+    Food,
+    Other,
+}
+
+impl ItemCategory {
+    pub const ALL: [ItemCategory; 6] = [
+        ItemCategory::Stone,
+        ItemCategory::Ore,
+        ItemCategory::Soil,
+        ItemCategory::Wood,
+        ItemCategory::Food,
+        ItemCategory::Other,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemCategory::Stone => "stone",
+            ItemCategory::Ore => "ore",
+            ItemCategory::Soil => "soil",
+            ItemCategory::Wood => "wood",
+            ItemCategory::Food => "food",
+            ItemCategory::Other => "other",
+        }
+    }
+}
+
+pub struct Item {
+    pub id: ItemId,
+    /// What the item is, e.g. "wood"
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    pub z: i32,
+    /// The worker on its way to pick it up, so nobody else goes for it
+    pub reserved_by: Option<WorkerId>,
+}
+
+impl Item {
+    /// Items get their id from `World::drop_item`; this is for putting back known items.
+    pub fn new(id: ItemId, kind: &str, x: f32, y: f32, z: i32) -> Self {
+        Self { id, kind: kind.to_string(), x, y, z, reserved_by: None }
+    }
+
+    /// Draws the item in `color`, its kind's colour from the item registry.
+    pub fn draw(&self, camera_x: f32, camera_y: f32, zoom: f32, color: Color) {
+        let screen_x = (self.x - camera_x) * zoom;
+        let screen_y = (self.y - camera_y) * zoom;
+        draw_rectangle(screen_x - 0.5 * zoom, screen_y - 0.25 * zoom, zoom, 0.5 * zoom, color);
+        // A dark edge keeps items apart from the ground they lie on
+        draw_rectangle_lines(screen_x - 0.5 * zoom, screen_y - 0.25 * zoom, zoom, 0.5 * zoom, 1.0, Color::new(0.0, 0.0, 0.0, 0.6));
+    }
+}
@@ -0,0 +1,81 @@
+use super::ItemCategory;
+use macroquad::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Where the item kind definitions live
+pub const ITEMS_PATH: &str = "assets/items.json";
+
+/// What one kind of item counts as and how it looks, as defined in the items asset.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ItemKind {
+    /// Stockpile filter and building material group
+    pub category: ItemCategory,
+    /// RGB colour it is drawn in
+    pub color: [f32; 3],
+}
+
+/// Properties for kinds the asset doesn't define
+static MISSING: ItemKind = ItemKind { category: ItemCategory::Other, color: [0.78, 0.78, 0.78] };
+
+/// Item kinds by name, e.g. "granite", loaded from [`ITEMS_PATH`].
+#[derive(Debug, Clone)]
+pub struct ItemRegistry {
+    kinds: HashMap<String, ItemKind>,
+}
+
+impl ItemRegistry {
+    /// Reads [`ITEMS_PATH`]; the error names the file and what went wrong with it.
+    pub async fn load() -> Result<Self, String> {
+        let json_str = load_string(ITEMS_PATH)
+            .await
+            .map_err(|e| format!("could not read {}: {}", ITEMS_PATH, e))?;
+        Self::from_json(&json_str).map_err(|e| format!("invalid {}: {}", ITEMS_PATH, e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let kinds = serde_json::from_str(json)?;
+        Ok(Self { kinds })
+    }
+
+    pub fn get(&self, kind: &str) -> &ItemKind {
+        self.kinds.get(kind).unwrap_or(&MISSING)
+    }
+
+    pub fn category(&self, kind: &str) -> ItemCategory {
+        self.get(kind).category
+    }
+
+    pub fn color(&self, kind: &str) -> Color {
+        let [r, g, b] = self.get(kind).color;
+        Color::new(r, g, b, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::localmap::material_registry::MATERIALS_PATH;
+
+    fn registry() -> ItemRegistry {
+        ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn every_material_drop_is_a_known_kind() {
+        let registry = registry();
+        let materials: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap();
+        for (material, props) in materials.as_object().unwrap() {
+            if let Some(drops) = props["drops"].as_str() {
+                assert!(registry.kinds.contains_key(drops), "{} drops unknown item kind {}", material, drops);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_kinds_are_other() {
+        let registry = registry();
+        assert_eq!(registry.category("granite"), ItemCategory::Stone);
+        assert_eq!(registry.category("no_such_thing"), ItemCategory::Other);
+    }
+}
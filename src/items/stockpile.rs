@@ -0,0 +1,91 @@
+use super::{Item, ItemCategory};
+use crate::world::localmap::position::WorldPos;
+
+/// Which item categories a stockpile takes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemFilter {
+    /// One bit per `ItemCategory`, in declaration order
+    mask: u8,
+}
+
+impl ItemFilter {
+    pub const ANY: ItemFilter = ItemFilter { mask: (1 << ItemCategory::ALL.len()) - 1 };
+
+    pub fn only(category: ItemCategory) -> Self {
+        Self { mask: 1 << category as u8 }
+    }
+
+    pub fn allows(self, category: ItemCategory) -> bool {
+        self.mask & (1 << category as u8) != 0
+    }
+
+    /// E.g. "stone, ore", or "anything"
+    pub fn name(self) -> String {
+        if self == Self::ANY {
+            return "anything".to_string();
+        }
+        let names: Vec<&str> = ItemCategory::ALL.iter().filter(|&&c| self.allows(c)).map(|c| c.name()).collect();
+        names.join(", ")
+    }
+
+    /// The next of the filters the player cycles through: anything, then each category alone
+    pub fn next(self) -> Self {
+        let position = ItemCategory::ALL.iter().position(|&c| self == Self::only(c));
+        match position {
+            None => Self::only(ItemCategory::ALL[0]),
+            Some(i) if i + 1 < ItemCategory::ALL.len() => Self::only(ItemCategory::ALL[i + 1]),
+            Some(_) => Self::ANY,
+        }
+    }
+
+    pub fn bits(self) -> u8 {
+        self.mask
+    }
+
+    pub fn from_bits(mask: u8) -> Self {
+        Self { mask: mask & Self::ANY.mask }
+    }
+}
+
+/// A zone on one level where workers store items, one item per subpixel.
+pub struct Stockpile {
+    /// Corners of the zone, inclusive; both on the zone's level
+    pub min: WorldPos,
+    pub max: WorldPos,
+    pub filter: ItemFilter,
+    /// Priority of the haul jobs bringing items here
+    pub priority: u8,
+}
+
+impl Stockpile {
+    /// A zone spanning the two corners, in any order.
+    pub fn new(a: WorldPos, b: WorldPos, filter: ItemFilter, priority: u8) -> Self {
+        Self {
+            min: WorldPos::new(a.x.min(b.x), a.y.min(b.y), a.z),
+            max: WorldPos::new(a.x.max(b.x), a.y.max(b.y), a.z),
+            filter,
+            priority,
+        }
+    }
+
+    pub fn contains(&self, pos: WorldPos) -> bool {
+        pos.z == self.min.z && (self.min.x..=self.max.x).contains(&pos.x) && (self.min.y..=self.max.y).contains(&pos.y)
+    }
+
+    pub fn accepts(&self, category: ItemCategory) -> bool {
+        self.filter.allows(category)
+    }
+
+    /// Whether `item`, of `category`, lies in this stockpile and belongs there
+    pub fn holds(&self, item: &Item, category: ItemCategory) -> bool {
+        self.contains(WorldPos::from_f32(item.x, item.y, item.z)) && self.accepts(category)
+    }
+
+    pub fn capacity(&self) -> usize {
+        ((self.max.x - self.min.x + 1) * (self.max.y - self.min.y + 1)) as usize
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = WorldPos> + '_ {
+        (self.min.y..=self.max.y).flat_map(move |y| (self.min.x..=self.max.x).map(move |x| WorldPos::new(x, y, self.min.z)))
+    }
+}
@@ -0,0 +1,141 @@
+use super::job::{Job, JobId, JobKind, JobState, WorkerId};
+use crate::items::ItemId;
+use crate::world::localmap::position::WorldPos;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Ticks before a job nobody could reach is offered again
+const UNREACHABLE_RETRY: u64 = 600;
+
+/// Every job waiting to be done on the local map. Designations post jobs here and take
+/// them back; workers claim one at a time, so no two work on the same job.
+#[derive(Default)]
+pub struct JobBoard {
+    jobs: HashMap<JobId, Job>,
+    /// The job of each kind at a position, if any
+    by_pos: HashMap<(JobKind, WorldPos), JobId>,
+    /// The haul job of each item, if any; items can share a position, so they get their own index
+    by_item: HashMap<ItemId, JobId>,
+    next_id: JobId,
+}
+
+impl JobBoard {
+    /// Posts a job, unless one of the same kind is already waiting at `pos`. Returns the
+    /// job's id either way.
+    pub fn post(&mut self, kind: JobKind, pos: WorldPos, priority: u8) -> JobId {
+        if let Some(&id) = self.by_pos.get(&(kind, pos)) {
+            return id;
+        }
+        let id = self.next_id;
+        self.insert(Job::new(id, kind, pos, priority));
+        id
+    }
+
+    /// Posts a job to take an item lying at `pos` to a stockpile, unless it has one already.
+    pub fn post_haul(&mut self, item: ItemId, pos: WorldPos, priority: u8) -> JobId {
+        if let Some(&id) = self.by_item.get(&item) {
+            return id;
+        }
+        let id = self.next_id;
+        self.insert(Job { item: Some(item), ..Job::new(id, JobKind::Haul, pos, priority) });
+        id
+    }
+
+    /// Puts back a job as it was, e.g. from a save.
+    pub fn insert(&mut self, job: Job) {
+        self.next_id = self.next_id.max(job.id + 1);
+        match job.item {
+            Some(item) => self.by_item.insert(item, job.id),
+            None => self.by_pos.insert((job.kind, job.pos), job.id),
+        };
+        self.jobs.insert(job.id, job);
+    }
+
+    /// Takes a job off the board, done or not.
+    pub fn remove(&mut self, id: JobId) -> Option<Job> {
+        let job = self.jobs.remove(&id)?;
+        match job.item {
+            Some(item) => self.by_item.remove(&item),
+            None => self.by_pos.remove(&(job.kind, job.pos)),
+        };
+        Some(job)
+    }
+
+    /// Takes an item's haul job off the board, e.g. when the item is used up.
+    pub fn withdraw_haul(&mut self, item: ItemId) -> Option<Job> {
+        let id = *self.by_item.get(&item)?;
+        self.remove(id)
+    }
+
+    /// Takes the job of `kind` at `pos` off the board, e.g. when its designation is lifted.
+    pub fn withdraw(&mut self, kind: JobKind, pos: WorldPos) -> Option<Job> {
+        let id = *self.by_pos.get(&(kind, pos))?;
+        self.remove(id)
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: JobId) -> Option<&mut Job> {
+        self.jobs.get_mut(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.values()
+    }
+
+    pub fn count(&self, kind: JobKind) -> usize {
+        self.jobs.values().filter(|job| job.kind == kind).count()
+    }
+
+    /// Whether the job can be taken at `tick`: open, or given up on long enough ago.
+    pub fn is_available(job: &Job, tick: u64) -> bool {
+        match job.state {
+            JobState::Open => true,
+            JobState::Claimed(_) => false,
+            JobState::Unreachable { since } => tick >= since + UNREACHABLE_RETRY,
+        }
+    }
+
+    /// Reserves the most urgent available job that `accept` allows for `worker`; among equally
+    /// urgent jobs, those fewest levels away from `from` go first, then the nearest.
+    pub fn claim(&mut self, worker: WorkerId, from: WorldPos, tick: u64, accept: impl Fn(&Job) -> bool) -> Option<JobId> {
+        let id = self
+            .jobs
+            .values()
+            .filter(|job| Self::is_available(job, tick) && accept(job))
+            .min_by_key(|job| (Reverse(job.priority), (job.pos.z - from.z).abs(), job.pos.distance_sq(from), job.id))?
+            .id;
+        self.jobs.get_mut(&id)?.state = JobState::Claimed(worker);
+        Some(id)
+    }
+
+    /// Hands a claimed job back so someone else can take it; its progress is kept.
+    pub fn release(&mut self, id: JobId) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.state = JobState::Open;
+        }
+    }
+
+    /// Hands a job back as out of reach, so nobody tries it again for a while.
+    pub fn give_up(&mut self, id: JobId, tick: u64) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.state = JobState::Unreachable { since: tick };
+        }
+    }
+
+    /// Whether the job was given up as out of reach and isn't offered yet.
+    pub fn is_unreachable(job: &Job, tick: u64) -> bool {
+        matches!(job.state, JobState::Unreachable { since } if tick < since + UNREACHABLE_RETRY)
+    }
+
+    /// Id the next posted job gets; saved so ids stay unique after loading.
+    pub fn next_id(&self) -> JobId {
+        self.next_id
+    }
+
+    pub fn set_next_id(&mut self, id: JobId) {
+        self.next_id = self.next_id.max(id);
+    }
+}
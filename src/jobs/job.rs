@@ -0,0 +1,71 @@
+use crate::items::ItemId;
+use crate::world::localmap::position::WorldPos;
+
+pub type JobId = u32;
+/// The creature working on a job
+pub type WorkerId = u32;
+
+/// Least urgent priority a job can have
+pub const MIN_PRIORITY: u8 = 1;
+/// Most urgent priority a job can have
+pub const MAX_PRIORITY: u8 = 5;
+pub const DEFAULT_PRIORITY: u8 = 3;
+
+/// What a job asks a worker to do at its position
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JobKind {
+    Dig,
+    Build,
+    Haul,
+    Chop,
+}
+
+impl JobKind {
+    pub const ALL: [JobKind; 4] = [JobKind::Dig, JobKind::Build, JobKind::Haul, JobKind::Chop];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            JobKind::Dig => "dig",
+            JobKind::Build => "build",
+            JobKind::Haul => "haul",
+            JobKind::Chop => "chop",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobState {
+    /// Waiting for someone to take it
+    Open,
+    /// Reserved by a worker; nobody else takes it until it is released
+    Claimed(WorkerId),
+    /// Nobody could find a way there at this tick; offered again after a while
+    Unreachable { since: u64 },
+}
+
+pub struct Job {
+    pub id: JobId,
+    pub kind: JobKind,
+    pub pos: WorldPos,
+    /// The item a haul job is about
+    pub item: Option<ItemId>,
+    /// From `MIN_PRIORITY` to `MAX_PRIORITY`; more urgent jobs are taken first
+    pub priority: u8,
+    pub state: JobState,
+    /// Frames of work done so far; kept when a worker gives the job up
+    pub progress: u32,
+}
+
+impl Job {
+    pub fn new(id: JobId, kind: JobKind, pos: WorldPos, priority: u8) -> Self {
+        Self {
+            id,
+            kind,
+            pos,
+            item: None,
+            priority: priority.clamp(MIN_PRIORITY, MAX_PRIORITY),
+            state: JobState::Open,
+            progress: 0,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Job system: work posted from designations and claimed by workers.
+
+pub mod job;
+pub mod board;
+
+pub use board::JobBoard;
+pub use job::{Job, JobId, JobKind, JobState, WorkerId};
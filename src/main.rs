@@ -1,4 +1,8 @@
 mod creatures;
+mod plants;
+mod items;
+mod jobs;
+mod construction;
 mod player;
 mod renderer;
 mod world;
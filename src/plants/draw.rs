@@ -0,0 +1,45 @@
+use macroquad::prelude::*;
+use super::{Plant, Species};
+
+const TRUNK: Color = Color::new(0.4, 0.26, 0.13, 1.0);
+const BERRY: Color = Color::new(0.62, 0.1, 0.3, 1.0);
+
+impl Plant {
+    pub fn draw(&self, camera_x: f32, camera_y: f32, zoom: f32) {
+        let screen_x = (self.x as f32 + 0.5 - camera_x) * zoom;
+        let screen_y = (self.y as f32 + 0.5 - camera_y) * zoom;
+        if self.is_felled() {
+            // Stump
+            draw_circle(screen_x, screen_y, 0.6 * zoom, TRUNK);
+            return;
+        }
+        let radius = self.radius() * zoom;
+        let color = self.species.color();
+        match self.species {
+            Species::Grass => {
+                for i in -1..=1 {
+                    let dx = i as f32 * radius * 0.5;
+                    draw_line(screen_x + dx, screen_y, screen_x + dx * 1.5, screen_y - radius, 0.25 * zoom, color);
+                }
+            }
+            Species::Cactus => {
+                draw_rectangle(screen_x - radius * 0.3, screen_y - radius, radius * 0.6, radius * 2.0, color);
+                draw_rectangle(screen_x - radius, screen_y - radius * 0.4, radius * 2.0, radius * 0.3, color);
+            }
+            _ => {
+                if self.species.is_tree() {
+                    draw_circle(screen_x, screen_y, radius * 0.3, TRUNK);
+                }
+                draw_circle(screen_x, screen_y - radius * 0.3, radius, color);
+            }
+        }
+        if self.has_fruit() {
+            for (dx, dy) in [(-0.4, -0.5), (0.3, -0.7), (0.1, 0.0)] {
+                draw_circle(screen_x + dx * radius, screen_y + dy * radius, 0.3 * zoom, BERRY);
+            }
+        }
+        if self.chop {
+            draw_circle_lines(screen_x, screen_y - radius * 0.3, radius + 0.3 * zoom, 0.3 * zoom, RED);
+        }
+    }
+}
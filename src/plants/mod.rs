@@ -0,0 +1,6 @@
+//! Plant system: species, growth, and rendering of local map vegetation.
+
+pub mod plant;
+pub mod draw;
+
+pub use plant::{Plant, Species};
@@ -0,0 +1,164 @@
+use macroquad::prelude::*;
+
+/// Frames a felled tree leaves a bare stump before a sapling sprouts again
+pub const REGROW_DELAY: u32 = 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Species {
+    Oak,
+    Pine,
+    JungleTree,
+    Shrub,
+    Grass,
+    Cactus,
+}
+
+impl Species {
+    /// Every species, in declaration order, so `ALL[s as usize] == s`
+    pub const ALL: [Species; 6] = [
+        Species::Oak,
+        Species::Pine,
+        Species::JungleTree,
+        Species::Shrub,
+        Species::Grass,
+        Species::Cactus,
+    ];
+
+    /// Trees can be designated for chopping and yield wood
+    pub fn is_tree(&self) -> bool {
+        matches!(self, Species::Oak | Species::Pine | Species::JungleTree)
+    }
+
+    /// Radius in subpixels once fully grown
+    pub fn max_radius(&self) -> f32 {
+        match self {
+            Species::Oak => 4.0,
+            Species::Pine => 3.0,
+            Species::JungleTree => 5.0,
+            Species::Shrub => 2.0,
+            Species::Grass => 1.0,
+            Species::Cactus => 1.5,
+        }
+    }
+
+    /// Growth per frame; a full grown plant is at 1.0
+    pub fn growth_rate(&self) -> f32 {
+        match self {
+            Species::Oak => 1.0 / (60.0 * 240.0),
+            Species::Pine => 1.0 / (60.0 * 180.0),
+            Species::JungleTree => 1.0 / (60.0 * 150.0),
+            Species::Shrub => 1.0 / (60.0 * 60.0),
+            Species::Grass => 1.0 / (60.0 * 20.0),
+            Species::Cactus => 1.0 / (60.0 * 300.0),
+        }
+    }
+
+    /// Wood items dropped by a fully grown tree
+    pub fn wood_yield(&self) -> u32 {
+        match self {
+            Species::Oak => 3,
+            Species::Pine => 2,
+            Species::JungleTree => 4,
+            _ => 0,
+        }
+    }
+
+    /// Frames a picked plant takes to bear fruit again, for those that bear any
+    pub fn fruit_time(&self) -> Option<u32> {
+        match self {
+            Species::Shrub => Some(60 * 60 * 5),
+            _ => None,
+        }
+    }
+
+    /// Frames of work to fell a fully grown tree
+    pub fn chop_time(&self) -> u32 {
+        match self {
+            Species::Oak => 120,
+            Species::Pine => 90,
+            Species::JungleTree => 150,
+            _ => 0,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Species::Oak => Color::from_rgba(46, 110, 40, 255),
+            Species::Pine => Color::from_rgba(24, 78, 48, 255),
+            Species::JungleTree => Color::from_rgba(60, 140, 36, 255),
+            Species::Shrub => Color::from_rgba(90, 120, 50, 255),
+            Species::Grass => Color::from_rgba(110, 160, 70, 255),
+            Species::Cactus => Color::from_rgba(70, 130, 80, 255),
+        }
+    }
+}
+
+pub struct Plant {
+    /// Subpixel position of the stem
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub species: Species,
+    /// 0 for a seedling, 1 when fully grown
+    pub growth: f32,
+    /// Designated for felling
+    pub chop: bool,
+    /// Frames left before a felled plant sprouts again
+    pub regrow_timer: u32,
+    /// Frames left before a picked plant bears fruit again
+    pub fruit_timer: u32,
+}
+
+impl Plant {
+    pub fn new(x: i32, y: i32, z: i32, species: Species, growth: f32) -> Self {
+        Self { x, y, z, species, growth, chop: false, regrow_timer: 0, fruit_timer: 0 }
+    }
+
+    /// Felled plants are stumps waiting to regrow
+    pub fn is_felled(&self) -> bool {
+        self.regrow_timer > 0
+    }
+
+    /// Current radius in subpixels
+    pub fn radius(&self) -> f32 {
+        self.species.max_radius() * (0.3 + 0.7 * self.growth)
+    }
+
+    /// How close a worker must stand to chop it: at the trunk's edge rather than inside it
+    pub fn chop_reach(&self) -> f32 {
+        self.radius() * 0.3 + 2.0
+    }
+
+    /// Trees need to be at least half grown before they are worth chopping
+    pub fn can_chop(&self) -> bool {
+        self.species.is_tree() && !self.is_felled() && self.growth >= 0.5
+    }
+
+    /// Berry bushes bear fruit once half grown, until it is picked
+    pub fn has_fruit(&self) -> bool {
+        self.species.fruit_time().is_some() && !self.is_felled() && self.growth >= 0.5 && self.fruit_timer == 0
+    }
+
+    /// Picks the fruit off the plant; it bears again after its species' fruit time.
+    pub fn pick(&mut self) {
+        self.fruit_timer = self.species.fruit_time().unwrap_or(0);
+    }
+
+    pub fn grow(&mut self) {
+        self.fruit_timer = self.fruit_timer.saturating_sub(1);
+        if self.regrow_timer > 0 {
+            self.regrow_timer -= 1;
+            return;
+        }
+        self.growth = (self.growth + self.species.growth_rate()).min(1.0);
+    }
+
+    /// Cuts the plant down, returning the wood it yields. The stump regrows later.
+    pub fn fell(&mut self) -> u32 {
+        let wood = (self.species.wood_yield() as f32 * self.growth).round().max(1.0) as u32;
+        self.growth = 0.0;
+        self.chop = false;
+        self.regrow_timer = REGROW_DELAY;
+        wood
+    }
+}
@@ -1,71 +1,88 @@
+use crate::construction::Construction;
+use crate::items::{ItemFilter, Stockpile};
+use crate::world::localmap::change::ChangeCause;
+use crate::world::localmap::position::WorldPos;
 use crate::world::localmap::world::World;
 use crate::world::localmap::terrain_material::TerrainMaterial;
 
-/// Paints a dig target area in the world at (x, y).
-pub fn paint_dig_target(world: &mut World, x: i32, y: i32) {
+/// Most subpixels one click on a vein marks, so clicking a stratum doesn't designate the map
+const VEIN_LIMIT: usize = 512;
+
+/// Paints a round dig target area in the world at (x, y) on level z, posting dig jobs of
+/// the given priority. Returns the subpixels that weren't designated already.
+pub fn paint_dig_target(world: &mut World, x: i32, y: i32, z: i32, priority: u8) -> Vec<WorldPos> {
     let radius = 2; // Adjust if you want bigger painting area
 
-    for dx in -radius..=radius {
-        for dy in -radius..=radius {
-            let px = x + dx;
-            let py = y + dy;
+    let center = WorldPos::new(x, y, z);
+    let area: Vec<WorldPos> = world
+        .subpixels_in_circle(center, radius)
+        .filter(|(_, subpixel)| !subpixel.dig_target)
+        .map(|(pos, _)| pos)
+        .collect();
+    for &pos in &area {
+        world.designate_dig(pos, priority);
+    }
+    area
+}
 
-            let (chunk_x, chunk_y) = (px.div_euclid(32 * 8), py.div_euclid(32 * 8));
-            let local_x = px.rem_euclid(32 * 8);
-            let local_y = py.rem_euclid(32 * 8);
-            let tile_x = local_x.div_euclid(8);
-            let tile_y = local_y.div_euclid(8);
-            let sub_x = local_x.rem_euclid(8);
-            let sub_y = local_y.rem_euclid(8);
+/// Marks the solid patch of one material connected to (x, y) on level z for digging, such
+/// as an ore vein, posting dig jobs of the given priority. Returns the newly marked subpixels.
+pub fn designate_vein(world: &mut World, x: i32, y: i32, z: i32, priority: u8) -> Vec<WorldPos> {
+    let start = WorldPos::new(x, y, z);
+    if !world.subpixel_at(start).is_some_and(|subpixel| world.materials.is_solid(subpixel.material)) {
+        return Vec::new();
+    }
+    let vein = world.flood_fill(start, VEIN_LIMIT);
+    vein.into_iter().filter(|&pos| world.designate_dig(pos, priority)).collect()
+}
 
-            if let Some(zlevel) = world.z_levels.get_mut(0) {
-                if let Some(chunk) = zlevel.chunks.get_mut(&(chunk_x, chunk_y)) {
-                    if let Some(tile) = chunk
-                        .tiles
-                        .get_mut(tile_x as usize)
-                        .and_then(|r| r.get_mut(tile_y as usize))
-                    {
-                        tile.subgrid[sub_x as usize][sub_y as usize].dig_target = true;
-                        chunk.dirty = true;
-                    }
-                }
-            }
-        }
+/// Plans a construction on the subpixel at (x, y) on level z, posting a build job of the
+/// given priority. Returns the subpixel if it was planned.
+pub fn plan_construction(world: &mut World, x: i32, y: i32, z: i32, construction: Construction, priority: u8) -> Vec<(WorldPos, Construction)> {
+    let pos = WorldPos::new(x, y, z);
+    if world.designate_build(pos, construction, priority) {
+        vec![(pos, construction)]
+    } else {
+        Vec::new()
     }
 }
 
-/// Counts the number of dig jobs in the world.
-pub fn count_dig_jobs(world: &World) -> usize {
-    let mut count = 0;
+/// Marks out a stockpile between two corners on the first one's level.
+pub fn add_stockpile(world: &mut World, from: WorldPos, to: WorldPos, filter: ItemFilter, priority: u8) {
+    world.stockpiles.push(Stockpile::new(from, WorldPos::new(to.x, to.y, from.z), filter, priority));
+}
 
-    for z in &world.z_levels {
-        for chunk in z.chunks.values() {
-            for row in &chunk.tiles {
-                for tile in row {
-                    for subrow in &tile.subgrid {
-                        for sub in subrow {
-                            if sub.dig_target {
-                                count += 1;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+/// Removes the stockpile at `pos`; the items in it stay where they are.
+pub fn remove_stockpile(world: &mut World, pos: WorldPos) -> Option<Stockpile> {
+    let index = world.stockpile_at(pos)?;
+    Some(world.stockpiles.remove(index))
+}
+
+/// Marks grown trees near (x, y) on level z for felling, posting chop jobs of the given
+/// priority. Returns where the newly marked trees stand.
+pub fn designate_chop(world: &mut World, x: i32, y: i32, z: i32, priority: u8) -> Vec<WorldPos> {
+    let radius = 4; // Trees are a few subpixels across, so be generous
 
-    count
+    let center = WorldPos::new(x, y, z);
+    let nearby: Vec<WorldPos> = world
+        .plants
+        .iter()
+        .map(|plant| WorldPos::new(plant.x, plant.y, plant.z))
+        .filter(|pos| pos.z == z && pos.distance_sq(center) <= radius * radius)
+        .collect();
+    nearby.into_iter().filter(|&pos| world.designate_chop(pos, priority)).collect()
 }
 
-/// Paints rock material in a small area around (x, y).
-pub fn paint_rock(world: &mut World, x: i32, y: i32) {
+/// Paints rock material in a small area around (x, y) on level z. What was painted over
+/// is announced as map changes, which is where undo history picks it up.
+pub fn paint_rock(world: &mut World, x: i32, y: i32, z: i32) {
     let radius = 2; // Paint a small 5x5 blob around the cursor
 
     for dx in -radius..=radius {
         for dy in -radius..=radius {
             let px = x + dx;
             let py = y + dy;
-            world.set_material_at(px, py, TerrainMaterial::Rock);
+            world.set_material_at(px, py, z, TerrainMaterial::Rock, ChangeCause::Paint);
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,39 @@
+use macroquad::prelude::*;
+use crate::world::worldmap::city::{City, SettlementTier};
+
+/// Draw a city icon centred on (cx, cy); shape depends on tier, rings mark walls and ports.
+pub fn draw_city_icon(city: &City, cx: f32, cy: f32, draw_size: f32) {
+    let civ_color = city.civ.color();
+    match city.tier {
+        SettlementTier::Hamlet => {
+            draw_circle(cx, cy, draw_size * 0.15, civ_color);
+        }
+        SettlementTier::Village => {
+            draw_circle(cx, cy, draw_size * 0.25, civ_color);
+            draw_circle_lines(cx, cy, draw_size * 0.25, 1.0, BLACK);
+        }
+        SettlementTier::Town => {
+            let s = draw_size * 0.5;
+            draw_rectangle(cx - s / 2.0, cy - s / 2.0, s, s, civ_color);
+            draw_rectangle_lines(cx - s / 2.0, cy - s / 2.0, s, s, 1.0, BLACK);
+        }
+        SettlementTier::City => {
+            let s = draw_size * 0.7;
+            draw_rectangle(cx - s / 2.0, cy - s / 2.0, s, s, civ_color);
+            draw_rectangle_lines(cx - s / 2.0, cy - s / 2.0, s, s, 2.0, BLACK);
+        }
+        SettlementTier::Capital => {
+            // Five-pointed star
+            draw_poly(cx, cy, 5, draw_size * 0.45, -90.0, civ_color);
+            draw_poly_lines(cx, cy, 5, draw_size * 0.45, -90.0, 2.0, GOLD);
+        }
+    }
+    if city.is_fortress {
+        draw_poly_lines(cx, cy, 6, draw_size * 0.6, 0.0, 2.0, DARKGRAY);
+    } else if city.has_walls {
+        draw_circle_lines(cx, cy, draw_size * 0.55, 1.0, DARKGRAY);
+    }
+    if city.is_port {
+        draw_circle(cx + draw_size * 0.35, cy + draw_size * 0.35, draw_size * 0.12, BLUE);
+    }
+}
@@ -0,0 +1,26 @@
+use macroquad::prelude::*;
+use crate::world::worldmap::site::{Site, SiteType};
+
+/// Draw a small site marker centred on (cx, cy).
+pub fn draw_site_icon(site: &Site, cx: f32, cy: f32, draw_size: f32) {
+    let color = site.kind.color();
+    let r = draw_size * 0.25;
+    match site.kind {
+        SiteType::Ruins | SiteType::Dungeon => {
+            draw_rectangle(cx - r, cy - r, r * 2.0, r * 2.0, color);
+            draw_rectangle_lines(cx - r, cy - r, r * 2.0, r * 2.0, 1.0, WHITE);
+        }
+        SiteType::Lair => {
+            draw_triangle(vec2(cx, cy - r), vec2(cx - r, cy + r), vec2(cx + r, cy + r), color);
+        }
+        SiteType::Shrine => {
+            draw_poly(cx, cy, 4, r, 45.0, color);
+        }
+        SiteType::Mine => {
+            draw_triangle(vec2(cx - r, cy - r), vec2(cx + r, cy - r), vec2(cx, cy + r), color);
+        }
+        SiteType::Watchtower => {
+            draw_rectangle(cx - r * 0.4, cy - r, r * 0.8, r * 2.0, color);
+        }
+    }
+}
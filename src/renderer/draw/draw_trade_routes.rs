@@ -34,4 +34,17 @@ pub fn draw_trade_routes(world_map: &WorldMap, camera: &Camera, cell_size: f32,
             }
         }
     }
-} 
\ No newline at end of file
+} 
+pub fn draw_transport_nodes(world_map: &WorldMap, camera: &Camera, cell_size: f32) {
+    for &(x, y) in &world_map.transport.bridges {
+        let sx = (x as f32 - camera.x) * cell_size + cell_size / 2.0;
+        let sy = (y as f32 - camera.y) * cell_size + cell_size / 2.0;
+        draw_rectangle(sx - cell_size * 0.2, sy - cell_size * 0.2, cell_size * 0.4, cell_size * 0.4, DARKBROWN);
+    }
+    for port in world_map.transport.ports() {
+        let (x, y) = port.pos;
+        let sx = (x as f32 - camera.x) * cell_size + cell_size / 2.0;
+        let sy = (y as f32 - camera.y) * cell_size + cell_size / 2.0;
+        draw_circle_lines(sx, sy, cell_size * 0.3, 2.0, WHITE);
+    }
+}
@@ -8,6 +8,8 @@ pub mod draw_elevation;
 pub mod draw_civilization;
 pub mod draw_biome_with_civ_overlay;
 pub mod draw_trade_routes;
+pub mod draw_city;
+pub mod draw_site;
 
 pub use draw_biome::*;
 pub use draw_mountain::*;
@@ -18,4 +20,6 @@ pub use draw_precipitation::*;
 pub use draw_elevation::*;
 pub use draw_civilization::*;
 pub use draw_biome_with_civ_overlay::*;
-pub use draw_trade_routes::*; 
\ No newline at end of file
+pub use draw_trade_routes::*;
+pub use draw_city::*;
+pub use draw_site::*; 
\ No newline at end of file
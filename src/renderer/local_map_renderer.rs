@@ -3,20 +3,49 @@ use crate::game::state::GameState;
 use crate::renderer::camera::Camera;
 use crate::renderer::grid::draw_grid;
 use crate::renderer::tile_render::TileRenderer;
+use crate::world::localmap::change::WorldChange;
+use crate::world::localmap::position::{ChunkPos, CHUNK_SPAN};
+use crate::world::localmap::world::SURFACE_Z;
+use std::collections::{HashMap, HashSet};
 
 pub struct LocalMapRenderer {
     pub camera: Camera,
+    /// Level currently shown
+    pub current_z: i32,
     tile_renderer: TileRenderer,
+    /// Drawn chunks of the shown level, redrawn only when something in them changed
+    chunk_textures: HashMap<ChunkPos, Texture2D>,
+    /// Chunks whose texture no longer matches the map
+    stale: HashSet<ChunkPos>,
 }
 
 impl LocalMapRenderer {
     pub fn default() -> Self {
         Self {
             camera: Camera::default(),
+            current_z: SURFACE_Z,
             tile_renderer: TileRenderer::default(),
+            chunk_textures: HashMap::new(),
+            stale: HashSet::new(),
         }
     }
 
+    /// Marks the chunks a frame's map changes touched for redrawing, along with the
+    /// chunks above them, which show them through their open air.
+    pub fn record_changes(&mut self, changes: &[WorldChange]) {
+        for change in changes {
+            let chunk = change.pos.chunk();
+            self.stale.insert(chunk);
+            self.stale.insert(ChunkPos { z: chunk.z + 1, ..chunk });
+        }
+    }
+
+    /// Forget every drawn chunk, e.g. after a different map was generated or loaded.
+    pub fn invalidate(&mut self) {
+        self.chunk_textures.clear();
+        self.stale.clear();
+    }
+
     pub fn move_camera_delta(&mut self, dx: f32, dy: f32) {
         self.camera.move_delta(dx, dy);
     }
@@ -42,147 +71,77 @@ impl LocalMapRenderer {
     }
 
     /// The only public rendering method: draws the world using an immutable GameState DTO.
-    pub fn draw(&self, state: &GameState) {
+    /// Chunks are drawn from their cached textures, which are redrawn when stale.
+    pub fn draw(&mut self, state: &GameState) {
         clear_background(BLACK);
 
-        if let Some(zlevel) = state.z_levels.get(0) {
-            let screen_width_px = screen_width();
-            let screen_height_px = screen_height();
-            let zoom = self.camera.zoom;
-            let camera_x = self.camera.x;
-            let camera_y = self.camera.y;
-            let screen_world_w = screen_width_px / zoom;
-            let screen_world_h = screen_height_px / zoom;
-            let world_left = camera_x.floor() as i32;
-            let world_top = camera_y.floor() as i32;
-            let world_right = (camera_x + screen_world_w).ceil() as i32;
-            let world_bottom = (camera_y + screen_world_h).ceil() as i32;
-            let chunk_size = 32;
-            let tile_size = 8;
-            let chunk_pixel_size = chunk_size * tile_size;
-
-            for ((chunk_x, chunk_y), chunk) in zlevel.chunks.iter() {
-                self.draw_chunk(
-                    chunk_x,
-                    chunk_y,
-                    chunk,
-                    chunk_pixel_size,
-                    tile_size,
-                    world_left,
-                    world_right,
-                    world_top,
-                    world_bottom,
-                    camera_x,
-                    camera_y,
-                    zoom,
-                );
+        let z = self.current_z;
+        let level = |z: i32| state.z_levels.iter().find(|level| level.z == z);
+        let Some(zlevel) = level(z) else { return; };
+        let below = level(z - 1);
+        // Textures of other levels and of unloaded chunks won't be drawn again soon
+        self.chunk_textures.retain(|pos, _| pos.z == z && zlevel.chunks.contains_key(&pos.key()));
+        self.stale.retain(|pos| pos.z == z);
+
+        let screen_width_px = screen_width();
+        let screen_height_px = screen_height();
+        let zoom = self.camera.zoom;
+        let camera_x = self.camera.x;
+        let camera_y = self.camera.y;
+        let world_left = camera_x.floor() as i32;
+        let world_top = camera_y.floor() as i32;
+        let world_right = (camera_x + screen_width_px / zoom).ceil() as i32;
+        let world_bottom = (camera_y + screen_height_px / zoom).ceil() as i32;
+        let tile_size = 8;
+
+        for (&(cx, cy), chunk) in zlevel.chunks.iter() {
+            let pos = ChunkPos { x: cx, y: cy, z };
+            let origin = pos.origin();
+            // Chunk culling (skip if completely outside view)
+            if origin.x + CHUNK_SPAN < world_left
+                || origin.x > world_right
+                || origin.y + CHUNK_SPAN < world_top
+                || origin.y > world_bottom
+            {
+                continue;
             }
 
-            draw_grid(
-                camera_x,
-                camera_y,
-                zoom,
-                screen_width_px,
-                screen_height_px,
-                tile_size,
+            // Water still moving leaves its chunk dirty after the fluid step; its levels
+            // change without a material change, so they aren't among the map changes
+            let below_chunk = below.and_then(|b| b.chunks.get(&(cx, cy)));
+            let flowing = chunk.dirty || below_chunk.is_some_and(|b| b.dirty);
+            let stale = self.stale.remove(&pos);
+            if stale || flowing || !self.chunk_textures.contains_key(&pos) {
+                let texture = self.tile_renderer.draw_chunk(state.materials, chunk, below_chunk, origin);
+                self.chunk_textures.insert(pos, texture);
+            }
+            let screen_x = (origin.x as f32 - camera_x) * zoom;
+            let screen_y = (origin.y as f32 - camera_y) * zoom;
+            let size = CHUNK_SPAN as f32 * zoom;
+            draw_texture_ex(
+                &self.chunk_textures[&pos],
+                screen_x,
+                screen_y,
+                WHITE,
+                DrawTextureParams { dest_size: Some(vec2(size, size)), ..Default::default() },
             );
-        }
-    }
-
-    fn draw_chunk(
-        &self,
-        chunk_x: &i32,
-        chunk_y: &i32,
-        chunk: &crate::world::localmap::chunk::Chunk,
-        chunk_pixel_size: i32,
-        tile_size: i32,
-        world_left: i32,
-        world_right: i32,
-        world_top: i32,
-        world_bottom: i32,
-        camera_x: f32,
-        camera_y: f32,
-        zoom: f32,
-    ) {
-        let chunk_pixel_x = chunk_x * chunk_pixel_size;
-        let chunk_pixel_y = chunk_y * chunk_pixel_size;
-
-        // Chunk culling (skip if completely outside view)
-        if chunk_pixel_x + chunk_pixel_size < world_left
-            || chunk_pixel_x > world_right
-            || chunk_pixel_y + chunk_pixel_size < world_top
-            || chunk_pixel_y > world_bottom
-        {
-            return;
-        }
 
-        // Precompute tile bounds for this chunk
-        let tile_x_start = ((world_left - chunk_pixel_x).max(0) / tile_size) as usize;
-        let tile_x_end = ((world_right - chunk_pixel_x).min(chunk_pixel_size - 1) / tile_size + 1).min(chunk.tiles.len() as i32) as usize;
-        let tile_y_start = ((world_top - chunk_pixel_y).max(0) / tile_size) as usize;
-        let tile_y_end = ((world_bottom - chunk_pixel_y).min(chunk_pixel_size - 1) / tile_size + 1).min(chunk.tiles[0].len() as i32) as usize;
-
-        for tile_x in tile_x_start..tile_x_end {
-            for tile_y in tile_y_start..tile_y_end {
-                self.draw_tile_in_chunk(
-                    &chunk.tiles[tile_x][tile_y],
-                    chunk_pixel_x,
-                    chunk_pixel_y,
-                    tile_x,
-                    tile_y,
-                    tile_size,
-                    world_left,
-                    world_right,
-                    world_top,
-                    world_bottom,
-                    camera_x,
-                    camera_y,
-                    zoom,
-                );
+            // Designated digging, over whatever the tiles drew
+            let overlay = Color::new(1.0, 0.0, 0.0, 0.4);
+            for &(x, y) in &chunk.dig_targets {
+                let sx = (origin.x as f32 + x as f32 - camera_x) * zoom;
+                let sy = (origin.y as f32 + y as f32 - camera_y) * zoom;
+                draw_rectangle(sx, sy, zoom, zoom, overlay);
             }
         }
-    }
 
-    fn draw_tile_in_chunk(
-        &self,
-        tile: &crate::world::localmap::tile::Tile,
-        chunk_pixel_x: i32,
-        chunk_pixel_y: i32,
-        tile_x: usize,
-        tile_y: usize,
-        tile_size: i32,
-        world_left: i32,
-        world_right: i32,
-        world_top: i32,
-        world_bottom: i32,
-        camera_x: f32,
-        camera_y: f32,
-        zoom: f32,
-    ) {
-        let world_x = chunk_pixel_x + tile_x as i32 * tile_size;
-        let world_y = chunk_pixel_y + tile_y as i32 * tile_size;
-
-        // Tile culling (should be redundant, but double-check)
-        if world_x + tile_size < world_left
-            || world_x > world_right
-            || world_y + tile_size < world_top
-            || world_y > world_bottom
-        {
-            return;
-        }
-
-        self.tile_renderer.draw_tile(
-            tile,
-            world_x,
-            world_y,
+        draw_grid(
             camera_x,
             camera_y,
             zoom,
+            screen_width_px,
+            screen_height_px,
             tile_size,
-            world_left,
-            world_right,
-            world_top,
-            world_bottom,
         );
     }
-} 
\ No newline at end of file
+}
@@ -3,4 +3,5 @@ pub mod grid;
 pub mod tile_render;
 pub mod local_map_renderer;
 pub mod world_map_renderer;
+pub mod region_map_renderer;
 pub mod draw;
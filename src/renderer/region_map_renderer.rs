@@ -0,0 +1,187 @@
+use macroquad::prelude::*;
+use crate::renderer::camera::Camera;
+use crate::renderer::draw::{draw_city_icon, draw_site_icon};
+use crate::world::regionmap::region_map::{RegionMap, CELLS_PER_TILE};
+use crate::world::worldmap::world_map::WorldMap;
+
+/// Screen pixels per region cell at zoom 1.0
+pub const CELL_PX: f32 = 1.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RegionView {
+    Terrain,
+    Elevation,
+    Political,
+}
+
+pub struct RegionMapRenderer {
+    pub camera: Camera,
+    pub view: RegionView,
+    texture: Option<Texture2D>,
+    texture_view: Option<RegionView>,
+}
+
+impl RegionMapRenderer {
+    pub fn new() -> Self {
+        Self {
+            camera: Camera::new(0.0, 0.0, 4.0),
+            view: RegionView::Terrain,
+            texture: None,
+            texture_view: None,
+        }
+    }
+
+    /// Forget the cached texture, e.g. after a new region was generated.
+    pub fn invalidate(&mut self) {
+        self.texture = None;
+        self.texture_view = None;
+    }
+
+    pub fn set_view(&mut self, view: RegionView) {
+        self.view = view;
+    }
+
+    /// Centre the camera on the region's centre world tile.
+    pub fn center_on(&mut self, region: &RegionMap) {
+        if let Some((cx, cy)) = region.world_tile_to_cell(region.center.0, region.center.1) {
+            let scale = CELL_PX * self.camera.zoom;
+            self.camera.x = cx - screen_width() / (2.0 * scale);
+            self.camera.y = cy - screen_height() / (2.0 * scale);
+        }
+    }
+
+    fn cell_color(&self, region: &RegionMap, x: usize, y: usize) -> Color {
+        let elev = region.elevation[x][y];
+        match self.view {
+            RegionView::Terrain => {
+                if region.rivers[x][y] && elev > region.sea_level {
+                    return DARKBLUE;
+                }
+                if region.roads[x][y] {
+                    return BROWN;
+                }
+                if let (true, Some(civ)) = (region.civ_border[x][y], region.civ[x][y]) {
+                    return civ.color();
+                }
+                // Shade the biome colour by local elevation for a bit of relief
+                let base = region.biomes[x][y].color();
+                let shade = (0.75 + (elev - region.sea_level) as f32 * 0.8).clamp(0.6, 1.15);
+                Color::new(
+                    (base.r * shade).min(1.0),
+                    (base.g * shade).min(1.0),
+                    (base.b * shade).min(1.0),
+                    1.0,
+                )
+            }
+            RegionView::Elevation => {
+                if region.is_water(x, y) {
+                    return Color::new(0.0, 0.2, 0.6, 1.0);
+                }
+                let t = elev.clamp(0.0, 1.0) as f32;
+                Color::new(t, t, t, 1.0)
+            }
+            RegionView::Political => {
+                if region.is_water(x, y) {
+                    return DARKBLUE;
+                }
+                if region.civ_border[x][y] {
+                    return BLACK;
+                }
+                match region.civ[x][y] {
+                    Some(civ) => civ.color(),
+                    None => DARKGRAY,
+                }
+            }
+        }
+    }
+
+    fn rebuild_texture(&mut self, region: &RegionMap) {
+        let mut image = Image::gen_image_color(region.width as u16, region.height as u16, BLACK);
+        for x in 0..region.width {
+            for y in 0..region.height {
+                image.set_pixel(x as u32, y as u32, self.cell_color(region, x, y));
+            }
+        }
+        let texture = Texture2D::from_image(&image);
+        texture.set_filter(FilterMode::Nearest);
+        self.texture = Some(texture);
+        self.texture_view = Some(self.view);
+    }
+
+    pub fn draw(&mut self, region: &RegionMap, world_map: &WorldMap) {
+        clear_background(BLACK);
+        if self.texture.is_none() || self.texture_view != Some(self.view) {
+            self.rebuild_texture(region);
+        }
+        let scale = CELL_PX * self.camera.zoom;
+        if let Some(texture) = &self.texture {
+            draw_texture_ex(
+                texture,
+                -self.camera.x * scale,
+                -self.camera.y * scale,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(region.width as f32 * scale, region.height as f32 * scale)),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // World tile grid, so the upsampled tiles are still recognisable
+        let grid_color = Color::new(1.0, 1.0, 1.0, 0.15);
+        let tile_px = CELLS_PER_TILE as f32 * scale;
+        for i in 0..=region.width / CELLS_PER_TILE {
+            let sx = (i * CELLS_PER_TILE) as f32 * scale - self.camera.x * scale;
+            draw_line(sx, -self.camera.y * scale, sx, -self.camera.y * scale + region.height as f32 * scale, 1.0, grid_color);
+        }
+        for j in 0..=region.height / CELLS_PER_TILE {
+            let sy = (j * CELLS_PER_TILE) as f32 * scale - self.camera.y * scale;
+            draw_line(-self.camera.x * scale, sy, -self.camera.x * scale + region.width as f32 * scale, sy, 1.0, grid_color);
+        }
+
+        // Sites and cities at the centre of their world tile
+        let icon_size = tile_px * 0.25;
+        for site in &world_map.sites {
+            if let Some((cx, cy)) = region.world_tile_to_cell(site.x, site.y) {
+                draw_site_icon(site, (cx - self.camera.x) * scale, (cy - self.camera.y) * scale, icon_size);
+            }
+        }
+        for city in &world_map.cities {
+            if let Some((cx, cy)) = region.world_tile_to_cell(city.x, city.y) {
+                draw_city_icon(city, (cx - self.camera.x) * scale, (cy - self.camera.y) * scale, icon_size);
+            }
+        }
+
+        draw_text(
+            &format!(
+                "Region around ({}, {}) - view: {:?} [1] Terrain [2] Elevation [3] Political",
+                region.center.0, region.center.1, self.view
+            ),
+            10.0,
+            20.0,
+            20.0,
+            WHITE,
+        );
+
+        // Hovered cell readout
+        let (mx, my) = mouse_position();
+        let cx = self.camera.x + mx / scale;
+        let cy = self.camera.y + my / scale;
+        if cx >= 0.0 && cy >= 0.0 && (cx as usize) < region.width && (cy as usize) < region.height {
+            let (x, y) = (cx as usize, cy as usize);
+            let (tx, ty) = region.cell_to_world_tile(x, y);
+            draw_text(
+                &format!(
+                    "Tile ({}, {}) {:?} elev {:.3}{}",
+                    tx, ty, region.biomes[x][y], region.elevation[x][y],
+                    if region.rivers[x][y] { " river" } else if region.roads[x][y] { " road" } else { "" }
+                ),
+                10.0,
+                40.0,
+                18.0,
+                WHITE,
+            );
+        }
+        draw_text("WASD/middle-drag: pan  Wheel: zoom  C: recentre  E: embark  Tab: world map", 10.0, screen_height() - 10.0, 18.0, WHITE);
+    }
+}
@@ -1,5 +1,13 @@
 use macroquad::prelude::*;
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::fluids::WATER_MAX;
+use crate::world::localmap::material_registry::MaterialRegistry;
+use crate::world::localmap::position::{WorldPos, CHUNK_SPAN};
 use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::tile::{Tile, TILE_SIZE};
+
+/// How much darker the level below looks when seen through open air
+const BELOW_SHADE: f32 = 0.45;
 
 pub struct TileRenderer;
 
@@ -9,95 +17,103 @@ impl Default for TileRenderer {
     }
 }
 
+fn shade(color: Color) -> Color {
+    Color::new(color.r * BELOW_SHADE, color.g * BELOW_SHADE, color.b * BELOW_SHADE, 1.0)
+}
+
 impl TileRenderer {
-    pub fn draw_tile(
+    /// Draws a chunk of the shown level into a texture, one texel per subpixel, with
+    /// `below` (the same chunk one level down) showing through its open air.
+    pub fn draw_chunk(&self, materials: &MaterialRegistry, chunk: &Chunk, below: Option<&Chunk>, origin: WorldPos) -> Texture2D {
+        let mut image = Image::gen_image_color(CHUNK_SPAN as u16, CHUNK_SPAN as u16, BLANK);
+        for (tx, column) in chunk.tiles.iter().enumerate() {
+            for (ty, tile) in column.iter().enumerate() {
+                let below = below.map(|b| &b.tiles[tx][ty]);
+                self.draw_tile(materials, tile, below, &mut image, (tx * TILE_SIZE, ty * TILE_SIZE), origin);
+            }
+        }
+        let texture = Texture2D::from_image(&image);
+        texture.set_filter(FilterMode::Nearest);
+        texture
+    }
+
+    /// Draws a tile of the current level at `at` in the chunk image. Where it is open air,
+    /// `below` (the same tile one level down) shows through, darkened.
+    fn draw_tile(
         &self,
-        tile: &crate::world::localmap::tile::Tile,
-        world_x: i32,
-        world_y: i32,
-        camera_x: f32,
-        camera_y: f32,
-        zoom: f32,
-        tile_size: i32,
-        world_left: i32,
-        world_right: i32,
-        world_top: i32,
-        world_bottom: i32,
+        materials: &MaterialRegistry,
+        tile: &Tile,
+        below: Option<&Tile>,
+        image: &mut Image,
+        (ix, iy): (usize, usize),
+        origin: WorldPos,
     ) {
-        if is_mixed_material(tile) {
-            // Per-subpixel render
-            let subgrid_size = tile.subgrid.len();
-            for sub_x in 0..subgrid_size {
-                for sub_y in 0..subgrid_size {
-                    let subpixel = &tile.subgrid[sub_x][sub_y];
-                    if subpixel.material == TerrainMaterial::Air {
-                        continue;
-                    }
-                    let pixel_x = world_x + sub_x as i32;
-                    let pixel_y = world_y + sub_y as i32;
+        let world_x = origin.x + ix as i32;
+        let world_y = origin.y + iy as i32;
+        let first = tile.get(0, 0);
+        let mat = first.material;
+        let shallow = mat == TerrainMaterial::Water && first.level < WATER_MAX;
+        let uniform = tile.uniform().is_some() && materials.is_plain(mat) && !shallow;
+        let mut draw_block = |color: Color| {
+            for sub_x in 0..TILE_SIZE {
+                for sub_y in 0..TILE_SIZE {
+                    image.set_pixel((ix + sub_x) as u32, (iy + sub_y) as u32, color);
+                }
+            }
+        };
 
-                    // Subpixel culling
-                    if pixel_x < world_left
-                        || pixel_x > world_right
-                        || pixel_y < world_top
-                        || pixel_y > world_bottom
-                    {
-                        continue;
+        if uniform && mat != TerrainMaterial::Air {
+            // Solid tile
+            draw_block(materials.color(mat, world_x, world_y));
+            return;
+        }
+        if uniform {
+            // Open tile: show the level below, or nothing
+            match below {
+                Some(below) if below.uniform().is_some_and(|cell| materials.is_plain(cell.material)) => {
+                    let below_mat = below.get(0, 0).material;
+                    if below_mat != TerrainMaterial::Air {
+                        draw_block(shade(materials.color(below_mat, world_x, world_y)));
                     }
+                    return;
+                }
+                Some(_) => {}
+                None => return,
+            }
+        }
 
-                    let sx = (pixel_x as f32 - camera_x) * zoom;
-                    let sy = (pixel_y as f32 - camera_y) * zoom;
-
-                    let color = match subpixel.material {
-                        TerrainMaterial::Dirt => BROWN,
-                        TerrainMaterial::Rock => GRAY,
-                        TerrainMaterial::Water => BLUE,
-                        _ => WHITE,
-                    };
-
-                    draw_rectangle(sx, sy, zoom, zoom, color);
+        // Per-subpixel render
+        for sub_x in 0..TILE_SIZE {
+            for sub_y in 0..TILE_SIZE {
+                let subpixel = tile.get(sub_x, sub_y);
+                let pixel_x = world_x + sub_x as i32;
+                let pixel_y = world_y + sub_y as i32;
 
-                    if subpixel.dig_target {
-                        let overlay = Color::new(1.0, 0.0, 0.0, 0.4);
-                        draw_rectangle(sx, sy, zoom, zoom, overlay);
+                let see_through = match below.map(|b| b.get(sub_x, sub_y).material) {
+                    Some(below_mat) if below_mat != TerrainMaterial::Air => {
+                        Some(shade(materials.color(below_mat, pixel_x, pixel_y)))
                     }
-                }
-            }
-        } else {
-            // Solid tile
-            let mat = tile.subgrid[0][0].material;
-            if mat == TerrainMaterial::Air {
-                // Don't draw anything for air tiles
-                return;
-            }
-            let fallback_color = match mat {
-                TerrainMaterial::Dirt => BROWN,
-                TerrainMaterial::Rock => GRAY,
-                TerrainMaterial::Water => BLUE,
-                _ => DARKGRAY,
-            };
+                    _ => None,
+                };
+                let color = match subpixel.material {
+                    TerrainMaterial::Air => match see_through {
+                        Some(color) => color,
+                        None => continue,
+                    },
+                    TerrainMaterial::Water if subpixel.level < WATER_MAX => {
+                        // Shallow water tints whatever shows through it
+                        let depth = 0.4 + 0.6 * subpixel.level as f32 / WATER_MAX as f32;
+                        mix(see_through.unwrap_or(BLACK), materials.color(TerrainMaterial::Water, pixel_x, pixel_y), depth)
+                    }
+                    material => materials.color(material, pixel_x, pixel_y),
+                };
 
-            let screen_x = (world_x as f32 - camera_x) * zoom;
-            let screen_y = (world_y as f32 - camera_y) * zoom;
-            draw_rectangle(
-                screen_x,
-                screen_y,
-                tile_size as f32 * zoom,
-                tile_size as f32 * zoom,
-                fallback_color,
-            );
+                image.set_pixel((ix + sub_x) as u32, (iy + sub_y) as u32, color);
+            }
         }
     }
 }
 
-fn is_mixed_material(tile: &crate::world::localmap::tile::Tile) -> bool {
-    let first = tile.subgrid[0][0].material;
-    for row in &tile.subgrid {
-        for sub in row {
-            if sub.material != first {
-                return true;
-            }
-        }
-    }
-    false
-} 
\ No newline at end of file
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    Color::new(a.r + (b.r - a.r) * t, a.g + (b.g - a.g) * t, a.b + (b.b - a.b) * t, 1.0)
+}
@@ -119,13 +119,20 @@ impl WorldMapRenderer {
                 }
             }
         }
-        // Draw cities as red circles
+        // Draw sites below cities
+        for site in &world_map.sites {
+            let sx = (site.x as f32 - camera.x) * cell_size + cell_size / 2.0;
+            let sy = (site.y as f32 - camera.y) * cell_size + cell_size / 2.0;
+            draw_site_icon(site, sx, sy, draw_size);
+        }
+        // Draw cities with tier-specific icons
         for city in &world_map.cities {
             let sx = (city.x as f32 - camera.x) * cell_size + cell_size / 2.0;
             let sy = (city.y as f32 - camera.y) * cell_size + cell_size / 2.0;
-            draw_circle(sx, sy, draw_size * 0.4, RED);
+            draw_city_icon(city, sx, sy, draw_size);
         }
         // Draw trade routes
         draw_trade_routes(world_map, camera, cell_size, sea_level);
+        draw_transport_nodes(world_map, camera, cell_size);
     }
 } 
\ No newline at end of file
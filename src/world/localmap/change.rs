@@ -0,0 +1,87 @@
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::zlevel::ZLevel;
+use std::collections::HashMap;
+
+/// What made a subpixel change material
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeCause {
+    /// A creature dug it out
+    Dig,
+    /// The player painted over it
+    Paint,
+    /// Water flowed in or out
+    Fluid,
+    /// Something fell into or out of it
+    Gravity,
+    /// The player took back an earlier change
+    Undo,
+    /// The player put back a change they had taken back
+    Redo,
+    /// A worker finished a construction
+    Build,
+}
+
+/// One subpixel changing material. Every change to the map goes through
+/// `World::set_material_at`, which announces it with one of these.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldChange {
+    pub pos: WorldPos,
+    pub old: TerrainMaterial,
+    pub new: TerrainMaterial,
+    pub cause: ChangeCause,
+}
+
+/// Number of loaded subpixels of each material, kept up to date from chunks coming and
+/// going and from `WorldChange`s rather than by rescanning the map.
+#[derive(Default)]
+pub struct BlockLedger {
+    counts: HashMap<TerrainMaterial, usize>,
+}
+
+impl BlockLedger {
+    /// Counts every chunk on the given levels.
+    pub fn count(levels: &[ZLevel]) -> Self {
+        let mut ledger = Self::default();
+        for chunk in levels.iter().flat_map(|level| level.chunks.values()) {
+            ledger.add_chunk(chunk);
+        }
+        ledger
+    }
+
+    pub fn get(&self, material: TerrainMaterial) -> usize {
+        self.counts.get(&material).copied().unwrap_or(0)
+    }
+
+    pub fn add_chunk(&mut self, chunk: &Chunk) {
+        for (material, count) in chunk.material_counts() {
+            self.add(material, count);
+        }
+    }
+
+    pub fn remove_chunk(&mut self, chunk: &Chunk) {
+        for (material, count) in chunk.material_counts() {
+            self.remove(material, count);
+        }
+    }
+
+    pub fn apply(&mut self, change: &WorldChange) {
+        self.remove(change.old, 1);
+        self.add(change.new, 1);
+    }
+
+    fn add(&mut self, material: TerrainMaterial, count: usize) {
+        *self.counts.entry(material).or_insert(0) += count;
+    }
+
+    /// Takes `count` away, never going below zero; materials that run out are dropped.
+    fn remove(&mut self, material: TerrainMaterial, count: usize) {
+        if let Some(total) = self.counts.get_mut(&material) {
+            *total = total.saturating_sub(count);
+            if *total == 0 {
+                self.counts.remove(&material);
+            }
+        }
+    }
+}
@@ -1,17 +1,98 @@
-use crate::world::localmap::tile::Tile;
+use crate::world::localmap::subpixel::Subpixel;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::tile::{Cell, Tile, TILE_SIZE};
+use std::collections::{HashMap, HashSet};
 
 pub const CHUNK_SIZE: usize = 32;
 
 pub struct Chunk {
     pub tiles: [[Tile; CHUNK_SIZE]; CHUNK_SIZE],
+    /// Subpixels marked for digging, by position within the chunk; there are few, so
+    /// they are kept here rather than in every tile
+    pub dig_targets: HashSet<(u8, u8)>,
+    /// Needs a fluid simulation step
     pub dirty: bool,
+    /// Changed since it was generated, so it must be kept on disk when unloaded
+    pub modified: bool,
 }
 
 impl Chunk {
     pub fn new() -> Self {
         Self {
-            tiles: [[Tile::default(); CHUNK_SIZE]; CHUNK_SIZE],
+            tiles: std::array::from_fn(|_| std::array::from_fn(|_| Tile::default())),
+            dig_targets: HashSet::new(),
             dirty: false,
+            modified: false,
         }
     }
+
+    /// The subpixel at (x, y) within the chunk, flags included.
+    pub fn get(&self, x: usize, y: usize) -> Subpixel {
+        let cell = self.tiles[x / TILE_SIZE][y / TILE_SIZE].get(x % TILE_SIZE, y % TILE_SIZE);
+        Subpixel {
+            material: cell.material,
+            dig_target: self.dig_targets.contains(&(x as u8, y as u8)),
+            level: cell.level,
+        }
+    }
+
+    /// Material of the subpixel at (x, y) within the chunk, without looking up its flags.
+    pub fn material(&self, x: usize, y: usize) -> TerrainMaterial {
+        self.tiles[x / TILE_SIZE][y / TILE_SIZE].get(x % TILE_SIZE, y % TILE_SIZE).material
+    }
+
+    /// Changes the subpixel at (x, y) within the chunk and marks its tile dirty. The tile
+    /// is compacted only when the cell that was there is no longer used anywhere in it.
+    pub fn set(&mut self, x: usize, y: usize, subpixel: Subpixel) {
+        let tile = &mut self.tiles[x / TILE_SIZE][y / TILE_SIZE];
+        let (sx, sy) = (x % TILE_SIZE, y % TILE_SIZE);
+        let (old, new) = (tile.get(sx, sy), Cell { material: subpixel.material, level: subpixel.level });
+        tile.set(sx, sy, new);
+        if old != new && !tile.contains(old) {
+            tile.compact();
+        }
+        tile.dirty = true;
+        if subpixel.dig_target {
+            self.dig_targets.insert((x as u8, y as u8));
+        } else {
+            self.dig_targets.remove(&(x as u8, y as u8));
+        }
+    }
+
+    /// Number of subpixels of each material.
+    pub fn material_counts(&self) -> HashMap<TerrainMaterial, usize> {
+        let mut counts = HashMap::new();
+        for tile in self.tiles.iter().flatten() {
+            for (cell, count) in tile.cell_counts() {
+                *counts.entry(cell.material).or_insert(0) += count;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subpixel(material: TerrainMaterial) -> Subpixel {
+        Subpixel { material, dig_target: false, level: 0 }
+    }
+
+    #[test]
+    fn a_tile_goes_back_to_uniform_once_its_last_odd_subpixel_is_undone() {
+        let mut chunk = Chunk::new();
+        chunk.set(9, 9, subpixel(TerrainMaterial::Rock));
+        chunk.set(10, 9, subpixel(TerrainMaterial::Rock));
+        assert!(chunk.tiles[1][1].uniform().is_none());
+
+        // Rock is still used at (10, 9), so the tile stays mixed
+        chunk.set(9, 9, subpixel(TerrainMaterial::Air));
+        assert!(chunk.tiles[1][1].uniform().is_none());
+        assert_eq!(chunk.material(10, 9), TerrainMaterial::Rock);
+
+        chunk.set(10, 9, subpixel(TerrainMaterial::Air));
+        assert_eq!(chunk.tiles[1][1].uniform().map(|cell| cell.material), Some(TerrainMaterial::Air));
+        assert_eq!(chunk.material_counts()[&TerrainMaterial::Air], CHUNK_SIZE * CHUNK_SIZE * TILE_SIZE * TILE_SIZE);
+    }
 }
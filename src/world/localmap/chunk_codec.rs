@@ -0,0 +1,225 @@
+//! Compact byte encoding of chunks and the plants growing on them, shared by region files and saves
+use crate::plants::{Plant, Species};
+use crate::world::localmap::binary::{ByteReader, ByteWriter};
+use crate::world::localmap::chunk::{Chunk, CHUNK_SIZE};
+use crate::world::localmap::subpixel::Subpixel;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::tile::{Cell, Tile, TILE_SIZE};
+use std::collections::HashSet;
+
+/// Set in the material byte of subpixels that are dig targets
+const DIG_TARGET_BIT: u8 = 0x80;
+/// Tile tags: one subpixel repeated over the tile, or runs of subpixels
+const TILE_UNIFORM: u8 = 0;
+const TILE_RUNS: u8 = 1;
+/// Set in the tile tag when the tile's dirty flag is
+const TILE_DIRTY_BIT: u8 = 0x80;
+
+fn write_subpixel(out: &mut ByteWriter, sub: &Subpixel) {
+    let flag = if sub.dig_target { DIG_TARGET_BIT } else { 0 };
+    out.u8(sub.material as u8 | flag);
+    out.u8(sub.level);
+}
+
+fn read_subpixel(input: &mut ByteReader) -> Option<Subpixel> {
+    let material = input.u8()?;
+    Some(Subpixel {
+        material: TerrainMaterial::from_index(material & !DIG_TARGET_BIT)?,
+        dig_target: material & DIG_TARGET_BIT != 0,
+        level: input.u8()?,
+    })
+}
+
+fn same(a: &Subpixel, b: &Subpixel) -> bool {
+    a.material == b.material && a.level == b.level && a.dig_target == b.dig_target
+}
+
+/// Encodes a chunk tile by tile. Most tiles are a single material and take three bytes;
+/// mixed tiles are run-length encoded as (subpixel, count) pairs.
+pub fn encode_chunk(chunk: &Chunk) -> Vec<u8> {
+    let mut out = ByteWriter::default();
+    out.bool(chunk.dirty);
+    out.bool(chunk.modified);
+    // Tiles with a dig target in them can't be stored as one subpixel
+    let flagged: HashSet<(usize, usize)> = chunk
+        .dig_targets
+        .iter()
+        .map(|&(x, y)| (x as usize / TILE_SIZE, y as usize / TILE_SIZE))
+        .collect();
+    for (tx, column) in chunk.tiles.iter().enumerate() {
+        for (ty, tile) in column.iter().enumerate() {
+            let dirty = if tile.dirty { TILE_DIRTY_BIT } else { 0 };
+            if let Some(cell) = tile.uniform().filter(|_| !flagged.contains(&(tx, ty))) {
+                out.u8(TILE_UNIFORM | dirty);
+                write_subpixel(&mut out, &Subpixel { material: cell.material, dig_target: false, level: cell.level });
+                continue;
+            }
+            out.u8(TILE_RUNS | dirty);
+            let cells = (0..TILE_SIZE * TILE_SIZE)
+                .map(|i| chunk.get(tx * TILE_SIZE + i / TILE_SIZE, ty * TILE_SIZE + i % TILE_SIZE));
+            let mut run: Option<(Subpixel, u8)> = None;
+            for sub in cells {
+                run = match run {
+                    Some((current, count)) if same(&current, &sub) => Some((current, count + 1)),
+                    Some((current, count)) => {
+                        write_subpixel(&mut out, &current);
+                        out.u8(count);
+                        Some((sub, 1))
+                    }
+                    None => Some((sub, 1)),
+                };
+            }
+            if let Some((current, count)) = run {
+                write_subpixel(&mut out, &current);
+                out.u8(count);
+            }
+        }
+    }
+    out.bytes
+}
+
+/// Rebuilds a chunk from [`encode_chunk`] output; `None` if the bytes are corrupt.
+pub fn decode_chunk(bytes: &[u8]) -> Option<Chunk> {
+    let mut input = ByteReader::new(bytes);
+    let mut chunk = Chunk::new();
+    chunk.dirty = input.bool()?;
+    chunk.modified = input.bool()?;
+    for tx in 0..CHUNK_SIZE {
+        for ty in 0..CHUNK_SIZE {
+            let tag = input.u8()?;
+            let mut tile = Tile::default();
+            match tag & !TILE_DIRTY_BIT {
+                TILE_UNIFORM => {
+                    let sub = read_subpixel(&mut input)?;
+                    tile = Tile::filled(Cell { material: sub.material, level: sub.level });
+                    if sub.dig_target {
+                        for i in 0..TILE_SIZE * TILE_SIZE {
+                            let (x, y) = (tx * TILE_SIZE + i / TILE_SIZE, ty * TILE_SIZE + i % TILE_SIZE);
+                            chunk.dig_targets.insert((x as u8, y as u8));
+                        }
+                    }
+                }
+                TILE_RUNS => {
+                    let mut filled = 0;
+                    while filled < TILE_SIZE * TILE_SIZE {
+                        let sub = read_subpixel(&mut input)?;
+                        let count = input.u8()? as usize;
+                        if count == 0 || filled + count > TILE_SIZE * TILE_SIZE {
+                            return None;
+                        }
+                        for i in filled..filled + count {
+                            let (sx, sy) = (i / TILE_SIZE, i % TILE_SIZE);
+                            tile.set(sx, sy, Cell { material: sub.material, level: sub.level });
+                            if sub.dig_target {
+                                chunk.dig_targets.insert(((tx * TILE_SIZE + sx) as u8, (ty * TILE_SIZE + sy) as u8));
+                            }
+                        }
+                        filled += count;
+                    }
+                    tile.compact();
+                }
+                _ => return None,
+            }
+            tile.dirty = tag & TILE_DIRTY_BIT != 0;
+            chunk.tiles[tx][ty] = tile;
+        }
+    }
+    input.is_empty().then_some(chunk)
+}
+
+pub fn write_plant(out: &mut ByteWriter, plant: &Plant) {
+    out.i32(plant.x);
+    out.i32(plant.y);
+    out.i32(plant.z);
+    out.u8(plant.species as u8);
+    out.f32(plant.growth);
+    out.bool(plant.chop);
+    out.u32(plant.regrow_timer);
+    out.u32(plant.fruit_timer);
+}
+
+pub fn read_plant(input: &mut ByteReader) -> Option<Plant> {
+    let (x, y, z) = (input.i32()?, input.i32()?, input.i32()?);
+    let species = *Species::ALL.get(input.u8()? as usize)?;
+    let mut plant = Plant::new(x, y, z, species, input.f32()?);
+    plant.chop = input.bool()?;
+    plant.regrow_timer = input.u32()?;
+    plant.fruit_timer = input.u32()?;
+    Some(plant)
+}
+
+/// Encodes the plants of a chunk column, each with the priority of its chop job (only
+/// meaningful for plants marked for felling).
+pub fn encode_plants(plants: &[(Plant, u8)]) -> Vec<u8> {
+    let mut out = ByteWriter::default();
+    out.u32(plants.len() as u32);
+    for (plant, priority) in plants {
+        write_plant(&mut out, plant);
+        out.u8(*priority);
+    }
+    out.bytes
+}
+
+/// Reads back [`encode_plants`] output; `None` if the bytes are corrupt.
+pub fn decode_plants(bytes: &[u8]) -> Option<Vec<(Plant, u8)>> {
+    let mut input = ByteReader::new(bytes);
+    let plants = (0..input.u32()?)
+        .map(|_| Some((read_plant(&mut input)?, input.u8()?)))
+        .collect::<Option<Vec<_>>>()?;
+    input.is_empty().then_some(plants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::localmap::fluids::WATER_MAX;
+
+    fn assert_same_chunk(a: &Chunk, b: &Chunk) {
+        for x in 0..CHUNK_SIZE * TILE_SIZE {
+            for y in 0..CHUNK_SIZE * TILE_SIZE {
+                assert!(same(&a.get(x, y), &b.get(x, y)), "subpixel ({}, {}) differs", x, y);
+            }
+        }
+        for (ta, tb) in a.tiles.iter().flatten().zip(b.tiles.iter().flatten()) {
+            assert_eq!(ta.uniform(), tb.uniform());
+            assert_eq!(ta.dirty, tb.dirty);
+        }
+        assert_eq!(a.dig_targets, b.dig_targets);
+        assert_eq!((a.dirty, a.modified), (b.dirty, b.modified));
+    }
+
+    #[test]
+    fn chunks_survive_a_round_trip() {
+        let mut chunk = Chunk::new();
+        chunk.modified = true;
+        // A uniform rock tile, one marked for digging throughout, and a mixed one
+        chunk.tiles[0][0] = Tile::filled(Cell { material: TerrainMaterial::Rock, level: 0 });
+        chunk.tiles[1][0] = Tile::filled(Cell { material: TerrainMaterial::Granite, level: 0 });
+        for x in TILE_SIZE..2 * TILE_SIZE {
+            for y in 0..TILE_SIZE {
+                chunk.dig_targets.insert((x as u8, y as u8));
+            }
+        }
+        chunk.set(17, 3, Subpixel { material: TerrainMaterial::Dirt, dig_target: true, level: 0 });
+        chunk.set(18, 3, Subpixel { material: TerrainMaterial::Clay, dig_target: false, level: 0 });
+        // Water at every level, in a tile otherwise of air
+        for level in 1..=WATER_MAX {
+            chunk.set(40 + level as usize, 40, Subpixel { material: TerrainMaterial::Water, dig_target: false, level });
+        }
+        chunk.tiles[5][5].dirty = false;
+
+        let decoded = decode_chunk(&encode_chunk(&chunk)).unwrap();
+        assert_same_chunk(&chunk, &decoded);
+        assert_eq!(decoded.tiles[0][0].uniform().map(|cell| cell.material), Some(TerrainMaterial::Rock));
+        assert!(decoded.tiles[2][0].uniform().is_none());
+    }
+
+    #[test]
+    fn corrupt_chunks_are_refused() {
+        let bytes = encode_chunk(&Chunk::new());
+        assert!(decode_chunk(&bytes[..bytes.len() - 1]).is_none());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(decode_chunk(&longer).is_none());
+    }
+}
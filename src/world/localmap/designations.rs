@@ -0,0 +1,76 @@
+use crate::construction::Construction;
+use crate::jobs::JobKind;
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::world::World;
+
+/// Designations: marks on the map that ask for work, each backed by a job on the board.
+/// They go through here so the marks and the board never disagree.
+impl World {
+    /// Marks a subpixel for digging and posts its dig job. Returns false if it was already
+    /// designated or isn't loaded.
+    pub fn designate_dig(&mut self, pos: WorldPos, priority: u8) -> bool {
+        let designated = self
+            .update_subpixel(pos.x, pos.y, pos.z, |subpixel| !std::mem::replace(&mut subpixel.dig_target, true))
+            .unwrap_or(false);
+        if designated {
+            self.jobs.post(JobKind::Dig, pos, priority);
+        }
+        designated
+    }
+
+    /// Lifts a dig designation and takes its job off the board. Returns whether there was one.
+    pub fn cancel_dig(&mut self, pos: WorldPos) -> bool {
+        let cancelled = self
+            .update_subpixel(pos.x, pos.y, pos.z, |subpixel| std::mem::replace(&mut subpixel.dig_target, false))
+            .unwrap_or(false);
+        self.jobs.withdraw(JobKind::Dig, pos);
+        cancelled
+    }
+
+    /// Plans a construction on a subpixel and posts its build job. Returns false if the
+    /// structure can't go there, the subpixel is marked for digging, or something is planned
+    /// there already.
+    pub fn designate_build(&mut self, pos: WorldPos, construction: Construction, priority: u8) -> bool {
+        let Some(subpixel) = self.subpixel_at(pos) else { return false; };
+        if subpixel.dig_target
+            || self.blueprints.contains_key(&pos)
+            || !construction.structure.can_build_on(&self.materials, subpixel.material)
+        {
+            return false;
+        }
+        self.blueprints.insert(pos, construction);
+        self.jobs.post(JobKind::Build, pos, priority);
+        true
+    }
+
+    /// Drops a planned construction and takes its job off the board. Returns what was planned.
+    pub fn cancel_build(&mut self, pos: WorldPos) -> Option<Construction> {
+        self.jobs.withdraw(JobKind::Build, pos);
+        self.blueprints.remove(&pos)
+    }
+
+    /// Marks the grown tree standing at `pos` for felling and posts its chop job. Returns
+    /// false if there is no such tree or it is marked already.
+    pub fn designate_chop(&mut self, pos: WorldPos, priority: u8) -> bool {
+        let Some(plant) = self
+            .plants
+            .iter_mut()
+            .find(|plant| WorldPos::new(plant.x, plant.y, plant.z) == pos && plant.can_chop() && !plant.chop)
+        else {
+            return false;
+        };
+        plant.chop = true;
+        self.jobs.post(JobKind::Chop, pos, priority);
+        true
+    }
+
+    /// Lifts the chop mark of the tree at `pos` and takes its job off the board.
+    pub fn cancel_chop(&mut self, pos: WorldPos) -> bool {
+        let mut cancelled = false;
+        for plant in self.plants.iter_mut().filter(|plant| WorldPos::new(plant.x, plant.y, plant.z) == pos) {
+            cancelled |= std::mem::replace(&mut plant.chop, false);
+        }
+        self.jobs.withdraw(JobKind::Chop, pos);
+        cancelled
+    }
+}
@@ -0,0 +1,244 @@
+use crate::world::localmap::change::ChangeCause;
+use crate::world::localmap::position::{ChunkPos, WorldPos};
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::tile::TILE_SIZE;
+use crate::world::localmap::world::World;
+use macroquad::rand::gen_range;
+use std::collections::{HashSet, VecDeque};
+
+/// Water held by a full subpixel
+pub const WATER_MAX: u8 = 8;
+/// One in this many steps a lone film of water (level 1) dries up
+const EVAPORATION_CHANCE: u32 = 600;
+/// Cells visited when looking for where pressurised water comes out
+const PRESSURE_SEARCH: usize = 96;
+/// Pressure searches allowed per step, so deep pools stay cheap
+const PRESSURE_BUDGET: usize = 64;
+
+const SIDEWAYS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Cellular-automaton water. Water falls into open space below, spreads sideways into
+/// hollows (open air, not floors) and, when a full column pushes down on full water,
+/// comes out at the nearest open cell below the column's top, so U-bends fill up.
+///
+/// Only dirty chunks are simulated: anything that changes a subpixel marks its chunk,
+/// and a chunk where nothing moved goes quiet again.
+impl World {
+    /// Water in a cell, or `None` if water can't go there (solid, floor, or not generated).
+    fn water_room(&self, x: i32, y: i32, z: i32) -> Option<u8> {
+        let subpixel = self.get_subpixel(x, y, z)?;
+        match subpixel.material {
+            TerrainMaterial::Water => Some(subpixel.level),
+            TerrainMaterial::Air => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Sets the water in a cell, waking the chunks around it only if it actually changed.
+    fn set_water(&mut self, x: i32, y: i32, z: i32, level: u8) {
+        let Some(current) = self.get_subpixel(x, y, z) else { return; };
+        let material = if level == 0 { TerrainMaterial::Air } else { TerrainMaterial::Water };
+        if current.material == material && current.level == level {
+            return;
+        }
+        if current.material != material {
+            // Wakes the neighbourhood itself
+            self.set_material_at(x, y, z, material, ChangeCause::Fluid);
+        } else {
+            self.wake_fluids(x, y, z);
+        }
+        self.update_subpixel(x, y, z, |subpixel| subpixel.level = level);
+    }
+
+    /// Marks the chunks around a changed subpixel (and the level above) for simulation.
+    pub fn wake_fluids(&mut self, x: i32, y: i32, z: i32) {
+        let mut keys = Vec::with_capacity(4);
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let key = WorldPos::new(x + dx, y + dy, z).chunk().key();
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        for level_z in [z, z + 1] {
+            let Some(level) = self.z_level_mut(level_z) else { continue; };
+            for key in &keys {
+                if let Some(chunk) = level.chunks.get_mut(key) {
+                    chunk.dirty = true;
+                }
+            }
+        }
+    }
+
+    /// Advances the water simulation by one step.
+    pub fn step_fluids(&mut self) {
+        // Collect and clear the dirty chunks, top level first so falling water keeps moving
+        let mut active = Vec::new();
+        for level in &mut self.z_levels {
+            for (&(cx, cy), chunk) in level.chunks.iter_mut() {
+                if chunk.dirty {
+                    chunk.dirty = false;
+                    active.push((cx, cy, level.z));
+                }
+            }
+        }
+
+        let mut pressure_budget = PRESSURE_BUDGET;
+        for (cx, cy, z) in active {
+            let Some(chunk) = self.z_level(z).and_then(|level| level.chunks.get(&(cx, cy))) else { continue; };
+            let origin = ChunkPos { x: cx, y: cy, z }.origin();
+            let mut cells = Vec::new();
+            for (tx, column) in chunk.tiles.iter().enumerate() {
+                for (ty, tile) in column.iter().enumerate() {
+                    // Most tiles hold no water at all
+                    if tile.uniform().is_some_and(|cell| cell.material != TerrainMaterial::Water) {
+                        continue;
+                    }
+                    for sx in 0..TILE_SIZE {
+                        for sy in 0..TILE_SIZE {
+                            if tile.get(sx, sy).material == TerrainMaterial::Water {
+                                cells.push((
+                                    origin.x + (tx * TILE_SIZE + sx) as i32,
+                                    origin.y + (ty * TILE_SIZE + sy) as i32,
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            // Alternate the sweep direction so water doesn't drift one way
+            if gen_range(0, 2) == 0 {
+                cells.reverse();
+            }
+            for (x, y) in cells {
+                self.flow(x, y, z, &mut pressure_budget);
+            }
+        }
+    }
+
+    fn flow(&mut self, x: i32, y: i32, z: i32, pressure_budget: &mut usize) {
+        let Some(mut level) = self.water_room(x, y, z) else { return; };
+        if level == 0 {
+            return;
+        }
+
+        // Fall into open space below
+        match self.water_room(x, y, z - 1) {
+            Some(below) if below < WATER_MAX => {
+                let moved = level.min(WATER_MAX - below);
+                self.set_water(x, y, z - 1, below + moved);
+                level -= moved;
+                self.set_water(x, y, z, level);
+                if level == 0 {
+                    return;
+                }
+            }
+            Some(_) if level == WATER_MAX && *pressure_budget > 0 => {
+                // Resting on full water: push it out wherever the pool is open lower down
+                *pressure_budget -= 1;
+                if let Some((ox, oy, oz)) = self.pressure_outlet(x, y, z) {
+                    let room = WATER_MAX - self.water_room(ox, oy, oz).unwrap_or(WATER_MAX);
+                    let moved = level.min(room);
+                    self.set_water(ox, oy, oz, WATER_MAX - room + moved);
+                    level -= moved;
+                    self.set_water(x, y, z, level);
+                    if level == 0 {
+                        return;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // Spread sideways, evening out with lower neighbours
+        let start = gen_range(0, SIDEWAYS.len());
+        for i in 0..SIDEWAYS.len() {
+            let (dx, dy) = SIDEWAYS[(start + i) % SIDEWAYS.len()];
+            if level <= 1 {
+                break;
+            }
+            let Some(next) = self.water_room(x + dx, y + dy, z) else { continue; };
+            if next + 1 < level {
+                let moved = (level - next) / 2;
+                self.set_water(x + dx, y + dy, z, next + moved);
+                level -= moved;
+                self.set_water(x, y, z, level);
+            }
+        }
+
+        // Thin films sometimes dry up while their chunk is awake; a film that can't go
+        // anywhere settles and lets the chunk sleep
+        if level == 1 && gen_range(0, EVAPORATION_CHANCE) == 0 {
+            self.set_water(x, y, z, 0);
+        }
+    }
+
+    /// Nearest open cell reachable through full water from `(x, y, z)` that lies below it.
+    fn pressure_outlet(&self, x: i32, y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert((x, y, z - 1));
+        queue.push_back((x, y, z - 1));
+        while let Some((cx, cy, cz)) = queue.pop_front() {
+            if seen.len() > PRESSURE_SEARCH {
+                return None;
+            }
+            for (dx, dy, dz) in [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, -1), (0, 0, 1)] {
+                let next = (cx + dx, cy + dy, cz + dz);
+                if next.2 >= z || !seen.insert(next) {
+                    continue;
+                }
+                match self.water_room(next.0, next.1, next.2) {
+                    Some(WATER_MAX) => queue.push_back(next),
+                    Some(_) => return Some(next),
+                    None => {}
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::registry::{ItemRegistry, ITEMS_PATH};
+    use crate::world::localmap::chunk::Chunk;
+    use crate::world::localmap::material_registry::{MaterialRegistry, MATERIALS_PATH};
+    use std::sync::Arc;
+
+    fn world() -> World {
+        let materials = MaterialRegistry::from_json(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap();
+        let item_kinds = ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap();
+        let mut world = World::new(Arc::new(materials), Arc::new(item_kinds));
+        world.insert_chunk(0, (0, 0), Chunk::new());
+        world
+    }
+
+    fn awake(world: &World) -> bool {
+        world.z_levels.iter().flat_map(|level| level.chunks.values()).any(|chunk| chunk.dirty)
+    }
+
+    #[test]
+    fn a_settled_film_lets_its_chunk_sleep() {
+        let mut world = world();
+        world.set_water(5, 5, 0, 1);
+        // The first step finds nothing to move; a film that happened to dry up takes one more
+        for _ in 0..3 {
+            world.step_fluids();
+        }
+        assert!(!awake(&world));
+    }
+
+    #[test]
+    fn setting_the_same_water_level_wakes_nothing() {
+        let mut world = world();
+        world.set_water(5, 5, 0, 4);
+        world.step_fluids();
+        for level in &mut world.z_levels {
+            level.chunks.values_mut().for_each(|chunk| chunk.dirty = false);
+        }
+        let level = world.get_subpixel(5, 5, 0).unwrap().level;
+        world.set_water(5, 5, 0, level);
+        assert!(!awake(&world));
+    }
+}
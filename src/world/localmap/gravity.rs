@@ -0,0 +1,179 @@
+use crate::particle::Particle;
+use crate::world::localmap::change::ChangeCause;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::world::World;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+/// How far (in subpixels) a stone ceiling may reach from the nearest wall below it
+const MAX_SPAN: i32 = 8;
+/// Frames an unsupported stone ceiling creaks before it comes down
+const COLLAPSE_DELAY: u32 = 180;
+/// Damage dealt by a falling subpixel of loose material and of stone
+const LOOSE_DAMAGE: f32 = 2.0;
+const STONE_DAMAGE: f32 = 25.0;
+
+const SIDEWAYS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Where something came down this step, and how hard it hits whoever stands there
+pub struct Landing {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub damage: f32,
+}
+
+/// Structural rules. Loose materials fall into open space below or slide down into a
+/// neighbouring hole; stone ceilings too far from any wall below creak for a while and
+/// then cave in. Only cells next to a change are checked, so untouched ground stays put.
+impl World {
+    /// Queues the cells whose support may have changed along with `(x, y, z)`. When the
+    /// cell was dug out (`opened`), the ceilings it may have held up are checked as well.
+    pub fn wake_gravity(&mut self, x: i32, y: i32, z: i32, opened: bool) {
+        if opened {
+            self.queue_unsupported_ceilings(x, y, z);
+        }
+        self.gravity_checks.insert((x, y, z));
+        self.gravity_checks.insert((x, y, z + 1));
+        for (dx, dy) in SIDEWAYS {
+            self.gravity_checks.insert((x + dx, y + dy, z));
+            self.gravity_checks.insert((x + dx, y + dy, z + 1));
+        }
+    }
+
+    /// Open space that something can fall into: air on a generated level.
+    fn is_open(&self, x: i32, y: i32, z: i32) -> bool {
+        self.get_subpixel(x, y, z)
+            .is_some_and(|subpixel| subpixel.material == TerrainMaterial::Air)
+    }
+
+    /// Whether any solid cell one level down, within `MAX_SPAN`, holds up a ceiling at `(x, y, z)`.
+    fn is_supported(&self, x: i32, y: i32, z: i32) -> bool {
+        for dx in -MAX_SPAN..=MAX_SPAN {
+            for dy in -MAX_SPAN..=MAX_SPAN {
+                let below = self.get_material_at(x + dx, y + dy, z - 1);
+                if self.materials.is_solid(below) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Starts the countdown on every stone ceiling one level above `(x, y, z)` that no longer
+    /// has a wall within `MAX_SPAN`. Walls are summed once over the surrounding window, so
+    /// each ceiling is a constant-time lookup.
+    fn queue_unsupported_ceilings(&mut self, x: i32, y: i32, z: i32) {
+        let reach = 2 * MAX_SPAN;
+        let size = (2 * reach + 1) as usize;
+        let stride = size + 1;
+        // sums[i][j] = solid cells in the window's first i columns and j rows
+        let mut sums = vec![0u32; stride * stride];
+        for i in 0..size {
+            for j in 0..size {
+                let material = self.get_material_at(x - reach + i as i32, y - reach + j as i32, z);
+                let solid = self.materials.is_solid(material) as u32;
+                sums[(i + 1) * stride + j + 1] = solid + sums[i * stride + j + 1] + sums[(i + 1) * stride + j] - sums[i * stride + j];
+            }
+        }
+        let walls = |i0: usize, j0: usize, i1: usize, j1: usize| {
+            sums[i1 * stride + j1] + sums[i0 * stride + j0] - sums[i0 * stride + j1] - sums[i1 * stride + j0]
+        };
+        for cx in -MAX_SPAN..=MAX_SPAN {
+            for cy in -MAX_SPAN..=MAX_SPAN {
+                let (i0, j0) = ((cx + MAX_SPAN) as usize, (cy + MAX_SPAN) as usize);
+                let span = (2 * MAX_SPAN + 1) as usize;
+                if walls(i0, j0, i0 + span, j0 + span) > 0 {
+                    continue;
+                }
+                let (px, py) = (x + cx, y + cy);
+                let ceiling = self.materials.get(self.get_material_at(px, py, z + 1));
+                if ceiling.solid && !ceiling.loose && self.is_open(px, py, z) {
+                    self.collapses.entry((px, py, z + 1)).or_insert(COLLAPSE_DELAY);
+                }
+            }
+        }
+    }
+
+    /// Moves whatever is at `(x, y, z)` to `to`, leaving air behind.
+    fn drop_material(&mut self, (x, y, z): (i32, i32, i32), to: (i32, i32, i32)) {
+        let material = self.get_material_at(x, y, z);
+        self.set_material_at(to.0, to.1, to.2, material, ChangeCause::Gravity);
+        self.set_material_at(x, y, z, TerrainMaterial::Air, ChangeCause::Gravity);
+    }
+
+    /// Advances falling, sliding and caving in by one step; returns where things landed.
+    pub fn step_gravity(&mut self, particles: &mut Vec<Particle>) -> Vec<Landing> {
+        let mut landings = Vec::new();
+        let checks: Vec<_> = self.gravity_checks.drain().collect();
+        for (x, y, z) in checks {
+            let material = self.get_material_at(x, y, z);
+            let props = self.materials.get(material);
+            if props.loose {
+                if self.is_open(x, y, z - 1) {
+                    self.drop_material((x, y, z), (x, y, z - 1));
+                    landings.push(Landing { x, y, z: z - 1, damage: LOOSE_DAMAGE });
+                    if gen_range(0, 8) == 0 {
+                        particles.push(dust(x, y, self.materials.color(material, x, y)));
+                    }
+                    continue;
+                }
+                // Slide down into a neighbouring hole
+                let start = gen_range(0, SIDEWAYS.len());
+                for i in 0..SIDEWAYS.len() {
+                    let (dx, dy) = SIDEWAYS[(start + i) % SIDEWAYS.len()];
+                    let (nx, ny) = (x + dx, y + dy);
+                    if self.is_open(nx, ny, z) && self.is_open(nx, ny, z - 1) {
+                        self.drop_material((x, y, z), (nx, ny, z - 1));
+                        landings.push(Landing { x: nx, y: ny, z: z - 1, damage: LOOSE_DAMAGE });
+                        break;
+                    }
+                }
+            } else if props.solid && self.is_open(x, y, z - 1) && !self.is_supported(x, y, z) {
+                self.collapses.entry((x, y, z)).or_insert(COLLAPSE_DELAY);
+            }
+        }
+
+        // Count down the creaking ceilings; dust trickles down as a warning
+        let mut due = Vec::new();
+        for (&(x, y, z), timer) in self.collapses.iter_mut() {
+            *timer -= 1;
+            if *timer == 0 {
+                due.push((x, y, z));
+            } else if gen_range(0, 40) == 0 {
+                particles.push(dust(x, y, LIGHTGRAY));
+            }
+        }
+        for cell in &due {
+            self.collapses.remove(cell);
+        }
+        // A wall built (or rubble piled up) in the meantime saves the ceiling, and one that
+        // was dug out has nothing left to fall. Everything is checked before anything falls,
+        // so a span coming down together doesn't prop itself up.
+        due.retain(|&(x, y, z)| {
+            self.materials.is_solid(self.get_material_at(x, y, z))
+                && self.is_open(x, y, z - 1)
+                && !self.is_supported(x, y, z)
+        });
+        for (x, y, z) in due {
+            let color = self.materials.color(self.get_material_at(x, y, z), x, y);
+            self.drop_material((x, y, z), (x, y, z - 1));
+            landings.push(Landing { x, y, z: z - 1, damage: STONE_DAMAGE });
+            for _ in 0..3 {
+                particles.push(dust(x, y, color));
+            }
+        }
+        landings
+    }
+}
+
+fn dust(x: i32, y: i32, color: Color) -> Particle {
+    Particle {
+        x: x as f32 + gen_range(0.0, 1.0),
+        y: y as f32 + gen_range(0.0, 1.0),
+        dx: gen_range(-0.3, 0.3),
+        dy: gen_range(-0.6, -0.1),
+        life: gen_range(20, 40),
+        color: Color::new(color.r, color.g, color.b, 0.7),
+    }
+}
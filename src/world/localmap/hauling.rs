@@ -0,0 +1,112 @@
+use crate::items::{Item, ItemCategory, ItemId};
+use crate::jobs::JobState;
+use crate::world::localmap::position::WorldPos;
+use crate::world::localmap::world::World;
+use std::collections::HashSet;
+
+/// Ticks between looks for items that should go to a stockpile
+const HAUL_CHECK_INTERVAL: u64 = 30;
+
+/// Items on the ground and the stockpiles they are hauled to.
+impl World {
+    /// Puts a new item on the ground and returns its id.
+    pub fn drop_item(&mut self, kind: &str, x: f32, y: f32, z: i32) -> ItemId {
+        let id = self.next_item_id;
+        self.next_item_id += 1;
+        self.items.push(Item::new(id, kind, x, y, z));
+        id
+    }
+
+    /// Picks an item up off the ground.
+    pub fn take_item(&mut self, id: ItemId) -> Option<Item> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        Some(self.items.swap_remove(index))
+    }
+
+    /// The category of the item's kind, from the item registry.
+    pub fn category(&self, item: &Item) -> ItemCategory {
+        self.item_kinds.category(&item.kind)
+    }
+
+    pub fn stockpile_at(&self, pos: WorldPos) -> Option<usize> {
+        self.stockpiles.iter().position(|stockpile| stockpile.contains(pos))
+    }
+
+    /// Whether the item lies in a stockpile that takes it.
+    pub fn is_stored(&self, item: &Item) -> bool {
+        self.stockpiles.iter().any(|stockpile| stockpile.holds(item, self.category(item)))
+    }
+
+    /// Whether `item` can be put down at `cell`: in a stockpile that takes it, with nothing there yet.
+    pub fn is_free_stockpile_cell(&self, cell: WorldPos, item: &Item) -> bool {
+        self.stockpiles.iter().any(|stockpile| stockpile.contains(cell) && stockpile.accepts(self.category(item)))
+            && !self.items.iter().any(|other| WorldPos::from_f32(other.x, other.y, other.z) == cell)
+    }
+
+    /// An empty cell for `item` in the most urgent stockpile on its level that takes it,
+    /// the nearest to `near` within that stockpile.
+    pub fn free_stockpile_cell(&self, item: &Item, near: WorldPos) -> Option<WorldPos> {
+        let occupied = self.occupied_cells();
+        let category = self.category(item);
+        self.stockpiles
+            .iter()
+            .filter(|stockpile| stockpile.min.z == near.z && stockpile.accepts(category))
+            .flat_map(|stockpile| {
+                stockpile
+                    .cells()
+                    .filter(|cell| !occupied.contains(cell))
+                    .map(move |cell| (stockpile.priority, cell))
+            })
+            .min_by_key(|&(priority, cell)| (std::cmp::Reverse(priority), cell.distance_sq(near)))
+            .map(|(_, cell)| cell)
+    }
+
+    fn occupied_cells(&self) -> HashSet<WorldPos> {
+        self.items.iter().map(|item| WorldPos::from_f32(item.x, item.y, item.z)).collect()
+    }
+
+    /// Every `HAUL_CHECK_INTERVAL` ticks, posts haul jobs for loose items a stockpile has
+    /// room for, and takes back those nobody has started on that are no longer needed.
+    pub fn update_hauling(&mut self) {
+        if !self.tick.is_multiple_of(HAUL_CHECK_INTERVAL) {
+            return;
+        }
+        let occupied = self.occupied_cells();
+        let mut room: Vec<usize> = self
+            .stockpiles
+            .iter()
+            .map(|stockpile| stockpile.cells().filter(|cell| !occupied.contains(cell)).count())
+            .collect();
+        let mut wanted = HashSet::new();
+        let mut post = Vec::new();
+        for item in &self.items {
+            if item.reserved_by.is_some() || self.is_stored(item) {
+                continue;
+            }
+            let best = self
+                .stockpiles
+                .iter()
+                .enumerate()
+                .filter(|&(i, stockpile)| stockpile.min.z == item.z && stockpile.accepts(self.category(item)) && room[i] > 0)
+                .max_by_key(|(_, stockpile)| stockpile.priority);
+            if let Some((i, stockpile)) = best {
+                room[i] -= 1;
+                wanted.insert(item.id);
+                post.push((item.id, WorldPos::from_f32(item.x, item.y, item.z), stockpile.priority));
+            }
+        }
+        for (item, pos, priority) in post {
+            self.jobs.post_haul(item, pos, priority);
+        }
+        let stale: Vec<ItemId> = self
+            .jobs
+            .iter()
+            .filter(|job| !matches!(job.state, JobState::Claimed(_)))
+            .filter_map(|job| job.item)
+            .filter(|item| !wanted.contains(item))
+            .collect();
+        for item in stale {
+            self.jobs.withdraw_haul(item);
+        }
+    }
+}
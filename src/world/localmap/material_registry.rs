@@ -0,0 +1,131 @@
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use macroquad::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Where the material definitions live
+pub const MATERIALS_PATH: &str = "assets/materials.json";
+
+/// How one material looks and behaves, as defined in the materials asset.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MaterialProps {
+    /// Display name
+    pub name: String,
+    /// RGB colours; the first is the base colour, any others are speckled in
+    pub colors: Vec<[f32; 3]>,
+    /// Blocks movement and can be dug out
+    pub solid: bool,
+    /// Creatures can stand on it
+    pub walkable: bool,
+    pub hardness: u32,
+    /// Frames a digger works on it before it gives way
+    pub dig_time: u32,
+    /// Item left behind when mined
+    pub drops: Option<String>,
+    /// Floor left behind when dug out
+    #[serde(default = "default_leaves")]
+    pub leaves: TerrainMaterial,
+    pub liquid: bool,
+    /// Falls or slides when nothing holds it up (sand, gravel, dirt)
+    #[serde(default)]
+    pub loose: bool,
+    /// Stone of the underground strata; ore veins form only in stone
+    #[serde(default)]
+    pub stone: bool,
+    /// Leads up to the same spot on the level above, as ramps do
+    #[serde(default)]
+    pub leads_up: bool,
+    /// Creatures can drink it
+    #[serde(default)]
+    pub drinkable: bool,
+    /// How readily it catches fire (0..1)
+    pub flammability: f32,
+}
+
+fn default_leaves() -> TerrainMaterial {
+    TerrainMaterial::Dirt
+}
+
+/// Properties for materials the asset doesn't define: a magenta wall, so the gap shows up
+static MISSING: MaterialProps = MaterialProps {
+    name: String::new(),
+    colors: Vec::new(),
+    solid: true,
+    walkable: false,
+    hardness: 0,
+    dig_time: 0,
+    drops: None,
+    leaves: TerrainMaterial::Dirt,
+    liquid: false,
+    loose: false,
+    stone: false,
+    leads_up: false,
+    drinkable: false,
+    flammability: 0.0,
+};
+
+/// Material properties by material, loaded from [`MATERIALS_PATH`].
+#[derive(Debug, Clone)]
+pub struct MaterialRegistry {
+    props: HashMap<TerrainMaterial, MaterialProps>,
+}
+
+impl MaterialRegistry {
+    /// Reads [`MATERIALS_PATH`]; the error names the file and what went wrong with it.
+    pub async fn load() -> Result<Self, String> {
+        let json_str = load_string(MATERIALS_PATH)
+            .await
+            .map_err(|e| format!("could not read {}: {}", MATERIALS_PATH, e))?;
+        Self::from_json(&json_str).map_err(|e| format!("invalid {}: {}", MATERIALS_PATH, e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let props = serde_json::from_str(json)?;
+        Ok(Self { props })
+    }
+
+    pub fn get(&self, material: TerrainMaterial) -> &MaterialProps {
+        self.props.get(&material).unwrap_or(&MISSING)
+    }
+
+    pub fn is_walkable(&self, material: TerrainMaterial) -> bool {
+        self.get(material).walkable
+    }
+
+    pub fn is_solid(&self, material: TerrainMaterial) -> bool {
+        self.get(material).solid
+    }
+
+    pub fn is_stone(&self, material: TerrainMaterial) -> bool {
+        self.get(material).stone
+    }
+
+    pub fn leads_up(&self, material: TerrainMaterial) -> bool {
+        self.get(material).leads_up
+    }
+
+    pub fn is_drinkable(&self, material: TerrainMaterial) -> bool {
+        self.get(material).drinkable
+    }
+
+    /// Whether every subpixel of this material is drawn in the same colour
+    pub fn is_plain(&self, material: TerrainMaterial) -> bool {
+        self.get(material).colors.len() <= 1
+    }
+
+    /// Colour of the material at a subpixel position; speckled materials pick a
+    /// colour by position so the pattern stays put.
+    pub fn color(&self, material: TerrainMaterial, x: i32, y: i32) -> Color {
+        let colors = &self.get(material).colors;
+        let index = match colors.len() {
+            0 => return MAGENTA,
+            1 => 0,
+            n => {
+                let h = (x as u32).wrapping_mul(0x9E37_79B1) ^ (y as u32).wrapping_mul(0x85EB_CA77);
+                ((h ^ (h >> 15)).wrapping_mul(0x2C1B_3C6D) >> 16) as usize % n
+            }
+        };
+        let [r, g, b] = colors[index];
+        Color::new(r, g, b, 1.0)
+    }
+}
@@ -3,4 +3,15 @@ pub mod tile;
 pub mod subpixel;
 pub mod zlevel;
 pub mod terrain_material;
+pub mod material_registry;
 pub mod world;
+pub mod position;
+pub mod query;
+pub mod change;
+pub mod designations;
+pub mod hauling;
+pub mod fluids;
+pub mod gravity;
+pub mod binary;
+pub mod chunk_codec;
+pub mod region_file;
@@ -0,0 +1,167 @@
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::tile::TILE_SIZE;
+
+/// Subpixels along one side of a chunk
+pub const CHUNK_SPAN: i32 = (CHUNK_SIZE * TILE_SIZE) as i32;
+
+/// A subpixel on the local map: the finest position there is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WorldPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// A tile on the local map, counted in tiles from the map origin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TilePos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// A chunk on the local map, counted in chunks from the map origin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl WorldPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Where a creature or item standing at `(x, y)` is.
+    pub fn from_f32(x: f32, y: f32, z: i32) -> Self {
+        Self::new(x.floor() as i32, y.floor() as i32, z)
+    }
+
+    pub fn tile(self) -> TilePos {
+        let size = TILE_SIZE as i32;
+        TilePos { x: self.x.div_euclid(size), y: self.y.div_euclid(size), z: self.z }
+    }
+
+    pub fn chunk(self) -> ChunkPos {
+        ChunkPos { x: self.x.div_euclid(CHUNK_SPAN), y: self.y.div_euclid(CHUNK_SPAN), z: self.z }
+    }
+
+    /// Position within its chunk, in subpixels.
+    pub fn in_chunk(self) -> (usize, usize) {
+        (self.x.rem_euclid(CHUNK_SPAN) as usize, self.y.rem_euclid(CHUNK_SPAN) as usize)
+    }
+
+    pub fn offset(self, dx: i32, dy: i32, dz: i32) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// Squared distance on the map plane, ignoring levels.
+    pub fn distance_sq(self, other: WorldPos) -> i32 {
+        let (dx, dy) = (self.x - other.x, self.y - other.y);
+        dx * dx + dy * dy
+    }
+}
+
+impl TilePos {
+    /// The tile's top-left subpixel.
+    pub fn origin(self) -> WorldPos {
+        let size = TILE_SIZE as i32;
+        WorldPos::new(self.x * size, self.y * size, self.z)
+    }
+
+    pub fn chunk(self) -> ChunkPos {
+        self.origin().chunk()
+    }
+
+    /// Index of the tile within its chunk's `tiles`.
+    pub fn in_chunk(self) -> (usize, usize) {
+        let (x, y) = self.origin().in_chunk();
+        (x / TILE_SIZE, y / TILE_SIZE)
+    }
+}
+
+impl ChunkPos {
+    /// The chunk's top-left subpixel.
+    pub fn origin(self) -> WorldPos {
+        WorldPos::new(self.x * CHUNK_SPAN, self.y * CHUNK_SPAN, self.z)
+    }
+
+    /// Key of the chunk in its level's `chunks`.
+    pub fn key(self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+}
+
+impl From<(i32, i32, i32)> for WorldPos {
+    fn from((x, y, z): (i32, i32, i32)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl From<WorldPos> for (i32, i32, i32) {
+    fn from(pos: WorldPos) -> Self {
+        (pos.x, pos.y, pos.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [(i32, i32, i32); 8] = [
+        (0, 0, 0),
+        (7, 8, 1),
+        (-1, -1, -1),
+        (-8, -9, -3),
+        (CHUNK_SPAN - 1, CHUNK_SPAN, 0),
+        (-CHUNK_SPAN, -CHUNK_SPAN - 1, 2),
+        (1000, -1000, -6),
+        (-12345, 6789, 0),
+    ];
+
+    #[test]
+    fn negative_positions_round_down() {
+        let pos = WorldPos::new(-1, -1, -2);
+        assert_eq!(pos.tile(), TilePos { x: -1, y: -1, z: -2 });
+        assert_eq!(pos.chunk(), ChunkPos { x: -1, y: -1, z: -2 });
+        assert_eq!(pos.in_chunk(), (CHUNK_SPAN as usize - 1, CHUNK_SPAN as usize - 1));
+        assert_eq!(WorldPos::from_f32(-0.5, 0.5, 0), WorldPos::new(-1, 0, 0));
+    }
+
+    #[test]
+    fn world_pos_round_trips_through_chunk() {
+        for (x, y, z) in SAMPLES {
+            let pos = WorldPos::new(x, y, z);
+            let (lx, ly) = pos.in_chunk();
+            assert!(lx < CHUNK_SPAN as usize && ly < CHUNK_SPAN as usize);
+            assert_eq!(pos.chunk().origin().offset(lx as i32, ly as i32, 0), pos);
+            assert_eq!(pos.chunk().origin().chunk(), pos.chunk());
+        }
+    }
+
+    #[test]
+    fn world_pos_round_trips_through_tile() {
+        let size = TILE_SIZE as i32;
+        for (x, y, z) in SAMPLES {
+            let pos = WorldPos::new(x, y, z);
+            let tile = pos.tile();
+            let origin = tile.origin();
+            assert!((0..size).contains(&(pos.x - origin.x)) && (0..size).contains(&(pos.y - origin.y)));
+            assert_eq!(origin.z, pos.z);
+            assert_eq!(origin.tile(), tile);
+            assert_eq!(tile.chunk(), pos.chunk());
+            let (lx, ly) = pos.in_chunk();
+            assert_eq!(tile.in_chunk(), (lx / TILE_SIZE, ly / TILE_SIZE));
+        }
+    }
+
+    #[test]
+    fn tuple_conversions_round_trip() {
+        for sample in SAMPLES {
+            let pos = WorldPos::from(sample);
+            assert_eq!(<(i32, i32, i32)>::from(pos), sample);
+            assert_eq!(ChunkPos { x: sample.0, y: sample.1, z: sample.2 }.key(), (sample.0, sample.1));
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::position::{ChunkPos, WorldPos, CHUNK_SPAN};
+use crate::world::localmap::subpixel::Subpixel;
+use crate::world::localmap::world::World;
+use std::collections::{HashSet, VecDeque};
+
+const SIDEWAYS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Spatial queries over the loaded part of the map. They only read chunk data, so they
+/// work the same with or without a window. Subpixels that aren't loaded are skipped.
+impl World {
+    pub fn chunk_at(&self, pos: ChunkPos) -> Option<&Chunk> {
+        self.z_level(pos.z)?.chunks.get(&pos.key())
+    }
+
+    pub fn subpixel_at(&self, pos: WorldPos) -> Option<Subpixel> {
+        self.get_subpixel(pos.x, pos.y, pos.z)
+    }
+
+    /// Every loaded subpixel in the box from `min` to `max` (inclusive, levels included),
+    /// visited chunk by chunk.
+    pub fn subpixels_in_rect(&self, min: WorldPos, max: WorldPos) -> impl Iterator<Item = (WorldPos, Subpixel)> + '_ {
+        let (lo, hi) = (min.chunk(), max.chunk());
+        (min.z..=max.z)
+            .filter_map(move |z| self.z_level(z))
+            .flat_map(move |level| {
+                (lo.x..=hi.x)
+                    .flat_map(move |cx| (lo.y..=hi.y).map(move |cy| ChunkPos { x: cx, y: cy, z: level.z }))
+                    .filter_map(move |pos| level.chunks.get(&pos.key()).map(|chunk| (pos.origin(), chunk)))
+            })
+            .flat_map(move |(origin, chunk)| {
+                let xs = min.x.max(origin.x)..=max.x.min(origin.x + CHUNK_SPAN - 1);
+                let ys = min.y.max(origin.y)..=max.y.min(origin.y + CHUNK_SPAN - 1);
+                xs.flat_map(move |x| {
+                    ys.clone().map(move |y| {
+                        let pos = WorldPos::new(x, y, origin.z);
+                        let (lx, ly) = pos.in_chunk();
+                        (pos, chunk.get(lx, ly))
+                    })
+                })
+            })
+    }
+
+    /// Every loaded subpixel within `radius` of `center` on its level.
+    pub fn subpixels_in_circle(&self, center: WorldPos, radius: i32) -> impl Iterator<Item = (WorldPos, Subpixel)> + '_ {
+        self.subpixels_in_rect(center.offset(-radius, -radius, 0), center.offset(radius, radius, 0))
+            .filter(move |(pos, _)| pos.distance_sq(center) <= radius * radius)
+    }
+
+    /// The closest subpixel within `radius` of `center` (on its level) that `matches`.
+    /// Searches outwards ring by ring and stops as soon as no farther ring can do better.
+    pub fn find_nearest(
+        &self,
+        center: WorldPos,
+        radius: i32,
+        mut matches: impl FnMut(WorldPos, Subpixel) -> bool,
+    ) -> Option<WorldPos> {
+        let mut best: Option<(i32, WorldPos)> = None;
+        for ring in 0..=radius {
+            if best.is_some_and(|(dist_sq, _)| ring * ring > dist_sq) {
+                break;
+            }
+            for pos in ring_positions(center, ring) {
+                let dist_sq = pos.distance_sq(center);
+                if dist_sq > radius * radius || best.is_some_and(|(best_sq, _)| dist_sq >= best_sq) {
+                    continue;
+                }
+                if let Some(subpixel) = self.subpixel_at(pos)
+                    && matches(pos, subpixel)
+                {
+                    best = Some((dist_sq, pos));
+                }
+            }
+        }
+        best.map(|(_, pos)| pos)
+    }
+
+    /// Walks a straight line from `from` to `to` (changing level evenly along the way if
+    /// they differ) and returns the first solid or unloaded subpixel after `from`.
+    pub fn raycast(&self, from: WorldPos, to: WorldPos) -> Option<WorldPos> {
+        let (dx, dy, dz) = (to.x - from.x, to.y - from.y, to.z - from.z);
+        let steps = dx.abs().max(dy.abs()).max(dz.abs());
+        let lerp = |start: i32, delta: i32, i: i32| start + (delta as f32 * i as f32 / steps as f32).round() as i32;
+        (1..=steps)
+            .map(|i| WorldPos::new(lerp(from.x, dx, i), lerp(from.y, dy, i), lerp(from.z, dz, i)))
+            .find(|&pos| {
+                self.subpixel_at(pos)
+                    .is_none_or(|subpixel| self.materials.is_solid(subpixel.material))
+            })
+    }
+
+    /// Whether `to` can be seen from `from`: nothing solid in between. `to` itself may
+    /// be solid, so a wall can be seen.
+    pub fn line_of_sight(&self, from: WorldPos, to: WorldPos) -> bool {
+        self.raycast(from, to).is_none_or(|hit| hit == to)
+    }
+
+    /// The region of the same material as `start` connected to it on its level, at most
+    /// `limit` subpixels (in the order they were reached).
+    pub fn flood_fill(&self, start: WorldPos, limit: usize) -> Vec<WorldPos> {
+        let Some(material) = self.subpixel_at(start).map(|subpixel| subpixel.material) else {
+            return Vec::new();
+        };
+        self.flood_fill_by(start, limit, |subpixel| subpixel.material == material)
+    }
+
+    /// Like [`World::flood_fill`], with any rule for which subpixels belong to the region.
+    pub fn flood_fill_by(&self, start: WorldPos, limit: usize, belongs: impl Fn(Subpixel) -> bool) -> Vec<WorldPos> {
+        let mut region = Vec::new();
+        if !self.subpixel_at(start).is_some_and(&belongs) {
+            return region;
+        }
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            if region.len() >= limit {
+                break;
+            }
+            region.push(pos);
+            for (dx, dy) in SIDEWAYS {
+                let next = pos.offset(dx, dy, 0);
+                if seen.insert(next) && self.subpixel_at(next).is_some_and(&belongs) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        region
+    }
+}
+
+/// Positions exactly `ring` steps (in the larger axis) from `center`.
+fn ring_positions(center: WorldPos, ring: i32) -> impl Iterator<Item = WorldPos> {
+    (-ring..=ring).flat_map(move |dx| {
+        let dys: Vec<i32> = if dx.abs() == ring { (-ring..=ring).collect() } else { vec![-ring, ring] };
+        dys.into_iter().map(move |dy| center.offset(dx, dy, 0))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::registry::{ItemRegistry, ITEMS_PATH};
+    use crate::world::localmap::change::ChangeCause;
+    use crate::world::localmap::material_registry::{MaterialRegistry, MATERIALS_PATH};
+    use crate::world::localmap::terrain_material::TerrainMaterial;
+    use std::sync::Arc;
+
+    /// Four open chunks meeting at the origin, on level 0.
+    fn world() -> World {
+        let materials = MaterialRegistry::from_json(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap();
+        let item_kinds = ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap();
+        let mut world = World::new(Arc::new(materials), Arc::new(item_kinds));
+        for cx in -1..=0 {
+            for cy in -1..=0 {
+                world.insert_chunk(0, (cx, cy), Chunk::new());
+            }
+        }
+        world
+    }
+
+    fn rock(world: &mut World, x: i32, y: i32) {
+        world.set_material_at(x, y, 0, TerrainMaterial::Rock, ChangeCause::Paint);
+    }
+
+    #[test]
+    fn rect_crosses_chunk_edges() {
+        let mut world = world();
+        rock(&mut world, -1, -1);
+        let found: Vec<_> = world.subpixels_in_rect(WorldPos::new(-2, -2, 0), WorldPos::new(1, 1, 0)).collect();
+        assert_eq!(found.len(), 16);
+        assert_eq!(found.iter().map(|(pos, _)| pos.chunk()).collect::<HashSet<_>>().len(), 4);
+        for (pos, subpixel) in found {
+            let expected = if (pos.x, pos.y) == (-1, -1) { TerrainMaterial::Rock } else { TerrainMaterial::Air };
+            assert_eq!(subpixel.material, expected, "at {:?}", pos);
+        }
+    }
+
+    #[test]
+    fn rect_skips_unloaded_chunks() {
+        let world = world();
+        let found: Vec<_> = world
+            .subpixels_in_rect(WorldPos::new(CHUNK_SPAN - 2, 0, 0), WorldPos::new(CHUNK_SPAN + 1, 0, 0))
+            .map(|(pos, _)| pos.x)
+            .collect();
+        assert_eq!(found, vec![CHUNK_SPAN - 2, CHUNK_SPAN - 1]);
+    }
+
+    #[test]
+    fn circle_crosses_chunk_edges() {
+        let world = world();
+        let center = WorldPos::new(0, 0, 0);
+        let found: HashSet<_> = world.subpixels_in_circle(center, 2).map(|(pos, _)| pos).collect();
+        assert_eq!(found.len(), 13);
+        assert!(found.iter().all(|pos| pos.distance_sq(center) <= 4));
+        assert!(found.contains(&WorldPos::new(-2, 0, 0)) && found.contains(&WorldPos::new(-1, -1, 0)));
+        assert!(!found.contains(&WorldPos::new(-2, -1, 0)));
+    }
+
+    #[test]
+    fn find_nearest_stops_after_the_closest_ring() {
+        let mut world = world();
+        rock(&mut world, 1, 0);
+        let mut looked_at = 0;
+        let found = world.find_nearest(WorldPos::new(0, 0, 0), 100, |_, subpixel| {
+            looked_at += 1;
+            subpixel.material == TerrainMaterial::Rock
+        });
+        assert_eq!(found, Some(WorldPos::new(1, 0, 0)));
+        assert!(looked_at <= 9, "looked at {} subpixels", looked_at);
+    }
+
+    #[test]
+    fn find_nearest_prefers_distance_over_ring() {
+        let mut world = world();
+        rock(&mut world, 3, 3);
+        rock(&mut world, -4, 0);
+        let found = world.find_nearest(WorldPos::new(0, 0, 0), 10, |_, subpixel| subpixel.material == TerrainMaterial::Rock);
+        assert_eq!(found, Some(WorldPos::new(-4, 0, 0)));
+        assert_eq!(world.find_nearest(WorldPos::new(0, 0, 0), 3, |_, subpixel| subpixel.material == TerrainMaterial::Rock), None);
+    }
+
+    #[test]
+    fn raycast_stops_at_solid_and_unloaded() {
+        let mut world = world();
+        for y in -5..=5 {
+            rock(&mut world, 5, y);
+        }
+        let origin = WorldPos::new(0, 0, 0);
+        assert_eq!(world.raycast(origin, WorldPos::new(10, 0, 0)), Some(WorldPos::new(5, 0, 0)));
+        assert_eq!(world.raycast(origin, WorldPos::new(10, 4, 0)), Some(WorldPos::new(5, 2, 0)));
+        assert_eq!(world.raycast(origin, WorldPos::new(-10, 0, 0)), None);
+        assert_eq!(world.raycast(origin, WorldPos::new(0, CHUNK_SPAN + 5, 0)), Some(WorldPos::new(0, CHUNK_SPAN, 0)));
+    }
+
+    #[test]
+    fn line_of_sight_through_open_and_solid() {
+        let mut world = world();
+        for y in -5..=5 {
+            rock(&mut world, 5, y);
+        }
+        let origin = WorldPos::new(0, 0, 0);
+        assert!(world.line_of_sight(origin, WorldPos::new(-20, 7, 0)));
+        assert!(world.line_of_sight(origin, WorldPos::new(5, 0, 0)), "the wall itself is visible");
+        assert!(!world.line_of_sight(origin, WorldPos::new(10, 0, 0)));
+        assert!(world.line_of_sight(origin, WorldPos::new(10, 20, 0)), "looking past the wall's end");
+    }
+
+    #[test]
+    fn flood_fill_stays_inside_its_walls() {
+        let mut world = world();
+        // Walls around the 5x5 square -2..=2, straddling all four chunks
+        for i in -3..=3 {
+            rock(&mut world, i, -3);
+            rock(&mut world, i, 3);
+            rock(&mut world, -3, i);
+            rock(&mut world, 3, i);
+        }
+        let region = world.flood_fill(WorldPos::new(0, 0, 0), 1000);
+        assert_eq!(region.len(), 25);
+        assert!(region.iter().all(|pos| pos.x.abs() <= 2 && pos.y.abs() <= 2 && pos.z == 0));
+        assert_eq!(region.iter().collect::<HashSet<_>>().len(), 25);
+
+        let walls = world.flood_fill(WorldPos::new(3, 0, 0), 1000);
+        assert_eq!(walls.len(), 24);
+        assert_eq!(world.flood_fill(WorldPos::new(0, 0, 0), 10).len(), 10);
+        assert!(world.flood_fill(WorldPos::new(CHUNK_SPAN, 0, 0), 10).is_empty());
+    }
+
+    #[test]
+    fn flood_fill_by_follows_its_rule() {
+        let mut world = world();
+        for i in -3..=3 {
+            rock(&mut world, i, 0);
+        }
+        let solid = |subpixel: Subpixel| subpixel.material == TerrainMaterial::Rock;
+        let region = world.flood_fill_by(WorldPos::new(-3, 0, 0), 100, solid);
+        assert_eq!(region.len(), 7);
+        assert!(world.flood_fill_by(WorldPos::new(0, 1, 0), 100, solid).is_empty());
+    }
+}
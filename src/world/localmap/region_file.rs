@@ -0,0 +1,215 @@
+//! Region files holding chunks that were changed and then unloaded, and the plants of
+//! every unloaded column
+use crate::plants::Plant;
+use crate::world::localmap::binary::{ByteReader, ByteWriter};
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::chunk_codec::{decode_chunk, decode_plants, encode_chunk, encode_plants};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Chunk columns along each side of one region file
+const REGION_CHUNKS: i32 = 4;
+
+/// Record tags: one chunk, or the plants of one column
+const CHUNK_RECORD: u8 = 0;
+const PLANTS_RECORD: u8 = 1;
+
+pub type ChunkKey = (i32, i32, i32);
+/// Plants of one column, with the priority of the chop job of those marked for felling
+pub type ColumnPlants = Vec<(Plant, u8)>;
+
+/// What unloaded columns left behind: their changed chunks, and their plants, which keep
+/// growing, being felled and being marked in ways regenerating them would forget
+#[derive(Default)]
+pub struct Stored {
+    pub chunks: Vec<(ChunkKey, Chunk)>,
+    pub plants: Vec<((i32, i32), ColumnPlants)>,
+}
+
+/// Records of one region file, still encoded
+#[derive(Default)]
+struct Region {
+    chunks: HashMap<ChunkKey, Vec<u8>>,
+    plants: HashMap<(i32, i32), Vec<u8>>,
+}
+
+fn corrupt(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("corrupt {}", what))
+}
+
+/// A directory of region files, each holding the stored chunks and plants of a square of
+/// chunk columns.
+///
+/// A region file is a list of tagged records: chunk x, y and z, then the chunk from
+/// [`encode_chunk`] as a length-prefixed block; or column x and y, then its plants from
+/// [`encode_plants`] as a block.
+#[derive(Clone)]
+pub struct RegionStore {
+    dir: PathBuf,
+}
+
+impl RegionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Deletes every stored chunk.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn path(&self, cx: i32, cy: i32) -> PathBuf {
+        let (rx, ry) = (cx.div_euclid(REGION_CHUNKS), cy.div_euclid(REGION_CHUNKS));
+        self.dir.join(format!("r.{}.{}.bin", rx, ry))
+    }
+
+    /// Records of a region file; empty if there is none.
+    fn read_region(path: &Path) -> io::Result<Region> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Region::default()),
+            Err(e) => return Err(e),
+        };
+        let mut input = ByteReader::new(&bytes);
+        let mut region = Region::default();
+        while !input.is_empty() {
+            let mut record = || {
+                match input.u8()? {
+                    CHUNK_RECORD => {
+                        let key = (input.i32()?, input.i32()?, input.i32()?);
+                        region.chunks.insert(key, input.block()?.to_vec());
+                    }
+                    PLANTS_RECORD => {
+                        let key = (input.i32()?, input.i32()?);
+                        region.plants.insert(key, input.block()?.to_vec());
+                    }
+                    _ => return None,
+                }
+                Some(())
+            };
+            record().ok_or_else(|| corrupt("region file"))?;
+        }
+        Ok(region)
+    }
+
+    /// The region holding column (cx, cy) among those read so far, reading it if needed.
+    fn region<'a>(&self, regions: &'a mut HashMap<PathBuf, Region>, cx: i32, cy: i32) -> io::Result<&'a mut Region> {
+        let path = self.path(cx, cy);
+        if !regions.contains_key(&path) {
+            regions.insert(path.clone(), Self::read_region(&path)?);
+        }
+        Ok(regions.get_mut(&path).unwrap())
+    }
+
+    /// Stores the given chunks and plants, replacing earlier copies of them.
+    pub fn save(&self, stored: &Stored) -> io::Result<()> {
+        let mut regions: HashMap<PathBuf, Region> = HashMap::new();
+        for &((cx, cy, z), ref chunk) in &stored.chunks {
+            self.region(&mut regions, cx, cy)?.chunks.insert((cx, cy, z), encode_chunk(chunk));
+        }
+        for &((cx, cy), ref plants) in &stored.plants {
+            self.region(&mut regions, cx, cy)?.plants.insert((cx, cy), encode_plants(plants));
+        }
+        fs::create_dir_all(&self.dir)?;
+        for (path, region) in regions {
+            let mut out = ByteWriter::default();
+            for ((cx, cy, z), data) in region.chunks {
+                out.u8(CHUNK_RECORD);
+                out.i32(cx);
+                out.i32(cy);
+                out.i32(z);
+                out.block(&data);
+            }
+            for ((cx, cy), data) in region.plants {
+                out.u8(PLANTS_RECORD);
+                out.i32(cx);
+                out.i32(cy);
+                out.block(&data);
+            }
+            fs::write(path, out.bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Stored chunks of column (cx, cy) by level, and its plants if it was unloaded before.
+    pub fn load_column(&self, cx: i32, cy: i32) -> io::Result<(HashMap<i32, Chunk>, Option<ColumnPlants>)> {
+        let mut region = Self::read_region(&self.path(cx, cy))?;
+        let mut column = HashMap::new();
+        for ((x, y, z), data) in region.chunks {
+            if (x, y) == (cx, cy) {
+                column.insert(z, decode_chunk(&data).ok_or_else(|| corrupt("chunk in region file"))?);
+            }
+        }
+        let plants = match region.plants.remove(&(cx, cy)) {
+            Some(data) => Some(decode_plants(&data).ok_or_else(|| corrupt("plants in region file"))?),
+            None => None,
+        };
+        Ok((column, plants))
+    }
+
+    /// Everything stored.
+    pub fn load_all(&self) -> io::Result<Stored> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Stored::default()),
+            Err(e) => return Err(e),
+        };
+        let mut stored = Stored::default();
+        for entry in entries {
+            let region = Self::read_region(&entry?.path())?;
+            for (key, data) in region.chunks {
+                stored.chunks.push((key, decode_chunk(&data).ok_or_else(|| corrupt("chunk in region file"))?));
+            }
+            for (key, data) in region.plants {
+                stored.plants.push((key, decode_plants(&data).ok_or_else(|| corrupt("plants in region file"))?));
+            }
+        }
+        Ok(stored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plants::Species;
+    use crate::world::localmap::terrain_material::TerrainMaterial;
+    use crate::world::localmap::subpixel::Subpixel;
+
+    #[test]
+    fn unloaded_columns_come_back_with_their_chunks_and_plants() {
+        let store = RegionStore::new(std::env::temp_dir().join(format!("region_test_{}", std::process::id())));
+        store.clear().unwrap();
+        let mut chunk = Chunk::new();
+        chunk.set(3, 4, Subpixel { material: TerrainMaterial::Rock, dig_target: false, level: 0 });
+        let mut felled = Plant::new(70, 80, 0, Species::Oak, 1.0);
+        felled.regrow_timer = 500;
+        let mut marked = Plant::new(90, 80, 0, Species::Pine, 0.5);
+        marked.chop = true;
+        store
+            .save(&Stored {
+                chunks: vec![((2, 3, -1), chunk)],
+                plants: vec![((2, 3), vec![(felled, 0), (marked, 6)]), ((3, 3), Vec::new())],
+            })
+            .unwrap();
+
+        let (chunks, plants) = store.load_column(2, 3).unwrap();
+        assert_eq!(chunks[&-1].get(3, 4).material, TerrainMaterial::Rock);
+        let plants = plants.unwrap();
+        assert_eq!(plants.len(), 2);
+        assert_eq!(plants[0].0.regrow_timer, 500);
+        assert!(plants[1].0.chop);
+        assert_eq!(plants[1].1, 6);
+        // A column left bare stays bare rather than being planted again
+        assert_eq!(store.load_column(3, 3).unwrap().1.map(|plants| plants.len()), Some(0));
+        assert!(store.load_column(2, 2).unwrap().1.is_none());
+
+        let all = store.load_all().unwrap();
+        assert_eq!((all.chunks.len(), all.plants.len()), (1, 2));
+        store.clear().unwrap();
+    }
+}
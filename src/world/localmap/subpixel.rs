@@ -1,9 +1,12 @@
 use crate::world::localmap::terrain_material::TerrainMaterial;
 
+/// One subpixel as the world accessors see it: its tile cell together with its flags.
 #[derive(Clone, Copy)]
 pub struct Subpixel {
     pub material: TerrainMaterial,
     pub dig_target: bool,
+    /// Amount of water held, up to `WATER_MAX`; only meaningful for water
+    pub level: u8,
 }
 
 impl Default for Subpixel {
@@ -11,6 +14,7 @@ impl Default for Subpixel {
         Self {
             material: TerrainMaterial::Air,
             dig_target: false,
+            level: 0,
         }
     }
 }
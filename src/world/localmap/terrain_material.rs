@@ -1,7 +1,80 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum TerrainMaterial {
     Air,
     Dirt,
     Rock,
     Water,
+    Sand,
+    /// Earth below the surface; dig it out to make floor
+    Soil,
+    /// Bedrock under the stone layers
+    DeepRock,
+    /// Dark, rich earth just under the surface
+    Topsoil,
+    /// Pockets of clay in the soil of wet ground
+    Clay,
+    /// Loose stones where the soil meets the rock
+    Gravel,
+    Granite,
+    Limestone,
+    Basalt,
+    Marble,
+    IronOre,
+    CopperOre,
+    GoldOre,
+    Coal,
+    Gems,
+    /// Molten rock in the deepest levels
+    Magma,
+    /// Built by workers; what it is made of is kept in `World::constructions`
+    Wall,
+    /// Built by workers
+    Floor,
+    /// Built by workers
+    Ramp,
+    /// Built by workers; creatures walk through, water doesn't
+    Door,
+    /// Built by workers over water
+    Bridge,
+    /// Built by workers; a place to sleep
+    Bed,
+}
+
+impl TerrainMaterial {
+    /// Every material, in declaration order, so `ALL[m as usize] == m`
+    pub const ALL: [TerrainMaterial; 26] = [
+        TerrainMaterial::Air,
+        TerrainMaterial::Dirt,
+        TerrainMaterial::Rock,
+        TerrainMaterial::Water,
+        TerrainMaterial::Sand,
+        TerrainMaterial::Soil,
+        TerrainMaterial::DeepRock,
+        TerrainMaterial::Topsoil,
+        TerrainMaterial::Clay,
+        TerrainMaterial::Gravel,
+        TerrainMaterial::Granite,
+        TerrainMaterial::Limestone,
+        TerrainMaterial::Basalt,
+        TerrainMaterial::Marble,
+        TerrainMaterial::IronOre,
+        TerrainMaterial::CopperOre,
+        TerrainMaterial::GoldOre,
+        TerrainMaterial::Coal,
+        TerrainMaterial::Gems,
+        TerrainMaterial::Magma,
+        TerrainMaterial::Wall,
+        TerrainMaterial::Floor,
+        TerrainMaterial::Ramp,
+        TerrainMaterial::Door,
+        TerrainMaterial::Bridge,
+        TerrainMaterial::Bed,
+    ];
+
+    /// Inverse of `material as u8`, for reading stored chunks
+    pub fn from_index(index: u8) -> Option<TerrainMaterial> {
+        Self::ALL.get(index as usize).copied()
+    }
 }
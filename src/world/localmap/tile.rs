@@ -1,18 +1,221 @@
-use crate::world::localmap::subpixel::Subpixel;
+use crate::world::localmap::terrain_material::TerrainMaterial;
 
 pub const TILE_SIZE: usize = 8;
+const CELLS: usize = TILE_SIZE * TILE_SIZE;
 
-#[derive(Clone, Copy)]
+/// What one subpixel of a tile is made of. Per-subpixel flags such as dig targets are kept
+/// in the chunk's side tables instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub material: TerrainMaterial,
+    /// Amount of water held, up to `WATER_MAX`; only meaningful for water
+    pub level: u8,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self { material: TerrainMaterial::Air, level: 0 }
+    }
+}
+
+/// Most tiles are a single material throughout, so they store one cell; the rest store a
+/// small palette and an index into it per subpixel.
+#[derive(Clone)]
+enum Cells {
+    Uniform(Cell),
+    Palette { palette: Vec<Cell>, indices: Box<[u8; CELLS]> },
+}
+
+#[derive(Clone)]
 pub struct Tile {
-    pub subgrid: [[Subpixel; TILE_SIZE]; TILE_SIZE],
+    cells: Cells,
     pub dirty: bool,
 }
 
 impl Default for Tile {
     fn default() -> Self {
-        Self {
-            subgrid: [[Subpixel::default(); TILE_SIZE]; TILE_SIZE],
-            dirty: false,
+        Self::filled(Cell::default())
+    }
+}
+
+impl Tile {
+    /// A tile made entirely of one cell
+    pub fn filled(cell: Cell) -> Self {
+        Self { cells: Cells::Uniform(cell), dirty: false }
+    }
+
+    pub fn get(&self, sx: usize, sy: usize) -> Cell {
+        match &self.cells {
+            Cells::Uniform(cell) => *cell,
+            Cells::Palette { palette, indices } => palette[indices[sx * TILE_SIZE + sy] as usize],
+        }
+    }
+
+    /// Changes one subpixel. The tile stays in palette form until [`Self::compact`] is called.
+    pub fn set(&mut self, sx: usize, sy: usize, cell: Cell) {
+        let index = sx * TILE_SIZE + sy;
+        match &mut self.cells {
+            Cells::Uniform(current) if *current == cell => {}
+            Cells::Uniform(current) => {
+                let mut indices = Box::new([0; CELLS]);
+                indices[index] = 1;
+                self.cells = Cells::Palette { palette: vec![*current, cell], indices };
+            }
+            Cells::Palette { palette, indices } => {
+                if let Some(entry) = palette.iter().position(|c| *c == cell) {
+                    indices[index] = entry as u8;
+                } else if palette.len() <= u8::MAX as usize {
+                    indices[index] = palette.len() as u8;
+                    palette.push(cell);
+                } else {
+                    // Full of entries nothing uses any more; compacting leaves at most one per subpixel
+                    self.compact();
+                    self.set(sx, sy, cell);
+                }
+            }
+        }
+    }
+
+    pub fn set_material(&mut self, sx: usize, sy: usize, material: TerrainMaterial) {
+        let cell = self.get(sx, sy);
+        self.set(sx, sy, Cell { material, ..cell });
+    }
+
+    /// The cell filling the whole tile, if it is a single one
+    pub fn uniform(&self) -> Option<Cell> {
+        match &self.cells {
+            Cells::Uniform(cell) => Some(*cell),
+            Cells::Palette { .. } => None,
+        }
+    }
+
+    /// Whether any subpixel of the tile is `cell`
+    pub fn contains(&self, cell: Cell) -> bool {
+        match &self.cells {
+            Cells::Uniform(current) => *current == cell,
+            Cells::Palette { palette, indices } => palette
+                .iter()
+                .enumerate()
+                .any(|(entry, c)| *c == cell && indices.contains(&(entry as u8))),
+        }
+    }
+
+    /// Applies `f` to every distinct cell of the tile
+    pub fn map(&mut self, f: impl Fn(Cell) -> Cell) {
+        match &mut self.cells {
+            Cells::Uniform(cell) => *cell = f(*cell),
+            Cells::Palette { palette, .. } => palette.iter_mut().for_each(|cell| *cell = f(*cell)),
         }
+        self.compact();
+    }
+
+    /// Number of subpixels of each distinct cell
+    pub fn cell_counts(&self) -> Vec<(Cell, usize)> {
+        match &self.cells {
+            Cells::Uniform(cell) => vec![(*cell, CELLS)],
+            Cells::Palette { palette, indices } => {
+                let mut counts = vec![0; palette.len()];
+                for &i in indices.iter() {
+                    counts[i as usize] += 1;
+                }
+                palette.iter().copied().zip(counts).filter(|(_, n)| *n > 0).collect()
+            }
+        }
+    }
+
+    /// Drops palette entries nothing uses (or that repeat another), and turns the tile back
+    /// into a single cell if only one is left.
+    pub fn compact(&mut self) {
+        let Cells::Palette { palette, indices } = &mut self.cells else { return; };
+        let mut kept: Vec<Cell> = Vec::with_capacity(palette.len());
+        let mut remap = vec![0u8; palette.len()];
+        let mut used = vec![false; palette.len()];
+        for &i in indices.iter() {
+            used[i as usize] = true;
+        }
+        for (entry, cell) in palette.iter().enumerate() {
+            if !used[entry] {
+                continue;
+            }
+            remap[entry] = match kept.iter().position(|c| c == cell) {
+                Some(existing) => existing as u8,
+                None => {
+                    kept.push(*cell);
+                    (kept.len() - 1) as u8
+                }
+            };
+        }
+        if kept.len() == 1 {
+            self.cells = Cells::Uniform(kept[0]);
+            return;
+        }
+        if kept.len() != palette.len() {
+            for i in indices.iter_mut() {
+                *i = remap[*i as usize];
+            }
+            *palette = kept;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(material: TerrainMaterial, level: u8) -> Cell {
+        Cell { material, level }
+    }
+
+    fn palette_len(tile: &Tile) -> Option<usize> {
+        match &tile.cells {
+            Cells::Uniform(_) => None,
+            Cells::Palette { palette, .. } => Some(palette.len()),
+        }
+    }
+
+    #[test]
+    fn a_uniform_tile_turns_into_a_palette_when_one_subpixel_changes() {
+        let mut tile = Tile::filled(cell(TerrainMaterial::Rock, 0));
+        tile.set(2, 3, cell(TerrainMaterial::Rock, 0));
+        assert_eq!(tile.uniform(), Some(cell(TerrainMaterial::Rock, 0)));
+
+        tile.set(2, 3, cell(TerrainMaterial::Water, 5));
+        assert_eq!(tile.uniform(), None);
+        assert_eq!(tile.get(2, 3), cell(TerrainMaterial::Water, 5));
+        assert_eq!(tile.get(3, 2), cell(TerrainMaterial::Rock, 0));
+        assert_eq!(palette_len(&tile), Some(2));
+    }
+
+    #[test]
+    fn compacting_folds_a_single_remaining_cell_back_into_a_uniform_tile() {
+        let mut tile = Tile::filled(cell(TerrainMaterial::Rock, 0));
+        tile.set(0, 0, cell(TerrainMaterial::Dirt, 0));
+        tile.set(1, 0, cell(TerrainMaterial::Sand, 0));
+        tile.set(0, 0, cell(TerrainMaterial::Rock, 0));
+        tile.compact();
+        // Dirt is no longer used and goes; rock and sand stay
+        assert_eq!(palette_len(&tile), Some(2));
+        assert_eq!(tile.get(1, 0), cell(TerrainMaterial::Sand, 0));
+
+        tile.set(1, 0, cell(TerrainMaterial::Rock, 0));
+        tile.compact();
+        assert_eq!(tile.uniform(), Some(cell(TerrainMaterial::Rock, 0)));
+    }
+
+    #[test]
+    fn a_full_palette_is_compacted_to_make_room() {
+        let mut tile = Tile::default();
+        // Air plus 255 water levels fill every palette index; only the last is still used
+        for level in 1..=u8::MAX {
+            tile.set(4, 4, cell(TerrainMaterial::Water, level));
+        }
+        assert_eq!(palette_len(&tile), Some(256));
+
+        tile.set(4, 4, cell(TerrainMaterial::Rock, 0));
+        // Air and the last water level survive compaction, then rock is added
+        assert_eq!(palette_len(&tile), Some(3));
+        assert_eq!(tile.get(4, 4), cell(TerrainMaterial::Rock, 0));
+        assert_eq!(tile.get(0, 0), Cell::default());
+        assert_eq!(tile.cell_counts(), vec![(Cell::default(), CELLS - 1), (cell(TerrainMaterial::Rock, 0), 1)]);
     }
 }
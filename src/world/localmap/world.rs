@@ -1,106 +1,193 @@
+use crate::construction::Construction;
+use crate::items::{Item, ItemId, ItemRegistry, Stockpile};
+use crate::jobs::JobBoard;
+use crate::plants::Plant;
+use crate::world::localmap::change::{BlockLedger, ChangeCause, WorldChange};
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::fluids::WATER_MAX;
+use crate::world::localmap::material_registry::MaterialRegistry;
+use crate::world::localmap::position::WorldPos;
 use crate::world::localmap::subpixel::Subpixel;
 use crate::world::localmap::terrain_material::TerrainMaterial;
 use crate::world::localmap::zlevel::ZLevel;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The surface level; underground levels have negative z.
+pub const SURFACE_Z: i32 = 0;
 
 pub struct World {
+    /// Levels ordered from the surface downwards
     pub z_levels: Vec<ZLevel>,
-    pub block_counts: HashMap<TerrainMaterial, usize>,
+    /// Loaded subpixels of each material
+    pub block_counts: BlockLedger,
+    /// How each material looks and behaves
+    pub materials: Arc<MaterialRegistry>,
+    /// Cells whose support changed and need a gravity check
+    pub gravity_checks: HashSet<(i32, i32, i32)>,
+    /// Unsupported stone ceilings and the frames left before they cave in
+    pub collapses: HashMap<(i32, i32, i32), u32>,
+    /// Trees, shrubs and grass growing on the map
+    pub plants: Vec<Plant>,
+    /// Items lying on the ground
+    pub items: Vec<Item>,
+    /// What each kind of item counts as and how it looks
+    pub item_kinds: Arc<ItemRegistry>,
+    /// Id the next item dropped gets
+    pub next_item_id: ItemId,
+    /// Zones items are hauled to
+    pub stockpiles: Vec<Stockpile>,
+    /// Frames simulated since the map was generated
+    pub tick: u64,
+    /// Work posted from designations, waiting for workers
+    pub jobs: JobBoard,
+    /// Constructions planned but not built yet, each with a build job
+    pub blueprints: HashMap<WorldPos, Construction>,
+    /// Finished constructions, so they can be told apart from natural terrain
+    pub constructions: HashMap<WorldPos, Construction>,
+    /// Material changes not yet picked up with `take_changes`
+    changes: Vec<WorldChange>,
 }
 
 impl World {
-    pub fn new() -> Self {
+    pub fn new(materials: Arc<MaterialRegistry>, item_kinds: Arc<ItemRegistry>) -> Self {
         Self {
-            z_levels: vec![ZLevel::new(0)],
-            block_counts: HashMap::new(),
+            z_levels: vec![ZLevel::new(SURFACE_Z)],
+            block_counts: BlockLedger::default(),
+            materials,
+            gravity_checks: HashSet::new(),
+            collapses: HashMap::new(),
+            plants: Vec::new(),
+            items: Vec::new(),
+            item_kinds,
+            next_item_id: 0,
+            stockpiles: Vec::new(),
+            tick: 0,
+            jobs: JobBoard::default(),
+            blueprints: HashMap::new(),
+            constructions: HashMap::new(),
+            changes: Vec::new(),
         }
     }
 
-    pub fn get_material_at(&self, x: i32, y: i32) -> TerrainMaterial {
-        if let Some(zlevel) = self.z_levels.get(0) {
-            let (chunk_x, chunk_y) = (x.div_euclid(32 * 8), y.div_euclid(32 * 8));
-            if let Some(chunk) = zlevel.chunks.get(&(chunk_x, chunk_y)) {
-                let local_x = x.rem_euclid(32 * 8);
-                let local_y = y.rem_euclid(32 * 8);
-
-                let tile_x = local_x.div_euclid(8);
-                let tile_y = local_y.div_euclid(8);
-
-                let sub_x = local_x.rem_euclid(8);
-                let sub_y = local_y.rem_euclid(8);
-
-                if let Some(tile) = chunk
-                    .tiles
-                    .get(tile_x as usize)
-                    .and_then(|row| row.get(tile_y as usize))
-                {
-                    let subpixel = &tile.subgrid[sub_x as usize][sub_y as usize];
-                    return subpixel.material;
-                }
-            }
-        }
-        TerrainMaterial::Air
+    pub fn z_level(&self, z: i32) -> Option<&ZLevel> {
+        self.z_levels.iter().find(|level| level.z == z)
     }
 
-    pub fn set_material_at(&mut self, x: i32, y: i32, material: TerrainMaterial) {
-        if let Some(zlevel) = self.z_levels.get_mut(0) {
-            let (chunk_x, chunk_y) = (x.div_euclid(32 * 8), y.div_euclid(32 * 8));
-            if let Some(chunk) = zlevel.chunks.get_mut(&(chunk_x, chunk_y)) {
-                let local_x = x.rem_euclid(32 * 8);
-                let local_y = y.rem_euclid(32 * 8);
-
-                let tile_x = local_x.div_euclid(8);
-                let tile_y = local_y.div_euclid(8);
-
-                let sub_x = local_x.rem_euclid(8);
-                let sub_y = local_y.rem_euclid(8);
-
-                if let Some(tile) = chunk
-                    .tiles
-                    .get_mut(tile_x as usize)
-                    .and_then(|row| row.get_mut(tile_y as usize))
-                {
-                    let subpixel = &mut tile.subgrid[sub_x as usize][sub_y as usize];
-                    let old_material = subpixel.material;
-                    if old_material != material {
-                        // Update block_counts
-                        *self.block_counts.entry(old_material).or_insert(1) -= 1;
-                        *self.block_counts.entry(material).or_insert(0) += 1;
-                    }
-                    subpixel.material = material;
-                    chunk.dirty = true; // Mark chunk dirty if you want later to re-render it
-                    tile.dirty = false;
-                }
+    pub fn z_level_mut(&mut self, z: i32) -> Option<&mut ZLevel> {
+        self.z_levels.iter_mut().find(|level| level.z == z)
+    }
+
+    /// Returns the level at `z`, creating it (and keeping the surface-down order) if missing.
+    pub fn z_level_or_insert(&mut self, z: i32) -> &mut ZLevel {
+        let index = match self.z_levels.iter().position(|level| level.z == z) {
+            Some(index) => index,
+            None => {
+                let index = self.z_levels.iter().position(|level| level.z < z).unwrap_or(self.z_levels.len());
+                self.z_levels.insert(index, ZLevel::new(z));
+                index
             }
+        };
+        &mut self.z_levels[index]
+    }
+
+    /// Highest and lowest existing levels.
+    pub fn z_range(&self) -> (i32, i32) {
+        let top = self.z_levels.iter().map(|level| level.z).max().unwrap_or(SURFACE_Z);
+        let bottom = self.z_levels.iter().map(|level| level.z).min().unwrap_or(SURFACE_Z);
+        (top, bottom)
+    }
+
+    pub fn get_material_at(&self, x: i32, y: i32, z: i32) -> TerrainMaterial {
+        self.get_subpixel(x, y, z)
+            .map(|subpixel| subpixel.material)
+            .unwrap_or(TerrainMaterial::Air)
+    }
+
+    /// Changes the material of one subpixel and announces it as a `WorldChange`, which is
+    /// also returned. This is the only way materials change once a chunk is loaded.
+    pub fn set_material_at(&mut self, x: i32, y: i32, z: i32, material: TerrainMaterial, cause: ChangeCause) -> Option<WorldChange> {
+        let pos = WorldPos::new(x, y, z);
+        let (local_x, local_y) = pos.in_chunk();
+        let chunk = self.z_level_mut(z)?.chunks.get_mut(&pos.chunk().key())?;
+        let mut subpixel = chunk.get(local_x, local_y);
+        let old = subpixel.material;
+        if old == material {
+            return None;
         }
+        subpixel.material = material;
+        subpixel.level = if material == TerrainMaterial::Water { WATER_MAX } else { 0 };
+        chunk.set(local_x, local_y, subpixel);
+        chunk.dirty = true;
+        chunk.modified = true;
+        let change = WorldChange { pos, old, new: material, cause };
+        self.notify(change);
+        Some(change)
     }
 
-    pub fn get_subpixel_mut(&mut self, x: i32, y: i32) -> Option<&mut Subpixel> {
-        if let Some(zlevel) = self.z_levels.get_mut(0) {
-            let chunk_x = x.div_euclid(32 * 8);
-            let chunk_y = y.div_euclid(32 * 8);
-            let local_x = x.rem_euclid(32 * 8);
-            let local_y = y.rem_euclid(32 * 8);
-
-            let tile_x = local_x.div_euclid(8);
-            let tile_y = local_y.div_euclid(8);
-            let sub_x = local_x.rem_euclid(8);
-            let sub_y = local_y.rem_euclid(8);
-
-            if let Some(chunk) = zlevel.chunks.get_mut(&(chunk_x, chunk_y)) {
-                if let Some(tile) = chunk
-                    .tiles
-                    .get_mut(tile_x as usize)
-                    .and_then(|row| row.get_mut(tile_y as usize))
-                {
-                    return Some(&mut tile.subgrid[sub_x as usize][sub_y as usize]);
-                }
+    /// Lets the world's own bookkeeping react to a change, then queues it for everyone else.
+    fn notify(&mut self, change: WorldChange) {
+        self.block_counts.apply(&change);
+        // Whatever was built here is gone
+        self.constructions.remove(&change.pos);
+        // Nearby water may now flow, and things may lose their support
+        let (x, y, z) = change.pos.into();
+        self.wake_fluids(x, y, z);
+        let opened = self.materials.is_solid(change.old) && !self.materials.is_solid(change.new);
+        self.wake_gravity(x, y, z, opened);
+        self.changes.push(change);
+    }
+
+    /// Material changes since the last call, oldest first.
+    pub fn take_changes(&mut self) -> Vec<WorldChange> {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Changes a subpixel's flags or water level through `f`; marks its tile and chunk
+    /// dirty. Returns what `f` returned, or `None` if the subpixel isn't loaded. The
+    /// material is left alone: that goes through `set_material_at`.
+    pub fn update_subpixel<R>(&mut self, x: i32, y: i32, z: i32, f: impl FnOnce(&mut Subpixel) -> R) -> Option<R> {
+        let pos = WorldPos::new(x, y, z);
+        let (local_x, local_y) = pos.in_chunk();
+        let chunk = self.z_level_mut(z)?.chunks.get_mut(&pos.chunk().key())?;
+        chunk.dirty = true;
+        chunk.modified = true;
+        let mut subpixel = chunk.get(local_x, local_y);
+        let material = subpixel.material;
+        let result = f(&mut subpixel);
+        debug_assert!(subpixel.material == material, "materials change through set_material_at");
+        subpixel.material = material;
+        chunk.set(local_x, local_y, subpixel);
+        Some(result)
+    }
+
+    pub fn get_subpixel(&self, x: i32, y: i32, z: i32) -> Option<Subpixel> {
+        let pos = WorldPos::new(x, y, z);
+        let (local_x, local_y) = pos.in_chunk();
+        Some(self.chunk_at(pos.chunk())?.get(local_x, local_y))
+    }
+
+    /// Adds a generated or reloaded chunk and lets water flow across its edges.
+    pub fn insert_chunk(&mut self, z: i32, (cx, cy): (i32, i32), mut chunk: Chunk) {
+        self.block_counts.add_chunk(&chunk);
+        chunk.dirty = true;
+        let level = self.z_level_or_insert(z);
+        level.chunks.insert((cx, cy), chunk);
+        for neighbour in [(cx - 1, cy), (cx + 1, cy), (cx, cy - 1), (cx, cy + 1)] {
+            if let Some(chunk) = level.chunks.get_mut(&neighbour) {
+                chunk.dirty = true;
             }
         }
-        None
     }
 
-    pub fn get_block_counts(&self) -> &HashMap<TerrainMaterial, usize> {
+    /// Takes a chunk out of the world, e.g. to unload it.
+    pub fn remove_chunk(&mut self, z: i32, pos: (i32, i32)) -> Option<Chunk> {
+        let chunk = self.z_level_mut(z)?.chunks.remove(&pos)?;
+        self.block_counts.remove_chunk(&chunk);
+        Some(chunk)
+    }
+
+    pub fn get_block_counts(&self) -> &BlockLedger {
         &self.block_counts
     }
 }
@@ -1,2 +1,3 @@
 pub mod localmap;
 pub mod worldmap;
+pub mod regionmap;
@@ -0,0 +1,4 @@
+//! Mid-scale region map generated around a single world map tile
+pub mod region_map;
+
+pub use region_map::RegionMap;
@@ -0,0 +1,50 @@
+use crate::world::worldmap::biome::BiomeId;
+use crate::world::worldmap::civilization::Civilization;
+
+/// Number of region cells along one side of a world map tile.
+pub const CELLS_PER_TILE: usize = 64;
+
+/// World tiles included on each side of the centre tile.
+pub const REGION_RADIUS: usize = 1;
+
+#[derive(Debug, Clone)]
+pub struct RegionMap {
+    /// World tile the region was generated for
+    pub center: (usize, usize),
+    /// World tile at the top-left corner of the region (cell 0,0)
+    pub origin: (usize, usize),
+    pub width: usize,
+    pub height: usize,
+    pub sea_level: f64,
+    pub elevation: Vec<Vec<f64>>,
+    pub biomes: Vec<Vec<BiomeId>>,
+    pub rivers: Vec<Vec<bool>>,
+    pub roads: Vec<Vec<bool>>,
+    pub civ: Vec<Vec<Option<Civilization>>>,
+    /// True where the owning civilization differs from a neighbouring cell
+    pub civ_border: Vec<Vec<bool>>,
+}
+
+impl RegionMap {
+    pub fn is_water(&self, x: usize, y: usize) -> bool {
+        self.elevation[x][y] <= self.sea_level || self.rivers[x][y]
+    }
+
+    /// Cell coordinates of the centre of a world tile, if it lies inside this region.
+    pub fn world_tile_to_cell(&self, tx: usize, ty: usize) -> Option<(f32, f32)> {
+        if tx < self.origin.0 || ty < self.origin.1 {
+            return None;
+        }
+        let cx = (tx - self.origin.0) * CELLS_PER_TILE + CELLS_PER_TILE / 2;
+        let cy = (ty - self.origin.1) * CELLS_PER_TILE + CELLS_PER_TILE / 2;
+        if cx >= self.width || cy >= self.height {
+            return None;
+        }
+        Some((cx as f32, cy as f32))
+    }
+
+    /// World tile containing the given region cell.
+    pub fn cell_to_world_tile(&self, x: usize, y: usize) -> (usize, usize) {
+        (self.origin.0 + x / CELLS_PER_TILE, self.origin.1 + y / CELLS_PER_TILE)
+    }
+}
@@ -1,5 +1,58 @@
 //! City struct for world generation
-use super::civilization::Civilization;
+use super::civilization::{Civilization, CivilizationInstance};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SettlementTier {
+    Hamlet,
+    Village,
+    Town,
+    City,
+    Capital,
+}
+
+impl SettlementTier {
+    /// Tier for a non-capital settlement of the given population.
+    pub fn from_population(population: u32) -> Self {
+        match population {
+            0..=1_999 => SettlementTier::Hamlet,
+            2_000..=9_999 => SettlementTier::Village,
+            10_000..=39_999 => SettlementTier::Town,
+            _ => SettlementTier::City,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Building {
+    /// Temple dedicated to the given religion
+    Temple(String),
+    GuildHall,
+    Market,
+    Barracks,
+    Library,
+    Shrine,
+    Shipyard,
+    Lighthouse,
+    Mill,
+    Keep,
+}
+
+impl Building {
+    pub fn name(&self) -> String {
+        match self {
+            Building::Temple(religion) => format!("Temple of {}", religion),
+            Building::GuildHall => "Guild Hall".to_string(),
+            Building::Market => "Market".to_string(),
+            Building::Barracks => "Barracks".to_string(),
+            Building::Library => "Library".to_string(),
+            Building::Shrine => "Shrine".to_string(),
+            Building::Shipyard => "Shipyard".to_string(),
+            Building::Lighthouse => "Lighthouse".to_string(),
+            Building::Mill => "Mill".to_string(),
+            Building::Keep => "Keep".to_string(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct City {
@@ -8,5 +61,15 @@ pub struct City {
     pub x: usize,
     pub y: usize,
     pub population: u32,
-    // Add more fields as needed (e.g., is_capital, trade_hub, etc.)
-} 
\ No newline at end of file
+    pub tier: SettlementTier,
+    /// Next to sea or ocean
+    pub is_port: bool,
+    /// On or next to a river
+    pub is_river_town: bool,
+    pub has_walls: bool,
+    pub is_fortress: bool,
+    pub founding_year: u32,
+    /// The civilization instance (and its culture) that governs this city
+    pub governor: CivilizationInstance,
+    pub buildings: Vec<Building>,
+}
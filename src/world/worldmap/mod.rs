@@ -3,6 +3,8 @@ pub mod biome;
 pub mod world_map;
 pub mod civilization;
 pub mod city;
+pub mod transport;
+pub mod site;
 
 pub use biome::BiomeId;
 pub use world_map::WorldMap;
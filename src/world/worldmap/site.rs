@@ -0,0 +1,66 @@
+//! Non-city points of interest (ruins, lairs, shrines, ...) for the world map
+use macroquad::prelude::*;
+use super::civilization::Civilization;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SiteType {
+    Ruins,
+    Lair,
+    Dungeon,
+    Shrine,
+    Mine,
+    Watchtower,
+}
+
+/// How the local map generator dresses the ground around this site (see `SiteStage`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LocalGenHint {
+    /// Broken stone walls and rubble on the surface
+    RuinedWalls,
+    /// A natural cave opening leading underground
+    CaveMouth,
+    /// Dug tunnels and rooms below the surface
+    UndergroundComplex,
+    /// A small cleared area with a stone structure at its centre
+    Clearing,
+    /// A vertical shaft into an ore body
+    MineShaft,
+    /// A single tall stone structure
+    Tower,
+}
+
+#[derive(Debug, Clone)]
+pub struct Site {
+    pub name: String,
+    pub kind: SiteType,
+    pub x: usize,
+    pub y: usize,
+    pub description: String,
+    pub local_hint: LocalGenHint,
+    /// Civilization that built or maintains the site, if any
+    pub civ: Option<Civilization>,
+}
+
+impl SiteType {
+    pub fn color(&self) -> Color {
+        match self {
+            SiteType::Ruins => LIGHTGRAY,
+            SiteType::Lair => MAROON,
+            SiteType::Dungeon => BLACK,
+            SiteType::Shrine => VIOLET,
+            SiteType::Mine => DARKBROWN,
+            SiteType::Watchtower => BEIGE,
+        }
+    }
+
+    pub fn local_hint(&self) -> LocalGenHint {
+        match self {
+            SiteType::Ruins => LocalGenHint::RuinedWalls,
+            SiteType::Lair => LocalGenHint::CaveMouth,
+            SiteType::Dungeon => LocalGenHint::UndergroundComplex,
+            SiteType::Shrine => LocalGenHint::Clearing,
+            SiteType::Mine => LocalGenHint::MineShaft,
+            SiteType::Watchtower => LocalGenHint::Tower,
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Persistent transport network (roads, rivers, sea lanes) for the world map
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    City,
+    Port,
+    Junction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    Road,
+    River,
+    SeaLane,
+}
+
+/// Which edge kinds a traveller is allowed to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TravelMode {
+    /// Roads only (armies, wagons)
+    Land,
+    /// Rivers and sea lanes only (ships)
+    Water,
+    /// Anything, switching between land and water at ports (caravans)
+    Any,
+}
+
+impl TravelMode {
+    pub fn allows(&self, kind: EdgeKind) -> bool {
+        match self {
+            TravelMode::Land => kind == EdgeKind::Road,
+            TravelMode::Water => kind != EdgeKind::Road,
+            TravelMode::Any => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransportNode {
+    pub pos: (usize, usize),
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransportEdge {
+    pub a: NodeId,
+    pub b: NodeId,
+    pub kind: EdgeKind,
+    pub cost: f64,
+    /// Tiles from `a` to `b`, both endpoints included
+    pub path: Vec<(usize, usize)>,
+}
+
+/// Result of a `shortest_route` query.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub path: Vec<(usize, usize)>,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransportNetwork {
+    pub nodes: Vec<TransportNode>,
+    pub edges: Vec<TransportEdge>,
+    /// Tiles where a road crosses a river
+    pub bridges: Vec<(usize, usize)>,
+    node_index: HashMap<(usize, usize), NodeId>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+#[derive(Copy, Clone)]
+struct QueueEntry {
+    node: NodeId,
+    cost: f64,
+}
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+impl Eq for QueueEntry {}
+
+impl TransportNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, pos: (usize, usize), kind: NodeKind) -> NodeId {
+        if let Some(&id) = self.node_index.get(&pos) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(TransportNode { pos, kind });
+        self.node_index.insert(pos, id);
+        self.adjacency.push(Vec::new());
+        id
+    }
+
+    pub fn add_edge(&mut self, a: NodeId, b: NodeId, kind: EdgeKind, cost: f64, path: Vec<(usize, usize)>) {
+        let idx = self.edges.len();
+        self.edges.push(TransportEdge { a, b, kind, cost, path });
+        self.adjacency[a].push(idx);
+        if a != b {
+            self.adjacency[b].push(idx);
+        }
+    }
+
+    pub fn node_at(&self, pos: (usize, usize)) -> Option<NodeId> {
+        self.node_index.get(&pos).copied()
+    }
+
+    pub fn ports(&self) -> impl Iterator<Item = &TransportNode> {
+        self.nodes.iter().filter(|n| n.kind == NodeKind::Port)
+    }
+
+    /// Cheapest route between the nodes at tiles `a` and `b` using only edges allowed by `mode`.
+    /// Returns `None` if either tile is not on the network or no route exists.
+    pub fn shortest_route(&self, a: (usize, usize), b: (usize, usize), mode: TravelMode) -> Option<Route> {
+        let start = self.node_at(a)?;
+        let goal = self.node_at(b)?;
+        if start == goal {
+            return Some(Route { path: vec![a], cost: 0.0 });
+        }
+
+        let mut dist = vec![f64::INFINITY; self.nodes.len()];
+        let mut came_from: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut open = BinaryHeap::new();
+        dist[start] = 0.0;
+        open.push(QueueEntry { node: start, cost: 0.0 });
+
+        while let Some(QueueEntry { node, cost }) = open.pop() {
+            if node == goal {
+                break;
+            }
+            if cost > dist[node] {
+                continue;
+            }
+            for &edge_idx in &self.adjacency[node] {
+                let edge = &self.edges[edge_idx];
+                if !mode.allows(edge.kind) {
+                    continue;
+                }
+                let next = if edge.a == node { edge.b } else { edge.a };
+                let tentative = cost + edge.cost;
+                if tentative < dist[next] {
+                    dist[next] = tentative;
+                    came_from[next] = Some(edge_idx);
+                    open.push(QueueEntry { node: next, cost: tentative });
+                }
+            }
+        }
+
+        if !dist[goal].is_finite() {
+            return None;
+        }
+
+        // Walk the edge chain back from the goal
+        let mut path = vec![b];
+        let mut current = goal;
+        while let Some(edge_idx) = came_from[current] {
+            let edge = &self.edges[edge_idx];
+            let prev = if edge.a == current { edge.b } else { edge.a };
+            if edge.b == current {
+                path.extend(edge.path.iter().rev().skip(1));
+            } else {
+                path.extend(edge.path.iter().skip(1));
+            }
+            current = prev;
+        }
+        path.reverse();
+        Some(Route { path, cost: dist[goal] })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two cities joined by a long road, and by a shorter river and sea lane through a port,
+    /// plus a city that is not connected to anything.
+    fn network() -> TransportNetwork {
+        let mut net = TransportNetwork::new();
+        let a = net.add_node((0, 0), NodeKind::City);
+        let b = net.add_node((4, 0), NodeKind::City);
+        let port = net.add_node((2, 2), NodeKind::Port);
+        net.add_node((9, 9), NodeKind::City);
+        net.add_edge(a, b, EdgeKind::Road, 8.0, vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]);
+        net.add_edge(a, port, EdgeKind::River, 2.0, vec![(0, 0), (1, 1), (2, 2)]);
+        net.add_edge(b, port, EdgeKind::SeaLane, 2.0, vec![(4, 0), (3, 1), (2, 2)]);
+        net
+    }
+
+    #[test]
+    fn route_to_same_node_is_free() {
+        let route = network().shortest_route((0, 0), (0, 0), TravelMode::Any).unwrap();
+        assert_eq!(route.path, vec![(0, 0)]);
+        assert_eq!(route.cost, 0.0);
+    }
+
+    #[test]
+    fn disconnected_pair_has_no_route() {
+        let net = network();
+        assert!(net.shortest_route((0, 0), (9, 9), TravelMode::Any).is_none());
+        assert!(net.shortest_route((0, 0), (5, 5), TravelMode::Any).is_none());
+    }
+
+    #[test]
+    fn any_mode_takes_the_cheapest_edges() {
+        let route = network().shortest_route((0, 0), (4, 0), TravelMode::Any).unwrap();
+        assert_eq!(route.cost, 4.0);
+        assert_eq!(route.path, vec![(0, 0), (1, 1), (2, 2), (3, 1), (4, 0)]);
+    }
+
+    #[test]
+    fn land_and_water_modes_filter_edges() {
+        let net = network();
+        let land = net.shortest_route((0, 0), (4, 0), TravelMode::Land).unwrap();
+        assert_eq!(land.cost, 8.0);
+        assert_eq!(land.path, vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]);
+
+        let water = net.shortest_route((4, 0), (0, 0), TravelMode::Water).unwrap();
+        assert_eq!(water.cost, 4.0);
+        assert_eq!(water.path, vec![(4, 0), (3, 1), (2, 2), (1, 1), (0, 0)]);
+
+        assert!(net.shortest_route((0, 0), (2, 2), TravelMode::Land).is_none());
+    }
+}
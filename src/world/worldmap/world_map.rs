@@ -1,6 +1,8 @@
 use crate::world::worldmap::biome::BiomeId;
 use crate::world::worldmap::civilization::{CivilizationInstance, CivilizationRelations, TradeRoute};
 use crate::world::worldmap::city::City;
+use crate::world::worldmap::transport::TransportNetwork;
+use crate::world::worldmap::site::Site;
 
 #[derive(Debug, Clone)]
 pub struct WorldMap {
@@ -25,18 +27,23 @@ pub struct WorldMap {
     pub civilization_map: Vec<Vec<Option<CivilizationInstance>>>,
     /// List of cities in the world
     pub cities: Vec<City>,
+    /// Ruins, lairs, shrines and other non-city points of interest
+    pub sites: Vec<Site>,
     /// Civilization relations (matrix)
     pub civ_relations: CivilizationRelations,
     /// Trade routes between cities/civilizations
     pub trade_routes: Vec<TradeRoute>,
+    /// Road, river and sea-lane graph linking cities, ports and junctions
+    pub transport: TransportNetwork,
     /// Sea level threshold for this world
     pub sea_level: f64,
     // You can add more fields later: elevation, rainfall, etc.
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ResourceType {
     Iron,
+    Copper,
     Gold,
     Coal,
     Gems,
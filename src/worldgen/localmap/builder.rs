@@ -1,36 +1,52 @@
 use super::generator::WorldGenerator;
 use super::pipeline::GenStage;
+use super::stages::caves::CaveStage;
 use super::stages::height::HeightStage;
 use super::stages::material::MaterialStage;
-use noise::OpenSimplex;
+use super::stages::ores::OreStage;
+use super::stages::roads::RoadStage;
+use super::stages::sites::SiteStage;
+use super::stages::strata::StrataStage;
+use super::stages::water::WaterStage;
+use super::tile_info::WorldTileInfo;
+use crate::world::localmap::material_registry::MaterialRegistry;
+use std::sync::Arc;
 
 pub struct WorldGeneratorBuilder {
     seed: u32,
     scale: f64,
+    tile: WorldTileInfo,
+    materials: Arc<MaterialRegistry>,
 }
 
 impl WorldGeneratorBuilder {
-    pub fn new(seed: u32) -> Self {
-        Self { seed, scale: 0.005 }
+    /// Stages look material properties (such as which rock is stone) up in `materials`
+    pub fn new(seed: u32, materials: Arc<MaterialRegistry>) -> Self {
+        Self { seed, scale: 0.005, tile: WorldTileInfo::default(), materials }
     }
 
-    pub fn with_scale(mut self, scale: f64) -> Self {
-        self.scale = scale;
-        self
-    }
-
-    pub fn with_height_noise(self, _noise: OpenSimplex) -> Self {
-        // placeholder – different noise types can be stored later
+    /// Generate the local map for this world map tile
+    pub fn with_world_tile(mut self, tile: WorldTileInfo) -> Self {
+        self.tile = tile;
         self
     }
 
     pub fn build(self) -> WorldGenerator {
+        // Every stage samples noise in global coordinates with the world seed,
+        // so local maps of neighbouring world tiles meet without seams
+        let seed = self.seed;
         // assemble stage list
         let stages: Vec<Box<dyn GenStage>> = vec![
-            Box::new(HeightStage::new(self.seed, self.scale)),
-            Box::new(MaterialStage::new(self.seed, self.scale, 0.7)),
+            Box::new(HeightStage::new(seed, self.scale)),
+            Box::new(MaterialStage::new(seed, self.scale, 0.7)),
+            Box::new(WaterStage::new(seed, self.scale)),
+            Box::new(RoadStage::new(seed)),
+            Box::new(StrataStage::new(seed, self.scale)),
+            Box::new(OreStage::new(seed)),
+            Box::new(CaveStage::new(seed)),
+            Box::new(SiteStage::new(seed)),
             // later: more stages appended here
         ];
-        WorldGenerator::from_stages(stages)
+        WorldGenerator::from_stages(stages, self.tile, self.materials)
     }
 }
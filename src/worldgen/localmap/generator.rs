@@ -1,74 +1,88 @@
+use crate::world::localmap::change::BlockLedger;
 use crate::world::localmap::chunk::Chunk;
 use crate::world::localmap::chunk::CHUNK_SIZE;
-use crate::world::localmap::tile::TILE_SIZE;
-use crate::world::localmap::zlevel::ZLevel;
-use crate::world::worldmap::biome::BiomeId;
+use crate::world::localmap::fluids::WATER_MAX;
+use crate::world::localmap::material_registry::MaterialRegistry;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::tile::{Cell, TILE_SIZE};
+use crate::world::localmap::world::SURFACE_Z;
 use crate::worldgen::localmap::pipeline::{GenCtx, GenStage};
 use crate::worldgen::localmap::stages::height::HeightStage;
-use std::collections::HashMap;
+use crate::worldgen::localmap::tile_info::WorldTileInfo;
+use std::sync::Arc;
+
+/// Number of levels generated below the surface
+pub const UNDERGROUND_LEVELS: i32 = 6;
 
 pub struct WorldGenerator {
     stages: Vec<Box<dyn GenStage>>,
+    tile: WorldTileInfo,
+    materials: Arc<MaterialRegistry>,
 }
 
 impl WorldGenerator {
-    pub fn new(seed: u32) -> Self {
+    pub fn new(seed: u32, materials: Arc<MaterialRegistry>) -> Self {
         Self {
             stages: vec![Box::new(HeightStage::new(seed, 0.005))],
+            tile: WorldTileInfo::default(),
+            materials,
         }
     }
 
-    pub fn generate_chunk(&self, chunk_x: i32, chunk_y: i32) -> Chunk {
+    pub fn generate_chunk(&self, chunk_x: i32, chunk_y: i32, z: i32) -> Chunk {
         let mut chunk = Chunk::new();
+        let world_x0 = chunk_x * CHUNK_SIZE as i32 * TILE_SIZE as i32;
+        let world_y0 = chunk_y * CHUNK_SIZE as i32 * TILE_SIZE as i32;
+        let mut biome = [[self.tile.biome; CHUNK_SIZE]; CHUNK_SIZE];
+        for (tx, column) in biome.iter_mut().enumerate() {
+            for (ty, b) in column.iter_mut().enumerate() {
+                *b = self.tile.biome_at(world_x0 + (tx * TILE_SIZE) as i32, world_y0 + (ty * TILE_SIZE) as i32);
+            }
+        }
         let mut ctx = GenCtx {
             chunk: &mut chunk,
-            world_x0: chunk_x * CHUNK_SIZE as i32 * TILE_SIZE as i32,
-            world_y0: chunk_y * CHUNK_SIZE as i32 * TILE_SIZE as i32,
+            world_x0,
+            world_y0,
+            z,
             height: [[0.0; CHUNK_SIZE]; CHUNK_SIZE],
-            biome: [[BiomeId::Plains; CHUNK_SIZE]; CHUNK_SIZE],
+            biome,
+            tile: &self.tile,
+            materials: &self.materials,
         };
         for stage in &self.stages {
             stage.execute(&mut ctx);
         }
+        // Generated water starts out full; this also shrinks every tile back to the
+        // fewest cells it needs
+        for tile in chunk.tiles.iter_mut().flatten() {
+            tile.map(|cell| match cell.material {
+                TerrainMaterial::Water => Cell { level: WATER_MAX, ..cell },
+                _ => cell,
+            });
+        }
         chunk
     }
 
-    pub fn from_stages(stages: Vec<Box<dyn GenStage>>) -> Self {
-        Self { stages }
+    pub fn from_stages(stages: Vec<Box<dyn GenStage>>, tile: WorldTileInfo, materials: Arc<MaterialRegistry>) -> Self {
+        Self { stages, tile, materials }
     }
 }
 
 impl WorldGenerator {
-    /// Fills the given world with chunks for every (cx, cy) in `area`.
+    /// Fills the given world with chunks for every (cx, cy) in `area`, from the surface
+    /// down through all underground levels.
     pub fn generate_into_world(&self, world: &mut crate::world::localmap::world::World, area: &[(i32, i32)]) {
-        // make sure z-level 0 has a chunk map
-        if world.z_levels.is_empty() {
-            world.z_levels.push(ZLevel {
-                z: 0,
-                chunks: HashMap::new(),
-            });
-        }
-        let z0 = &mut world.z_levels[0];
-
-        for &(cx, cy) in area {
-            // skip if chunk already exists
-            z0.chunks
-                .entry((cx, cy))
-                .or_insert_with(|| self.generate_chunk(cx, cy));
-        }
-        // After generation, count all block types and store in world.block_counts
-        let mut counts = std::collections::HashMap::new();
-        for chunk in z0.chunks.values() {
-            for tile_row in &chunk.tiles {
-                for tile in tile_row {
-                    for subgrid_row in &tile.subgrid {
-                        for sub in subgrid_row {
-                            *counts.entry(sub.material).or_insert(0) += 1;
-                        }
-                    }
-                }
+        for z in (SURFACE_Z - UNDERGROUND_LEVELS..=SURFACE_Z).rev() {
+            let level = world.z_level_or_insert(z);
+            for &(cx, cy) in area {
+                // skip if chunk already exists
+                level
+                    .chunks
+                    .entry((cx, cy))
+                    .or_insert_with(|| self.generate_chunk(cx, cy, z));
             }
         }
-        world.block_counts = counts;
+        // After generation, count all block types and store in world.block_counts
+        world.block_counts = BlockLedger::count(&world.z_levels);
     }
 }
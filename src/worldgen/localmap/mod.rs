@@ -2,4 +2,8 @@
 pub mod builder;
 pub mod generator;
 pub mod pipeline;
+pub mod segments;
 pub mod stages;
+pub mod streaming;
+pub mod tile_info;
+pub mod vegetation;
@@ -1,13 +1,20 @@
 use crate::world::localmap::chunk::Chunk;
 use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::material_registry::MaterialRegistry;
 use crate::world::worldmap::biome::BiomeId;
+use crate::worldgen::localmap::tile_info::WorldTileInfo;
 
 pub struct GenCtx<'a> {
     pub chunk: &'a mut Chunk,
     pub world_x0: i32,
     pub world_y0: i32,
+    /// Level being generated; 0 is the surface, negative is underground
+    pub z: i32,
     pub height: [[f32; CHUNK_SIZE]; CHUNK_SIZE],
     pub biome: [[BiomeId; CHUNK_SIZE]; CHUNK_SIZE],
+    /// World map tile this local map sits on
+    pub tile: &'a WorldTileInfo,
+    pub materials: &'a MaterialRegistry,
 }
 
 pub trait GenStage: Send + Sync {
@@ -0,0 +1,72 @@
+//! Meandering line segments (rivers, roads) laid across local maps
+use crate::worldgen::worldmap::utils::noise::fractal_noise;
+use noise::Perlin;
+
+/// Maximum sideways wander (in subpixels) halfway along a segment.
+const MEANDER: f64 = 90.0;
+
+/// A straight link between two world tile centres in local subpixels, bent by noise.
+/// The bend is pinned to zero at both ends so consecutive segments join up.
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    pub a: (f64, f64),
+    pub b: (f64, f64),
+    /// World-space seed for the bend, identical for every embark that sees this segment
+    seed: (f64, f64),
+}
+
+impl Segment {
+    pub fn new(a: (f64, f64), b: (f64, f64), seed: (f64, f64)) -> Self {
+        Self { a, b, seed }
+    }
+
+    /// Distance (in subpixels) from a point to the bent centre line.
+    pub fn distance(&self, perlin: &Perlin, px: f64, py: f64) -> f64 {
+        let (dx, dy) = (self.b.0 - self.a.0, self.b.1 - self.a.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return ((px - self.a.0).powi(2) + (py - self.a.1).powi(2)).sqrt();
+        }
+        let (rx, ry) = (px - self.a.0, py - self.a.1);
+        let t = (rx * dx + ry * dy) / (len * len);
+        if !(0.0..=1.0).contains(&t) {
+            let end = if t < 0.0 { self.a } else { self.b };
+            return ((px - end.0).powi(2) + (py - end.1).powi(2)).sqrt();
+        }
+        let across = (rx * -dy + ry * dx) / len;
+        let bend = fractal_noise(perlin, self.seed.0 * 3.1 + t * 2.0, self.seed.1 * 3.1, 3, 0.5)
+            * MEANDER
+            * (std::f64::consts::PI * t).sin();
+        (across - bend).abs()
+    }
+
+    /// Point on the bent centre line at `t` (0 at `a`, 1 at `b`).
+    pub fn point_at(&self, perlin: &Perlin, t: f64) -> (f64, f64) {
+        let (dx, dy) = (self.b.0 - self.a.0, self.b.1 - self.a.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return self.a;
+        }
+        let bend = fractal_noise(perlin, self.seed.0 * 3.1 + t * 2.0, self.seed.1 * 3.1, 3, 0.5)
+            * MEANDER
+            * (std::f64::consts::PI * t).sin();
+        (
+            self.a.0 + dx * t - dy / len * bend,
+            self.a.1 + dy * t + dx / len * bend,
+        )
+    }
+
+    /// Straight-line length between the end points.
+    pub fn length(&self) -> f64 {
+        ((self.b.0 - self.a.0).powi(2) + (self.b.1 - self.a.1).powi(2)).sqrt()
+    }
+
+    /// True if the segment's bounding box, widened by `margin`, overlaps the given rectangle.
+    pub fn near_rect(&self, x0: f64, y0: f64, x1: f64, y1: f64, margin: f64) -> bool {
+        let m = margin + MEANDER;
+        self.a.0.min(self.b.0) - m <= x1
+            && self.a.0.max(self.b.0) + m >= x0
+            && self.a.1.min(self.b.1) - m <= y1
+            && self.a.1.max(self.b.1) + m >= y0
+    }
+}
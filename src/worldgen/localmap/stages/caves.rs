@@ -0,0 +1,305 @@
+use crate::worldgen::localmap::generator::UNDERGROUND_LEVELS;
+use crate::worldgen::localmap::pipeline::{GenCtx, GenStage};
+use crate::worldgen::localmap::segments::Segment;
+use crate::worldgen::localmap::tile_info::{WorldTileInfo, WORLD_TILE_SUBPIXELS};
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::tile::TILE_SIZE;
+use noise::{NoiseFn, Perlin};
+
+/// Size (in global subpixels) of the grid cells that each hold one cavern
+const CELL: f64 = 192.0;
+/// Grid cells along each side of a world tile
+const TILE_CELLS: i64 = WORLD_TILE_SUBPIXELS as i64 / CELL as i64;
+/// Chance of a tunnel off the backbone between two neighbouring caverns, at cave factor 1
+const EXTRA_TUNNELS: f64 = 0.35;
+/// Base radius of a cavern in subpixels
+const CAVERN_RADIUS: f64 = 24.0;
+/// Base half-width of a tunnel in subpixels
+const TUNNEL_HALF_WIDTH: f64 = 3.0;
+/// How far (in levels) a tunnel's carving reaches above and below its centre, so
+/// tunnels changing level open a shaft on both
+const TUNNEL_Z_REACH: f64 = 0.6;
+/// Caverns this many levels down or deeper may hold a lake
+const LAKE_DEPTH: i32 = 3;
+/// How far past a chunk to look for caverns and tunnels that reach into it
+const SEARCH_MARGIN: f64 = CELL + 120.0;
+
+/// One cavern, placed at a jittered point in its grid cell on a random level.
+struct Cavern {
+    pos: (f64, f64),
+    z: i32,
+    radius: f64,
+    lake: bool,
+    /// Blended biome cave factor
+    density: f64,
+}
+
+/// What a subpixel of the chunk gets carved into
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Carve {
+    None,
+    Open,
+    Lake,
+    /// Where a tunnel climbs to the level above
+    Ramp,
+}
+
+/// A tunnel between two neighbouring caverns, possibly changing level on the way.
+struct Tunnel {
+    segment: Segment,
+    z: (i32, i32),
+    half_width: f64,
+}
+
+/// Carves caverns joined by meandering tunnels into the underground levels.
+///
+/// Every grid cell holds a cavern. A backbone of tunnels (see [`CaveStage::in_backbone`])
+/// connects them all, so the caves form one network; further tunnels to the east and
+/// south neighbours are added at random, more of them where density is high. Tunnels
+/// changing level climb ramps. Caverns and tunnels are derived from global positions
+/// only, so they continue across chunks and neighbouring embarks.
+pub struct CaveStage {
+    perlin: Perlin,
+    seed: u32,
+}
+
+/// Deterministic value in `0.0..1.0` for a grid cell.
+pub(crate) fn hash01(seed: u32, cx: i64, cy: i64, salt: u64) -> f64 {
+    let mut h = (seed as u64)
+        ^ (cx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (cy as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ salt.wrapping_mul(0x1656_67B1_9E37_79F9);
+    // splitmix64 finaliser
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl CaveStage {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            perlin: Perlin::new(seed.wrapping_add(4)),
+            seed,
+        }
+    }
+
+    fn cavern(&self, tile: &WorldTileInfo, cx: i64, cy: i64) -> Cavern {
+        let h = |salt| hash01(self.seed, cx, cy, salt);
+        let pos = (
+            (cx as f64 + 0.2 + 0.6 * h(0)) * CELL,
+            (cy as f64 + 0.2 + 0.6 * h(1)) * CELL,
+        );
+        let z = -1 - (h(2) * UNDERGROUND_LEVELS as f64) as i32;
+        let (lx, ly) = tile.local(pos.0, pos.1);
+        let density = tile.blend(lx, ly).profile.caves as f64;
+        Cavern {
+            pos,
+            z,
+            radius: CAVERN_RADIUS * (0.5 + h(3)) * density,
+            lake: z <= -LAKE_DEPTH && h(4) < 0.4,
+            density,
+        }
+    }
+
+    /// Whether the tunnel from cell (cx, cy) to its east (`dir` 0) or south (`dir` 1)
+    /// neighbour is part of the backbone keeping the caves connected. The caverns under each
+    /// world tile are joined by a spanning tree, a binary-tree maze in which every cavern but
+    /// the north-west one links to its west or north neighbour; neighbouring tiles are
+    /// joined by one tunnel across their shared edge. So the caverns under any explorable
+    /// area are connected, whichever tile it is around.
+    fn in_backbone(&self, (cx, cy): (i64, i64), dir: u64) -> bool {
+        let (nx, ny) = if dir == 0 { (cx + 1, cy) } else { (cx, cy + 1) };
+        let tile = |c: i64| c.div_euclid(TILE_CELLS);
+        let (tx, ty) = (tile(cx), tile(cy));
+        if (tx, ty) != (tile(nx), tile(ny)) {
+            // One crossing per tile edge, at a cell picked for that edge
+            let along = if dir == 0 { cy } else { cx };
+            let crossing = (hash01(self.seed, tx, ty, 8 + dir) * TILE_CELLS as f64) as i64;
+            return along.rem_euclid(TILE_CELLS) == crossing;
+        }
+        // Inside the tile, the tunnel is on the backbone if it is the neighbour's one link
+        // towards the tile's north-west corner
+        let links_west = match (nx.rem_euclid(TILE_CELLS), ny.rem_euclid(TILE_CELLS)) {
+            (_, 0) => true,
+            (0, _) => false,
+            _ => hash01(self.seed, nx, ny, 7) < 0.5,
+        };
+        links_west == (dir == 0)
+    }
+
+    /// Tunnel from cavern `a` at cell `(cx, cy)` to its neighbour `b` in direction `dir`, if it
+    /// is on the backbone or the dice allow an extra one.
+    fn tunnel(&self, a: &Cavern, b: &Cavern, (cx, cy): (i64, i64), dir: u64) -> Option<Tunnel> {
+        let density = (a.density + b.density) * 0.5;
+        if !self.in_backbone((cx, cy), dir) && hash01(self.seed, cx, cy, 5 + dir) >= EXTRA_TUNNELS * density {
+            return None;
+        }
+        Some(Tunnel {
+            segment: Segment::new(a.pos, b.pos, (cx as f64 * 0.37 + dir as f64, cy as f64 * 0.37)),
+            z: (a.z, b.z),
+            half_width: TUNNEL_HALF_WIDTH * (0.7 + 0.4 * density).min(1.6),
+        })
+    }
+}
+
+impl GenStage for CaveStage {
+    fn execute(&self, ctx: &mut GenCtx) {
+        if ctx.z >= 0 {
+            return;
+        }
+        let z = ctx.z;
+        let span = (CHUNK_SIZE * TILE_SIZE) as f64;
+        let (gx0, gy0) = ctx.tile.global(ctx.world_x0, ctx.world_y0);
+        let cell_range = |g0: f64| {
+            ((g0 - SEARCH_MARGIN) / CELL).floor() as i64..=((g0 + span + SEARCH_MARGIN) / CELL).floor() as i64
+        };
+
+        let mut caverns = Vec::new();
+        let mut tunnels = Vec::new();
+        for cx in cell_range(gx0) {
+            for cy in cell_range(gy0) {
+                let here = self.cavern(ctx.tile, cx, cy);
+                for (dir, (nx, ny)) in [(0, (cx + 1, cy)), (1, (cx, cy + 1))] {
+                    let there = self.cavern(ctx.tile, nx, ny);
+                    let Some(tunnel) = self.tunnel(&here, &there, (cx, cy), dir) else { continue; };
+                    let (lo, hi) = (tunnel.z.0.min(tunnel.z.1), tunnel.z.0.max(tunnel.z.1));
+                    if z >= lo - 1 && z <= hi + 1
+                        && tunnel.segment.near_rect(gx0, gy0, gx0 + span, gy0 + span, tunnel.half_width)
+                    {
+                        tunnels.push(tunnel);
+                    }
+                }
+                if here.z == z {
+                    caverns.push(here);
+                }
+            }
+        }
+        if caverns.is_empty() && tunnels.is_empty() {
+            return;
+        }
+
+        // Rasterise into a chunk-sized mask first, then apply it
+        let size = CHUNK_SIZE * TILE_SIZE;
+        let mut mask = vec![Carve::None; size * size];
+
+        // Caverns: blobs with a noisy outline
+        for cavern in &caverns {
+            let reach = cavern.radius * 1.5;
+            let x_lo = ((cavern.pos.0 - reach - gx0).floor().max(0.0)) as usize;
+            let x_hi = ((cavern.pos.0 + reach - gx0).ceil().min(span - 1.0)).max(0.0) as usize;
+            let y_lo = ((cavern.pos.1 - reach - gy0).floor().max(0.0)) as usize;
+            let y_hi = ((cavern.pos.1 + reach - gy0).ceil().min(span - 1.0)).max(0.0) as usize;
+            for lx in x_lo..=x_hi {
+                for ly in y_lo..=y_hi {
+                    let (gx, gy) = (gx0 + lx as f64, gy0 + ly as f64);
+                    let d = ((gx - cavern.pos.0).powi(2) + (gy - cavern.pos.1).powi(2)).sqrt();
+                    let wobble = 1.0 + 0.4 * self.perlin.get([gx * 0.04, gy * 0.04, z as f64 * 1.7]);
+                    if d < cavern.radius * wobble {
+                        let lake = cavern.lake && self.perlin.get([gx * 0.03, gy * 0.03, 50.0]) < 0.15;
+                        let cell = &mut mask[lx * size + ly];
+                        *cell = if lake || *cell == Carve::Lake { Carve::Lake } else { Carve::Open };
+                    }
+                }
+            }
+        }
+
+        // Tunnels: walk the centre line, carving only where its depth is near this level.
+        // Where the level above is carved too, the floor here is a ramp up to it
+        for tunnel in &tunnels {
+            let steps = tunnel.segment.length().ceil().max(1.0) as usize;
+            let r = tunnel.half_width.ceil() as i64;
+            for i in 0..=steps {
+                let t = i as f64 / steps as f64;
+                let tunnel_z = tunnel.z.0 as f64 + (tunnel.z.1 - tunnel.z.0) as f64 * t;
+                if (tunnel_z - z as f64).abs() > TUNNEL_Z_REACH {
+                    continue;
+                }
+                let ramp = tunnel_z - z as f64 >= 1.0 - TUNNEL_Z_REACH;
+                let (px, py) = tunnel.segment.point_at(&self.perlin, t);
+                let (cx, cy) = ((px - gx0).floor() as i64, (py - gy0).floor() as i64);
+                if cx < -r || cy < -r || cx >= size as i64 + r || cy >= size as i64 + r {
+                    continue;
+                }
+                for ox in -r..=r {
+                    for oy in -r..=r {
+                        let (lx, ly) = (cx + ox, cy + oy);
+                        if lx < 0 || ly < 0 || lx >= size as i64 || ly >= size as i64 {
+                            continue;
+                        }
+                        if ((ox * ox + oy * oy) as f64) <= tunnel.half_width * tunnel.half_width {
+                            let cell = &mut mask[lx as usize * size + ly as usize];
+                            if ramp && *cell != Carve::Lake {
+                                *cell = Carve::Ramp;
+                            } else if *cell == Carve::None {
+                                *cell = Carve::Open;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for lx in 0..size {
+            for ly in 0..size {
+                let material = match mask[lx * size + ly] {
+                    Carve::None => continue,
+                    Carve::Open => TerrainMaterial::Air,
+                    Carve::Lake => TerrainMaterial::Water,
+                    Carve::Ramp => TerrainMaterial::Ramp,
+                };
+                ctx.chunk.tiles[lx / TILE_SIZE][ly / TILE_SIZE].set_material(lx % TILE_SIZE, ly % TILE_SIZE, material);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creatures::pathfinding::find_path;
+    use crate::items::registry::{ItemRegistry, ITEMS_PATH};
+    use crate::world::localmap::material_registry::{MaterialRegistry, MATERIALS_PATH};
+    use crate::world::localmap::position::{WorldPos, CHUNK_SPAN};
+    use crate::world::localmap::world::World;
+    use crate::worldgen::localmap::builder::WorldGeneratorBuilder;
+    use std::sync::Arc;
+
+    const SEED: u32 = 7;
+
+    #[test]
+    fn caverns_a_level_apart_are_joined_by_a_walkable_tunnel() {
+        let tile = WorldTileInfo::default();
+        let stage = CaveStage::new(SEED);
+        // Neighbouring caverns on the backbone, the second one level below the first
+        let (a, b) = (0..TILE_CELLS)
+            .flat_map(|cx| (0..TILE_CELLS).flat_map(move |cy| [((cx, cy), 0), ((cx, cy), 1)]))
+            .filter(|&(cell, dir)| stage.in_backbone(cell, dir))
+            .map(|((cx, cy), dir)| {
+                let next = if dir == 0 { (cx + 1, cy) } else { (cx, cy + 1) };
+                (stage.cavern(&tile, cx, cy), stage.cavern(&tile, next.0, next.1))
+            })
+            .find(|(a, b)| a.z - b.z == 1 && !a.lake && !b.lake)
+            .unwrap();
+
+        let materials = Arc::new(MaterialRegistry::from_json(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap());
+        let item_kinds = ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap();
+        let generator = WorldGeneratorBuilder::new(SEED, materials.clone()).with_world_tile(tile.clone()).build();
+        let mut world = World::new(materials, Arc::new(item_kinds));
+        let local = |cavern: &Cavern| {
+            let (x, y) = tile.local(cavern.pos.0, cavern.pos.1);
+            WorldPos::new(x as i32, y as i32, cavern.z)
+        };
+        let (start, goal) = (local(&a), local(&b));
+        let chunks = |from: i32, to: i32| (from.min(to) - CHUNK_SPAN).div_euclid(CHUNK_SPAN)..=(from.max(to) + CHUNK_SPAN).div_euclid(CHUNK_SPAN);
+        let area: Vec<(i32, i32)> = chunks(start.x, goal.x)
+            .flat_map(|cx| chunks(start.y, goal.y).map(move |cy| (cx, cy)))
+            .collect();
+        generator.generate_into_world(&mut world, &area);
+
+        let path = find_path(&world, start, goal, 2.0).expect("no way between the caverns");
+        assert!(path.iter().any(|pos| world.get_material_at(pos.x, pos.y, pos.z) == TerrainMaterial::Ramp));
+        assert_eq!(path[0].z, goal.z);
+    }
+}
@@ -23,7 +23,13 @@ impl GenStage for HeightStage {
             for ty in 0..CHUNK_SIZE {
                 let wx = ctx.world_x0 + (tx * TILE_SIZE) as i32;
                 let wy = ctx.world_y0 + (ty * TILE_SIZE) as i32;
-                ctx.height[tx][ty] = ((self.perlin.get([wx as f64 * self.scale, wy as f64 * self.scale]) + 1.0) * 0.5) as f32;
+                // Sample in global coordinates so neighbouring embarks line up
+                let (gx, gy) = ctx.tile.global(wx, wy);
+                let h = ((self.perlin.get([gx * self.scale, gy * self.scale]) + 1.0) * 0.5) as f32;
+                // Rugged biomes push the height further from the midpoint
+                let half = TILE_SIZE as f64 * 0.5;
+                let relief = ctx.tile.blend(wx as f64 + half, wy as f64 + half).profile.relief;
+                ctx.height[tx][ty] = 0.5 + (h - 0.5) * relief;
             }
         }
     }
@@ -22,27 +22,47 @@ impl MaterialStage {
 
 impl GenStage for MaterialStage {
     fn execute(&self, ctx: &mut GenCtx) {
+        if ctx.z != 0 {
+            return;
+        }
         // Debug: print min/max subpixel noise for this chunk
         let mut min_h = f32::MAX;
         let mut max_h = f32::MIN;
         for tx in 0..CHUNK_SIZE {
             for ty in 0..CHUNK_SIZE {
+                let tile_height = ctx.height[tx][ty];
                 for sx in 0..TILE_SIZE {
                     for sy in 0..TILE_SIZE {
                         let wx = ctx.world_x0 + (tx * TILE_SIZE + sx) as i32;
                         let wy = ctx.world_y0 + (ty * TILE_SIZE + sy) as i32;
-                        let sub_height = ((self.perlin.get([
-                            wx as f64 * self.scale,
-                            wy as f64 * self.scale,
-                        ]) + 1.0) * 0.5) as f32;
+                        let terrain = ctx.tile.blend(wx as f64, wy as f64);
+                        let profile = terrain.profile;
+                        let (gx, gy) = ctx.tile.global(wx, wy);
+                        let mut sub_height = ((self.perlin.get([gx * self.scale, gy * self.scale]) + 1.0) * 0.5) as f32;
+                        // Dunes: squash the noise along one axis into long troughs
+                        if profile.dunes > 0.0 {
+                            let dune = ((self.perlin.get([gx * self.scale * 0.25, gy * self.scale * 4.0]) + 1.0) * 0.5) as f32;
+                            sub_height += (dune - sub_height) * profile.dunes;
+                        }
                         if sub_height < min_h { min_h = sub_height; }
                         if sub_height > max_h { max_h = sub_height; }
-                        let sub = &mut ctx.chunk.tiles[tx][ty].subgrid[sx][sy];
-                        if sub_height < self.dirt_height {
-                            sub.material = TerrainMaterial::Dirt;
+                        // Dither sand into dirt where a sandy biome meets another one
+                        let ground = if terrain.sand > 0.0 {
+                            let dither = ((self.perlin.get([gx * self.scale * 6.0, gy * self.scale * 6.0 + 100.0]) + 1.0) * 0.5) as f32;
+                            if terrain.sand > dither { TerrainMaterial::Sand } else { TerrainMaterial::Dirt }
                         } else {
-                            sub.material = TerrainMaterial::Air;
-                        }
+                            profile.ground
+                        };
+                        // Ragged cliff edges rather than tile-aligned steps
+                        let cliff_height = tile_height + (sub_height - 0.5) * 0.1;
+                        let material = if cliff_height > profile.rock_line {
+                            TerrainMaterial::Rock
+                        } else if sub_height < self.dirt_height + profile.open_offset {
+                            ground
+                        } else {
+                            TerrainMaterial::Air
+                        };
+                        ctx.chunk.tiles[tx][ty].set_material(sx, sy, material);
                     }
                 }
             }
@@ -52,4 +72,4 @@ impl GenStage for MaterialStage {
             ctx.world_x0, ctx.world_y0, min_h, max_h
         );
     }
-} 
\ No newline at end of file
+}
@@ -1,2 +1,8 @@
+pub mod caves;
 pub mod height;
 pub mod material;
+pub mod ores;
+pub mod roads;
+pub mod sites;
+pub mod strata;
+pub mod water;
@@ -0,0 +1,165 @@
+use crate::worldgen::localmap::pipeline::{GenCtx, GenStage};
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::tile::TILE_SIZE;
+use crate::world::worldmap::world_map::ResourceType;
+use noise::{NoiseFn, Perlin};
+
+/// Size (in global subpixels) of the regions that share one ore type
+const REGION: f64 = 64.0;
+/// Half-width of a vein in noise units; veins follow the noise's zero crossings
+const VEIN_WIDTH: f64 = 0.02;
+/// Frequency of the vein noise per subpixel
+const VEIN_FREQUENCY: f64 = 0.06;
+/// How much more likely (and wider) the world tile's own resource is
+const RESOURCE_BOOST: f64 = 6.0;
+
+/// Where an ore is found: host stones and minimum depth (in levels)
+struct OreKind {
+    ore: TerrainMaterial,
+    resource: ResourceType,
+    weight: f64,
+    min_depth: i32,
+    hosts: &'static [TerrainMaterial],
+}
+
+const ORES: [OreKind; 5] = [
+    OreKind {
+        ore: TerrainMaterial::Coal,
+        resource: ResourceType::Coal,
+        weight: 0.8,
+        min_depth: 1,
+        hosts: &[TerrainMaterial::Limestone],
+    },
+    OreKind {
+        ore: TerrainMaterial::IronOre,
+        resource: ResourceType::Iron,
+        weight: 1.0,
+        min_depth: 1,
+        hosts: &[
+            TerrainMaterial::Limestone,
+            TerrainMaterial::Granite,
+            TerrainMaterial::Basalt,
+            TerrainMaterial::Marble,
+            TerrainMaterial::DeepRock,
+        ],
+    },
+    OreKind {
+        ore: TerrainMaterial::CopperOre,
+        resource: ResourceType::Copper,
+        weight: 0.7,
+        min_depth: 1,
+        hosts: &[TerrainMaterial::Limestone, TerrainMaterial::Granite, TerrainMaterial::Basalt],
+    },
+    OreKind {
+        ore: TerrainMaterial::GoldOre,
+        resource: ResourceType::Gold,
+        weight: 0.15,
+        min_depth: 3,
+        hosts: &[TerrainMaterial::Granite, TerrainMaterial::Marble, TerrainMaterial::DeepRock],
+    },
+    OreKind {
+        ore: TerrainMaterial::Gems,
+        resource: ResourceType::Gems,
+        weight: 0.1,
+        min_depth: 4,
+        hosts: &[TerrainMaterial::Granite, TerrainMaterial::Basalt, TerrainMaterial::DeepRock],
+    },
+];
+
+/// Scatters ore veins through the stone strata. Each region of stone gets one ore
+/// type, picked by the host stone and depth; the world tile's resource is picked far
+/// more often and forms thicker, more widespread veins.
+pub struct OreStage {
+    perlin: Perlin,
+    seed: u32,
+}
+
+impl OreStage {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            perlin: Perlin::new(seed.wrapping_add(5)),
+            seed,
+        }
+    }
+
+    /// Deterministic value in `0.0..1.0` for a region on a level.
+    fn hash01(&self, rx: i64, ry: i64, z: i32) -> f64 {
+        let mut h = (self.seed as u64)
+            ^ (rx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (ry as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+        h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        h ^= h >> 31;
+        (h >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Picks the ore for a region given its host stone, or `None` if no ore fits.
+    fn pick(&self, host: TerrainMaterial, depth: i32, roll: f64, resource: Option<ResourceType>) -> Option<&'static OreKind> {
+        let weight = |kind: &OreKind| {
+            if depth < kind.min_depth || !kind.hosts.contains(&host) {
+                0.0
+            } else if resource == Some(kind.resource) {
+                kind.weight * RESOURCE_BOOST
+            } else {
+                kind.weight
+            }
+        };
+        let total: f64 = ORES.iter().map(weight).sum();
+        let mut roll = roll * total;
+        for kind in &ORES {
+            let w = weight(kind);
+            if w > 0.0 && roll < w {
+                return Some(kind);
+            }
+            roll -= w;
+        }
+        None
+    }
+}
+
+impl GenStage for OreStage {
+    fn execute(&self, ctx: &mut GenCtx) {
+        if ctx.z >= 0 {
+            return;
+        }
+        let depth = -ctx.z;
+        let resource = ctx.tile.resource;
+        // Tiles rich in a resource have veins nearly everywhere, others only in patches
+        let gate = if resource.is_some() { -0.3 } else { 0.1 };
+        for tx in 0..CHUNK_SIZE {
+            for ty in 0..CHUNK_SIZE {
+                let (tgx, tgy) = ctx.tile.global(
+                    ctx.world_x0 + (tx * TILE_SIZE) as i32,
+                    ctx.world_y0 + (ty * TILE_SIZE) as i32,
+                );
+                if self.perlin.get([tgx * 0.01, tgy * 0.01, ctx.z as f64 * 0.5]) < gate {
+                    continue;
+                }
+                let region = ((tgx / REGION).floor() as i64, (tgy / REGION).floor() as i64);
+                let roll = self.hash01(region.0, region.1, ctx.z);
+                let tile = &mut ctx.chunk.tiles[tx][ty];
+                for sx in 0..TILE_SIZE {
+                    for sy in 0..TILE_SIZE {
+                        let host = tile.get(sx, sy).material;
+                        if !ctx.materials.is_stone(host) {
+                            continue;
+                        }
+                        let Some(kind) = self.pick(host, depth, roll, resource) else { continue; };
+                        let width = if resource == Some(kind.resource) { VEIN_WIDTH * 1.5 } else { VEIN_WIDTH };
+                        let (gx, gy) = (tgx + sx as f64, tgy + sy as f64);
+                        let vein = self.perlin.get([
+                            gx * VEIN_FREQUENCY,
+                            gy * VEIN_FREQUENCY,
+                            ctx.z as f64 * 0.9 + 100.0,
+                        ]);
+                        if vein.abs() < width {
+                            tile.set_material(sx, sy, kind.ore);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::worldgen::localmap::pipeline::{GenCtx, GenStage};
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::tile::TILE_SIZE;
+use crate::world::worldmap::civilization::Civilization;
+use noise::Perlin;
+
+/// Half-width of a road in subpixels
+const ROAD_HALF_WIDTH: f64 = 3.0;
+
+/// Lays roads along the transport network's road edges crossing the tile. Roads stop at water.
+/// Inside the lands of road-building civilizations they are paved; elsewhere they are
+/// packed dirt tracks.
+pub struct RoadStage {
+    perlin: Perlin,
+}
+
+impl RoadStage {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            perlin: Perlin::new(seed.wrapping_add(2)),
+        }
+    }
+}
+
+impl GenStage for RoadStage {
+    fn execute(&self, ctx: &mut GenCtx) {
+        if ctx.z != 0 {
+            return;
+        }
+        let span = (CHUNK_SIZE * TILE_SIZE) as f64;
+        let (x0, y0) = (ctx.world_x0 as f64, ctx.world_y0 as f64);
+        let roads: Vec<_> = ctx.tile.roads.iter()
+            .filter(|s| s.near_rect(x0, y0, x0 + span, y0 + span, ROAD_HALF_WIDTH))
+            .collect();
+        if roads.is_empty() {
+            return;
+        }
+        let surface = match ctx.tile.civ {
+            Some(Civilization::Human | Civilization::Dwarf | Civilization::GnomeHalfling) => TerrainMaterial::Floor,
+            _ => TerrainMaterial::Dirt,
+        };
+        for tx in 0..CHUNK_SIZE {
+            for ty in 0..CHUNK_SIZE {
+                for sx in 0..TILE_SIZE {
+                    for sy in 0..TILE_SIZE {
+                        let tile = &mut ctx.chunk.tiles[tx][ty];
+                        if tile.get(sx, sy).material == TerrainMaterial::Water {
+                            continue;
+                        }
+                        let wx = x0 + (tx * TILE_SIZE + sx) as f64;
+                        let wy = y0 + (ty * TILE_SIZE + sy) as f64;
+                        if roads.iter().any(|s| s.distance(&self.perlin, wx, wy) < ROAD_HALF_WIDTH) {
+                            tile.set_material(sx, sy, surface);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::worldgen::localmap::pipeline::{GenCtx, GenStage};
+use crate::worldgen::localmap::stages::caves::hash01;
+use crate::worldgen::localmap::tile_info::LocalSite;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::tile::TILE_SIZE;
+use crate::world::localmap::world::SURFACE_Z;
+use crate::world::worldmap::site::LocalGenHint;
+
+/// How far (in subpixels) a site is moved off its world tile's centre, so it doesn't sit
+/// where the roads meet
+const SITE_JITTER: f64 = 96.0;
+/// Distance from a site's centre beyond which nothing of it is built
+const SITE_REACH: i32 = 24;
+/// Levels a mine shaft goes down
+const MINE_DEPTH: i32 = 4;
+/// Ores a mine shaft may have been dug for
+const MINE_ORES: [TerrainMaterial; 4] =
+    [TerrainMaterial::IronOre, TerrainMaterial::CopperOre, TerrainMaterial::GoldOre, TerrainMaterial::Coal];
+
+/// Dresses the world sites on and around the tile (see [`LocalGenHint`]): ruined walls,
+/// cave mouths, dug complexes, clearings, mine shafts and towers. Runs last, so sites cut
+/// through whatever the terrain stages left. Each site is placed from its world tile alone,
+/// so neighbouring embarks build it in the same place.
+pub struct SiteStage {
+    seed: u32,
+}
+
+impl SiteStage {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    /// Local subpixel position of a site's centre.
+    fn centre(&self, site: &LocalSite) -> (i32, i32) {
+        let (tx, ty) = (site.tile.0 as i64, site.tile.1 as i64);
+        let jitter = |salt| (hash01(self.seed, tx, ty, salt) * 2.0 - 1.0) * SITE_JITTER;
+        ((site.centre.0 + jitter(20)).floor() as i32, (site.centre.1 + jitter(21)).floor() as i32)
+    }
+
+    /// Deterministic value in `0.0..1.0` for a spot of a site, e.g. to break up its walls.
+    fn roll(&self, site: &LocalSite, dx: i32, dy: i32, salt: u64) -> f64 {
+        hash01(self.seed ^ site.tile.0 as u32 ^ (site.tile.1 as u32).rotate_left(16), dx as i64, dy as i64, salt)
+    }
+
+    /// What the subpixel `(dx, dy)` from a site's centre becomes on level `z`, if the site
+    /// reaches it.
+    fn dress(&self, site: &LocalSite, z: i32, dx: i32, dy: i32) -> Option<TerrainMaterial> {
+        let r = ((dx * dx + dy * dy) as f64).sqrt();
+        let depth = SURFACE_Z - z;
+        match site.hint {
+            LocalGenHint::RuinedWalls => {
+                if depth != 0 {
+                    return None;
+                }
+                let ring = dx.abs().max(dy.abs());
+                let outer = ring == 14 || ring == 15;
+                // An outer wall and one across the middle, both broken up in 3-subpixel stretches
+                if outer || (dx == 0 && ring < 14) {
+                    let (wall, along) = if !outer {
+                        (0, dy)
+                    } else if dx.abs() >= dy.abs() {
+                        (dx.signum(), dy)
+                    } else {
+                        (2 * dy.signum(), dx)
+                    };
+                    return (self.roll(site, wall, along.div_euclid(3), 1) < 0.6).then_some(TerrainMaterial::Wall);
+                }
+                if ring < 14 {
+                    let rubble = self.roll(site, dx, dy, 2) < 0.04;
+                    return Some(if rubble { TerrainMaterial::Gravel } else { TerrainMaterial::Dirt });
+                }
+                None
+            }
+            LocalGenHint::CaveMouth => match depth {
+                0 if r <= 6.0 => Some(TerrainMaterial::Dirt),
+                0 if r <= 9.0 && self.roll(site, dx, dy, 3) < 0.5 => Some(TerrainMaterial::Rock),
+                1 if dx == 0 && dy == 0 => Some(TerrainMaterial::Ramp),
+                1 if r <= 10.0 => Some(TerrainMaterial::Air),
+                _ => None,
+            },
+            LocalGenHint::UndergroundComplex => {
+                if depth == 0 {
+                    return (r <= 2.0).then_some(TerrainMaterial::Dirt);
+                }
+                if !(1..=2).contains(&depth) {
+                    return None;
+                }
+                // The ramp up from each floor sits in a different room
+                let ramp = if depth == 1 { (0, 0) } else { (16, 16) };
+                if (dx, dy) == ramp {
+                    return Some(TerrainMaterial::Ramp);
+                }
+                // Three by three rooms 16 apart, joined by corridors along the rows and columns
+                let near = |v: i32| (v - (v as f64 / 16.0).round() as i32 * 16).abs();
+                let inside = dx.abs() <= 21 && dy.abs() <= 21;
+                let room = near(dx) <= 5 && near(dy) <= 5;
+                let corridor = near(dx) <= 1 || near(dy) <= 1;
+                (inside && (room || corridor)).then_some(TerrainMaterial::Floor)
+            }
+            LocalGenHint::Clearing => match depth {
+                0 if dx.abs() <= 1 && dy.abs() <= 1 => Some(TerrainMaterial::Wall),
+                0 if r <= 14.0 => Some(TerrainMaterial::Dirt),
+                _ => None,
+            },
+            LocalGenHint::MineShaft => {
+                if depth == 0 {
+                    return (r <= 3.0).then_some(TerrainMaterial::Dirt);
+                }
+                if !(1..=MINE_DEPTH).contains(&depth) {
+                    return None;
+                }
+                if dx == 0 && dy == 0 {
+                    return Some(TerrainMaterial::Ramp);
+                }
+                if r <= 3.0 {
+                    return Some(TerrainMaterial::Air);
+                }
+                // The ore body the shaft was sunk into
+                let ore = MINE_ORES[(self.roll(site, 0, 0, 4) * MINE_ORES.len() as f64) as usize];
+                (depth == MINE_DEPTH && r <= 7.0 && self.roll(site, dx, dy, 5) < 0.6).then_some(ore)
+            }
+            LocalGenHint::Tower => match depth {
+                0 if r <= 4.5 => Some(TerrainMaterial::Floor),
+                0 if r <= 6.5 && dy > 0 && dx.abs() <= 1 => Some(TerrainMaterial::Door),
+                0 if r <= 6.5 => Some(TerrainMaterial::Wall),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl GenStage for SiteStage {
+    fn execute(&self, ctx: &mut GenCtx) {
+        let span = (CHUNK_SIZE * TILE_SIZE) as i32;
+        for site in &ctx.tile.sites {
+            let (cx, cy) = self.centre(site);
+            if cx + SITE_REACH < ctx.world_x0
+                || cy + SITE_REACH < ctx.world_y0
+                || cx - SITE_REACH >= ctx.world_x0 + span
+                || cy - SITE_REACH >= ctx.world_y0 + span
+            {
+                continue;
+            }
+            for tx in 0..CHUNK_SIZE {
+                for ty in 0..CHUNK_SIZE {
+                    for sx in 0..TILE_SIZE {
+                        for sy in 0..TILE_SIZE {
+                            let dx = ctx.world_x0 + (tx * TILE_SIZE + sx) as i32 - cx;
+                            let dy = ctx.world_y0 + (ty * TILE_SIZE + sy) as i32 - cy;
+                            if dx.abs() > SITE_REACH || dy.abs() > SITE_REACH {
+                                continue;
+                            }
+                            let tile = &mut ctx.chunk.tiles[tx][ty];
+                            // Sites are built around water, not in it
+                            if tile.get(sx, sy).material == TerrainMaterial::Water {
+                                continue;
+                            }
+                            if let Some(material) = self.dress(site, ctx.z, dx, dy) {
+                                tile.set_material(sx, sy, material);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creatures::pathfinding::find_path;
+    use crate::items::registry::{ItemRegistry, ITEMS_PATH};
+    use crate::world::localmap::material_registry::{MaterialRegistry, MATERIALS_PATH};
+    use crate::world::localmap::position::{WorldPos, CHUNK_SPAN};
+    use crate::world::localmap::world::World;
+    use crate::worldgen::localmap::builder::WorldGeneratorBuilder;
+    use crate::worldgen::localmap::tile_info::WorldTileInfo;
+    use std::sync::Arc;
+
+    const SEED: u32 = 7;
+
+    #[test]
+    fn a_mine_shaft_leads_from_the_surface_down_to_its_ore() {
+        let site = LocalSite { hint: LocalGenHint::MineShaft, tile: (0, 0), centre: (128.0, 128.0) };
+        let tile = WorldTileInfo { sites: vec![site], ..WorldTileInfo::default() };
+        let (cx, cy) = SiteStage::new(SEED).centre(&site);
+
+        let materials = Arc::new(MaterialRegistry::from_json(&std::fs::read_to_string(MATERIALS_PATH).unwrap()).unwrap());
+        let item_kinds = ItemRegistry::from_json(&std::fs::read_to_string(ITEMS_PATH).unwrap()).unwrap();
+        let generator = WorldGeneratorBuilder::new(SEED, materials.clone()).with_world_tile(tile).build();
+        let mut world = World::new(materials, Arc::new(item_kinds));
+        let around = |v: i32| (v - SITE_REACH).div_euclid(CHUNK_SPAN)..=(v + SITE_REACH).div_euclid(CHUNK_SPAN);
+        let area: Vec<(i32, i32)> = around(cx).flat_map(|x| around(cy).map(move |y| (x, y))).collect();
+        generator.generate_into_world(&mut world, &area);
+
+        let bottom = SURFACE_Z - MINE_DEPTH;
+        let ores = (-7..=7)
+            .flat_map(|dx| (-7..=7).map(move |dy| (cx + dx, cy + dy)))
+            .filter(|&(x, y)| MINE_ORES.contains(&world.get_material_at(x, y, bottom)))
+            .count();
+        assert!(ores > 0);
+        let start = WorldPos::new(cx + 1, cy, SURFACE_Z);
+        let goal = WorldPos::new(cx + 2, cy, bottom);
+        let path = find_path(&world, start, goal, 1.0).expect("no way down the shaft");
+        assert_eq!(path[0].z, bottom);
+    }
+}
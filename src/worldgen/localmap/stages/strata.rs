@@ -0,0 +1,92 @@
+use crate::worldgen::localmap::generator::UNDERGROUND_LEVELS;
+use crate::worldgen::localmap::pipeline::{GenCtx, GenStage};
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::tile::TILE_SIZE;
+use noise::{NoiseFn, Perlin};
+
+/// Depth (in levels below the surface) where the upper strata give way to the lower ones
+const UPPER_STONE_DEPTH: f32 = 2.5;
+/// Depth (in levels below the surface) where stone gives way to deep rock
+const DEEP_ROCK_DEPTH: f32 = 4.0;
+/// How far layer boundaries wander up and down (in levels)
+const LAYER_JITTER: f32 = 0.6;
+/// How far (in subpixels) geology borders between world tiles wander
+const BORDER_JITTER: f64 = 48.0;
+/// Thickness (in levels) of the topsoil and of the gravel bed under the soil
+const TOPSOIL_DEPTH: f32 = 1.0;
+const GRAVEL_BED: f32 = 0.25;
+
+/// Fills the levels below the surface with strata: topsoil, soil with clay pockets and a
+/// gravel bed (water-logged in places, an aquifer), the biome's upper and lower stone, deep rock and, at the very bottom, magma.
+/// Soil is thicker under low ground and missing where rock is exposed on the surface.
+pub struct StrataStage {
+    perlin: Perlin,
+    scale: f64,
+}
+
+impl StrataStage {
+    pub fn new(seed: u32, scale: f64) -> Self {
+        Self {
+            perlin: Perlin::new(seed.wrapping_add(3)),
+            scale,
+        }
+    }
+}
+
+impl GenStage for StrataStage {
+    fn execute(&self, ctx: &mut GenCtx) {
+        if ctx.z >= 0 {
+            return;
+        }
+        let depth = -ctx.z as f32;
+        let bottom = ctx.z == -UNDERGROUND_LEVELS;
+        let s = self.scale;
+        for tx in 0..CHUNK_SIZE {
+            for ty in 0..CHUNK_SIZE {
+                let tile_height = ctx.height[tx][ty];
+                // Geology is blended per tile, sampled at a wobbly offset so borders between
+                // world tiles don't run straight
+                let (wx0, wy0) = (ctx.world_x0 + (tx * TILE_SIZE) as i32, ctx.world_y0 + (ty * TILE_SIZE) as i32);
+                let (tgx, tgy) = ctx.tile.global(wx0, wy0);
+                let border = self.perlin.get([tgx * s * 2.0, tgy * s * 2.0, 7.0]) * BORDER_JITTER;
+                let profile = ctx.tile.blend(wx0 as f64 + border, wy0 as f64 - border).profile;
+                let soil_depth = if tile_height > profile.rock_line { 0.0 } else { 1.0 + (1.0 - tile_height) * 1.5 };
+                for sx in 0..TILE_SIZE {
+                    for sy in 0..TILE_SIZE {
+                        let (gx, gy) = (tgx + sx as f64, tgy + sy as f64);
+                        let jitter = self.perlin.get([gx * s * 4.0, gy * s * 4.0]) as f32 * LAYER_JITTER;
+                        let d = depth + jitter;
+
+                        let material = if d <= soil_depth {
+                            let pocket = self.perlin.get([gx * s * 12.0, gy * s * 12.0, depth as f64]) as f32;
+                            if d > soil_depth - GRAVEL_BED {
+                                // Wet ground holds water in the gravel bed: an aquifer
+                                if pocket < profile.wetness * 0.6 - 0.45 {
+                                    TerrainMaterial::Water
+                                } else {
+                                    TerrainMaterial::Gravel
+                                }
+                            } else if pocket > 0.45 - profile.wetness * 0.4 {
+                                TerrainMaterial::Clay
+                            } else if d <= TOPSOIL_DEPTH {
+                                TerrainMaterial::Topsoil
+                            } else {
+                                TerrainMaterial::Soil
+                            }
+                        } else if d <= UPPER_STONE_DEPTH {
+                            profile.upper_stone
+                        } else if d <= DEEP_ROCK_DEPTH {
+                            profile.lower_stone
+                        } else if bottom && self.perlin.get([gx * s * 6.0, gy * s * 6.0, 31.0]) > 0.35 {
+                            TerrainMaterial::Magma
+                        } else {
+                            TerrainMaterial::DeepRock
+                        };
+                        ctx.chunk.tiles[tx][ty].set_material(sx, sy, material);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::worldgen::localmap::pipeline::{GenCtx, GenStage};
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::tile::TILE_SIZE;
+use noise::{NoiseFn, Perlin};
+
+/// Half-width of a river channel in subpixels
+const RIVER_HALF_WIDTH: f64 = 6.0;
+/// How far (in world elevation units) the shoreline is pushed around by noise
+const SHORE_NOISE: f64 = 0.015;
+
+/// Floods land below sea level, carves river channels and fills swamp pools and ponds.
+pub struct WaterStage {
+    perlin: Perlin,
+    scale: f64,
+}
+
+impl WaterStage {
+    pub fn new(seed: u32, scale: f64) -> Self {
+        Self {
+            perlin: Perlin::new(seed.wrapping_add(1)),
+            scale,
+        }
+    }
+}
+
+impl GenStage for WaterStage {
+    fn execute(&self, ctx: &mut GenCtx) {
+        if ctx.z != 0 {
+            return;
+        }
+        let tile = ctx.tile;
+        let span = (CHUNK_SIZE * TILE_SIZE) as f64;
+        let (x0, y0) = (ctx.world_x0 as f64, ctx.world_y0 as f64);
+        let rivers: Vec<_> = tile.rivers.iter()
+            .filter(|s| s.near_rect(x0, y0, x0 + span, y0 + span, RIVER_HALF_WIDTH))
+            .collect();
+        for tx in 0..CHUNK_SIZE {
+            for ty in 0..CHUNK_SIZE {
+                for sx in 0..TILE_SIZE {
+                    for sy in 0..TILE_SIZE {
+                        let wx = ctx.world_x0 + (tx * TILE_SIZE + sx) as i32;
+                        let wy = ctx.world_y0 + (ty * TILE_SIZE + sy) as i32;
+                        let terrain = tile.blend(wx as f64, wy as f64);
+                        let (gx, gy) = tile.global(wx, wy);
+                        let shore = self.perlin.get([gx * self.scale * 2.0, gy * self.scale * 2.0]) * SHORE_NOISE;
+                        let mut water = terrain.elevation + shore <= tile.sea_level;
+                        // Swamps are mostly pools; very wet tiles get the odd pond
+                        if !water && terrain.profile.wetness > 0.0 {
+                            let threshold = -1.0 + terrain.profile.wetness as f64 * 0.7;
+                            water = self.perlin.get([gx * self.scale * 3.0, gy * self.scale * 3.0]) < threshold;
+                        }
+                        if !water {
+                            water = rivers.iter()
+                                .any(|s| s.distance(&self.perlin, wx as f64, wy as f64) < RIVER_HALF_WIDTH);
+                        }
+                        if water {
+                            ctx.chunk.tiles[tx][ty].set_material(sx, sy, TerrainMaterial::Water);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
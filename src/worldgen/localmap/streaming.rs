@@ -0,0 +1,196 @@
+//! Loads local map chunks around the camera and creatures, and unloads far-away ones
+use crate::jobs::JobKind;
+use crate::plants::Plant;
+use crate::world::localmap::chunk::Chunk;
+use crate::world::localmap::position::{WorldPos, CHUNK_SPAN};
+use crate::world::localmap::region_file::{ColumnPlants, RegionStore, Stored};
+use crate::world::localmap::world::{World, SURFACE_Z};
+use crate::worldgen::localmap::generator::{WorldGenerator, UNDERGROUND_LEVELS};
+use crate::worldgen::localmap::tile_info::{WorldTileInfo, EXPLORE_MAX, EXPLORE_MIN};
+use crate::worldgen::localmap::vegetation::place_plants;
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::JoinHandle;
+
+/// Chunks loaded beyond the edges of what is being looked at
+const LOAD_MARGIN: i32 = 1;
+/// Loaded chunks are kept until they are this many chunks away from everything in view
+const KEEP_MARGIN: i32 = 3;
+
+enum Job {
+    /// Generate column (cx, cy), or reload it from the region files
+    Load(i32, i32),
+    /// Write changed chunks and plants to the region files
+    Save(Stored),
+}
+
+/// Every level of one chunk column, ready to go into the world
+struct Column {
+    pos: (i32, i32),
+    chunks: Vec<(i32, Chunk)>,
+    /// Plants as the column was left, with the priorities of their chop jobs; `None` the
+    /// first time it is loaded
+    plants: Option<ColumnPlants>,
+}
+
+/// Streams chunk columns in and out of the local world. Columns are generated (or read back
+/// from disk if they were changed before being unloaded) on a background thread. Plants of
+/// unloaded columns are always kept on disk, so felled trees stay felled.
+pub struct ChunkStreamer {
+    jobs: Option<Sender<Job>>,
+    columns: Receiver<Column>,
+    worker: Option<JoinHandle<()>>,
+    store: RegionStore,
+    /// Columns requested from the worker and not yet received
+    pending: HashSet<(i32, i32)>,
+    tile: WorldTileInfo,
+    seed: u32,
+}
+
+impl ChunkStreamer {
+    /// Starts streaming for the local map generated by `generator`. Region files from an
+    /// earlier embark on the same world tile are replaced by `stored`, the changed chunks
+    /// and plants that are not in the world (empty for a fresh embark, see [`Self::finish`]).
+    pub fn new(generator: WorldGenerator, tile: WorldTileInfo, seed: u32, stored: &Stored) -> Self {
+        let store = RegionStore::new(format!("saves/local/{}_{}_{}", seed, tile.x, tile.y));
+        if let Err(e) = store.clear() {
+            eprintln!("Could not clear old region files: {}", e);
+        }
+        if let Err(e) = store.save(stored) {
+            eprintln!("Could not write region file: {}", e);
+        }
+        let worker_store = store.clone();
+        let (jobs, job_rx) = channel();
+        let (column_tx, columns) = channel();
+        let worker = std::thread::spawn(move || {
+            let store = worker_store;
+            for job in job_rx {
+                match job {
+                    Job::Load(cx, cy) => {
+                        let (mut stored, plants) = store.load_column(cx, cy).unwrap_or_else(|e| {
+                            eprintln!("Could not read chunk column ({}, {}): {}", cx, cy, e);
+                            Default::default()
+                        });
+                        let chunks = (SURFACE_Z - UNDERGROUND_LEVELS..=SURFACE_Z)
+                            .rev()
+                            .map(|z| (z, stored.remove(&z).unwrap_or_else(|| generator.generate_chunk(cx, cy, z))))
+                            .collect();
+                        if column_tx.send(Column { pos: (cx, cy), chunks, plants }).is_err() {
+                            // The streamer is gone
+                            break;
+                        }
+                    }
+                    Job::Save(stored) => {
+                        if let Err(e) = store.save(&stored) {
+                            eprintln!("Could not write region file: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+        Self { jobs: Some(jobs), columns, worker: Some(worker), store, pending: HashSet::new(), tile, seed }
+    }
+
+    /// Chunk columns within `margin` chunks of any of the focus rectangles (in subpixels),
+    /// clipped to the explorable area.
+    fn columns_near(focus: &[(f32, f32, f32, f32)], margin: i32) -> HashSet<(i32, i32)> {
+        let span = CHUNK_SPAN as f32;
+        let (lo, hi) = (EXPLORE_MIN.div_euclid(CHUNK_SPAN), (EXPLORE_MAX - 1).div_euclid(CHUNK_SPAN));
+        let mut columns = HashSet::new();
+        for &(x0, y0, x1, y1) in focus {
+            let range = |a: f32, b: f32| {
+                ((a / span).floor() as i32 - margin).max(lo)..=((b / span).floor() as i32 + margin).min(hi)
+            };
+            for cx in range(x0, x1) {
+                for cy in range(y0, y1) {
+                    columns.insert((cx, cy));
+                }
+            }
+        }
+        columns
+    }
+
+    /// Adds finished columns to the world, requests the ones near `focus` (rectangles in
+    /// subpixels: the view and each creature) and unloads those far from all of it.
+    pub fn update(&mut self, world: &mut World, focus: &[(f32, f32, f32, f32)]) {
+        let Some(jobs) = &self.jobs else { return; };
+
+        while let Ok(Column { pos, chunks, plants }) = self.columns.try_recv() {
+            self.pending.remove(&pos);
+            for (z, chunk) in chunks {
+                world.insert_chunk(z, pos, chunk);
+            }
+            match plants {
+                Some(plants) => {
+                    for (plant, priority) in plants {
+                        if plant.chop {
+                            world.jobs.post(JobKind::Chop, WorldPos::new(plant.x, plant.y, plant.z), priority);
+                        }
+                        world.plants.push(plant);
+                    }
+                }
+                None => world.plants.extend(place_plants(world, &self.tile, self.seed, pos)),
+            }
+        }
+
+        let loaded: HashSet<(i32, i32)> = world
+            .z_level(SURFACE_Z)
+            .map(|level| level.chunks.keys().copied().collect())
+            .unwrap_or_default();
+        for pos in Self::columns_near(focus, LOAD_MARGIN) {
+            if !loaded.contains(&pos) && self.pending.insert(pos) {
+                let _ = jobs.send(Job::Load(pos.0, pos.1));
+            }
+        }
+
+        let keep = Self::columns_near(focus, KEEP_MARGIN);
+        for pos in loaded.difference(&keep) {
+            let mut stored = Stored::default();
+            for z in SURFACE_Z - UNDERGROUND_LEVELS..=SURFACE_Z {
+                if let Some(chunk) = world.remove_chunk(z, *pos)
+                    && chunk.modified
+                {
+                    stored.chunks.push(((pos.0, pos.1, z), chunk));
+                }
+            }
+            // Unchanged chunks are simply generated again when they come back into view
+            let (leaving, staying): (Vec<Plant>, Vec<Plant>) = std::mem::take(&mut world.plants)
+                .into_iter()
+                .partition(|plant| WorldPos::new(plant.x, plant.y, plant.z).chunk().key() == *pos);
+            world.plants = staying;
+            // Chop jobs go with their trees and are posted again when they come back
+            let leaving: ColumnPlants = leaving
+                .into_iter()
+                .map(|plant| {
+                    let job = world.jobs.withdraw(JobKind::Chop, WorldPos::new(plant.x, plant.y, plant.z));
+                    (plant, job.map_or(0, |job| job.priority))
+                })
+                .collect();
+            stored.plants.push((*pos, leaving));
+            let _ = jobs.send(Job::Save(stored));
+        }
+    }
+}
+
+impl ChunkStreamer {
+    /// Waits for the worker to finish writing unloaded chunks.
+    fn stop(&mut self) {
+        self.jobs = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    /// Stops streaming and returns the changed chunks and plants that were unloaded to disk.
+    pub fn finish(mut self) -> std::io::Result<Stored> {
+        self.stop();
+        self.store.load_all()
+    }
+}
+
+impl Drop for ChunkStreamer {
+    /// Lets the worker finish writing unloaded chunks before the next streamer clears them.
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,367 @@
+//! World map data for the tile a local map is generated from
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::tile::TILE_SIZE;
+use crate::world::worldmap::biome::{BiomeId, VegetationType};
+use crate::world::worldmap::civilization::Civilization;
+use crate::world::worldmap::site::LocalGenHint;
+use crate::world::worldmap::transport::EdgeKind;
+use crate::world::worldmap::world_map::{ResourceType, WorldMap};
+use crate::worldgen::localmap::segments::Segment;
+use std::collections::HashSet;
+
+/// Local subpixels covered by one world map tile along each axis (three chunks)
+pub const WORLD_TILE_SUBPIXELS: i32 = 3 * (CHUNK_SIZE * TILE_SIZE) as i32;
+/// Local subpixel coordinate of the world tile's west/north edge, so chunks -1..=1 cover it
+pub const WORLD_TILE_ORIGIN: i32 = -((CHUNK_SIZE * TILE_SIZE) as i32);
+/// West/north edge of the explorable area: the world tile and its eight neighbours, which
+/// is as far as the snapshot in [`WorldTileInfo`] reaches
+pub const EXPLORE_MIN: i32 = WORLD_TILE_ORIGIN - WORLD_TILE_SUBPIXELS;
+/// East/south edge (exclusive) of the explorable area
+pub const EXPLORE_MAX: i32 = WORLD_TILE_ORIGIN + 2 * WORLD_TILE_SUBPIXELS;
+
+/// The parts of a world tile that are blended across tile boundaries.
+#[derive(Debug, Clone, Copy)]
+pub struct TileSample {
+    pub biome: BiomeId,
+    pub elevation: f64,
+    pub precipitation: f64,
+}
+
+/// A world site on or next to the tile, for the local generator to dress.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalSite {
+    pub hint: LocalGenHint,
+    /// World map tile the site is on
+    pub tile: (usize, usize),
+    /// Local subpixel position of that tile's centre
+    pub centre: (f64, f64),
+}
+
+/// Snapshot of one world map tile and its 3x3 neighbourhood, handed to every local generation stage.
+#[derive(Debug, Clone)]
+pub struct WorldTileInfo {
+    pub x: usize,
+    pub y: usize,
+    pub biome: BiomeId,
+    pub sea_level: f64,
+    pub temperature: f64,
+    pub vegetation: f64,
+    pub vegetation_type: VegetationType,
+    pub resource: Option<ResourceType>,
+    /// Civilization owning the tile, which decides how its roads are surfaced
+    pub civ: Option<Civilization>,
+    /// Surrounding tiles indexed `[dx + 1][dy + 1]` (this tile's elevation and precipitation
+    /// are at `[1][1]`); off-map neighbours repeat the border tile
+    pub neighbours: [[TileSample; 3]; 3],
+    /// River reaches (in local subpixels) that touch this tile, continuing into its neighbours
+    pub rivers: Vec<Segment>,
+    /// Road segments (in local subpixels) of the transport network crossing this tile
+    pub roads: Vec<Segment>,
+    /// Sites on this tile and its neighbours
+    pub sites: Vec<LocalSite>,
+}
+
+/// How a biome shapes the local terrain.
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainProfile {
+    /// Walkable ground material
+    pub ground: TerrainMaterial,
+    /// Multiplier on the height noise amplitude (rolling plains vs. craggy peaks)
+    pub relief: f32,
+    /// Height above which the ground becomes exposed rock (cliffs)
+    pub rock_line: f32,
+    /// Shift of the material stage's dirt height; positive means fewer open pockets
+    pub open_offset: f32,
+    /// How strongly open pockets are stretched into wind-aligned dune troughs (0..1)
+    pub dunes: f32,
+    /// How readily ponds form (0 = never, 1 = swamp)
+    pub wetness: f32,
+    /// Multiplier on cave size and tunnel count underground
+    pub caves: f32,
+    /// Stone of the upper strata, just under the soil
+    pub upper_stone: TerrainMaterial,
+    /// Stone of the lower strata, above the deep rock
+    pub lower_stone: TerrainMaterial,
+}
+
+/// A terrain profile interpolated between neighbouring world tiles at one local position.
+#[derive(Debug, Clone, Copy)]
+pub struct BlendedTerrain {
+    pub profile: TerrainProfile,
+    /// Share of the blend coming from sandy biomes, for dithering the ground material
+    pub sand: f32,
+    pub elevation: f64,
+}
+
+impl TerrainProfile {
+    pub fn for_biome(biome: BiomeId, precipitation: f64) -> Self {
+        let (ground, relief, rock_line, open_offset, dunes) = match biome {
+            BiomeId::Desert => (TerrainMaterial::Sand, 0.6, 1.1, -0.08, 1.0),
+            BiomeId::Beach => (TerrainMaterial::Sand, 0.3, 1.1, 0.05, 0.0),
+            BiomeId::Mountain => (TerrainMaterial::Dirt, 1.6, 0.45, 0.1, 0.0),
+            BiomeId::Snow => (TerrainMaterial::Dirt, 1.4, 0.5, 0.1, 0.0),
+            BiomeId::Hills => (TerrainMaterial::Dirt, 1.2, 0.72, 0.02, 0.0),
+            BiomeId::Savanna => (TerrainMaterial::Dirt, 0.7, 0.95, 0.02, 0.0),
+            _ => (TerrainMaterial::Dirt, 0.8, 0.95, 0.0, 0.0),
+        };
+        // Swamps are mostly pools; very wet tiles get the odd pond
+        let wetness = if biome == BiomeId::Swamp {
+            1.0
+        } else if precipitation > 0.7 {
+            0.5
+        } else {
+            0.0
+        };
+        // Hard, folded rock under high ground is riddled with caves; wet lowlands much less so
+        let caves = match biome {
+            BiomeId::Mountain => 1.6,
+            BiomeId::Hills | BiomeId::Snow => 1.3,
+            BiomeId::Desert => 0.8,
+            BiomeId::Beach => 0.5,
+            BiomeId::Swamp => 0.4,
+            BiomeId::Ocean | BiomeId::Sea | BiomeId::Lake => 0.3,
+            _ => 1.0,
+        };
+        // Sediments on top of the crystalline basement; mountains are granite through and
+        // through, coasts and swamps sit on basalt
+        let (upper_stone, lower_stone) = match biome {
+            BiomeId::Mountain => (TerrainMaterial::Granite, TerrainMaterial::Granite),
+            BiomeId::Snow => (TerrainMaterial::Marble, TerrainMaterial::Granite),
+            BiomeId::Hills => (TerrainMaterial::Limestone, TerrainMaterial::Marble),
+            BiomeId::Ocean | BiomeId::Sea | BiomeId::Beach | BiomeId::Swamp => {
+                (TerrainMaterial::Limestone, TerrainMaterial::Basalt)
+            }
+            _ => (TerrainMaterial::Limestone, TerrainMaterial::Granite),
+        };
+        TerrainProfile { ground, relief, rock_line, open_offset, dunes, wetness, caves, upper_stone, lower_stone }
+    }
+}
+
+impl TileSample {
+    fn from_world_map(world: &WorldMap, x: usize, y: usize) -> Self {
+        Self {
+            biome: world.biomes[x][y],
+            elevation: world.elevation[x][y],
+            precipitation: world.precipitation[x][y],
+        }
+    }
+
+    /// Elevation used for shorelines: lakes and seas sit below sea level even if the
+    /// world map stored them a little higher.
+    fn water_elevation(&self, sea_level: f64) -> f64 {
+        match self.biome {
+            BiomeId::Ocean | BiomeId::Sea | BiomeId::Lake => self.elevation.min(sea_level - 0.02),
+            _ => self.elevation,
+        }
+    }
+}
+
+impl Default for WorldTileInfo {
+    fn default() -> Self {
+        let sample = TileSample { biome: BiomeId::Plains, elevation: 0.5, precipitation: 0.5 };
+        Self {
+            x: 0,
+            y: 0,
+            biome: sample.biome,
+            sea_level: 0.35,
+            temperature: 0.5,
+            vegetation: 0.5,
+            vegetation_type: VegetationType::Grass,
+            resource: None,
+            civ: None,
+            neighbours: [[sample; 3]; 3],
+            rivers: Vec::new(),
+            roads: Vec::new(),
+            sites: Vec::new(),
+        }
+    }
+}
+
+impl WorldTileInfo {
+    pub fn from_world_map(world: &WorldMap, x: usize, y: usize) -> Self {
+        let clamp = |v: usize, d: i32, max: usize| (v as i32 + d).clamp(0, max as i32 - 1) as usize;
+        let mut neighbours = [[TileSample::from_world_map(world, x, y); 3]; 3];
+        for dx in -1i32..=1 {
+            for dy in -1i32..=1 {
+                let (nx, ny) = (clamp(x, dx, world.width), clamp(y, dy, world.height));
+                neighbours[(dx + 1) as usize][(dy + 1) as usize] = TileSample::from_world_map(world, nx, ny);
+            }
+        }
+
+        let mut info = Self {
+            x,
+            y,
+            biome: world.biomes[x][y],
+            sea_level: world.sea_level,
+            temperature: world.temperature[x][y],
+            vegetation: world.vegetation[x][y],
+            vegetation_type: world.vegetation_map[x][y],
+            resource: world.resources[x][y],
+            civ: world.civilization_map[x][y].as_ref().map(|c| c.civ_type),
+            neighbours,
+            rivers: Vec::new(),
+            roads: Vec::new(),
+            sites: Vec::new(),
+        };
+        info.rivers = info.river_links(world).into_iter().map(|(a, b)| info.segment(a, b)).collect();
+        info.roads = info.road_links(world).into_iter().map(|(a, b)| info.segment(a, b)).collect();
+        info.sites = world
+            .sites
+            .iter()
+            .filter(|site| info.is_near((site.x, site.y)))
+            .map(|site| LocalSite {
+                hint: site.local_hint,
+                tile: (site.x, site.y),
+                centre: info.tile_centre_local((site.x, site.y)),
+            })
+            .collect();
+        info
+    }
+
+    /// True if a world tile is within one step of this one, so a link touching it can cross the embark.
+    fn is_near(&self, (tx, ty): (usize, usize)) -> bool {
+        tx.abs_diff(self.x) <= 1 && ty.abs_diff(self.y) <= 1
+    }
+
+    /// Pairs of adjacent world tiles joined by a river, as in the region map.
+    fn river_links(&self, world: &WorldMap) -> Vec<((usize, usize), (usize, usize))> {
+        let mut links = Vec::new();
+        let x0 = self.x.saturating_sub(2);
+        let y0 = self.y.saturating_sub(2);
+        for tx in x0..(self.x + 3).min(world.width) {
+            for ty in y0..(self.y + 3).min(world.height) {
+                if !world.rivers[tx][ty] {
+                    continue;
+                }
+                for (dx, dy) in [(1i32, 0i32), (0, 1), (1, 1), (1, -1)] {
+                    let nx = tx as i32 + dx;
+                    let ny = ty as i32 + dy;
+                    if nx < 0 || ny < 0 || nx as usize >= world.width || ny as usize >= world.height {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    let water_next = world.rivers[nx][ny] || world.elevation[nx][ny] <= world.sea_level;
+                    if water_next && (self.is_near((tx, ty)) || self.is_near((nx, ny))) {
+                        links.push(((tx, ty), (nx, ny)));
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    /// Road edges of the transport network passing near this tile, one link per pair of tiles.
+    fn road_links(&self, world: &WorldMap) -> Vec<((usize, usize), (usize, usize))> {
+        let mut seen = HashSet::new();
+        let is_land = |(x, y): (usize, usize)| world.elevation[x][y] > world.sea_level;
+        for edge in world.transport.edges.iter().filter(|edge| edge.kind == EdgeKind::Road) {
+            for w in edge.path.windows(2) {
+                let (a, b) = (w[0].min(w[1]), w[0].max(w[1]));
+                if a != b && is_land(a) && is_land(b) && (self.is_near(a) || self.is_near(b)) {
+                    seen.insert((a, b));
+                }
+            }
+        }
+        seen.into_iter().collect()
+    }
+
+    /// Local subpixel position of a world tile's centre.
+    pub fn tile_centre_local(&self, (tx, ty): (usize, usize)) -> (f64, f64) {
+        let half = WORLD_TILE_SUBPIXELS as f64 * 0.5;
+        (
+            (tx as f64 - self.x as f64) * WORLD_TILE_SUBPIXELS as f64 + WORLD_TILE_ORIGIN as f64 + half,
+            (ty as f64 - self.y as f64) * WORLD_TILE_SUBPIXELS as f64 + WORLD_TILE_ORIGIN as f64 + half,
+        )
+    }
+
+    fn segment(&self, a: (usize, usize), b: (usize, usize)) -> Segment {
+        // Seed the meander from the world-space midpoint so neighbouring embarks agree
+        let seed = ((a.0 + b.0) as f64 * 0.5, (a.1 + b.1) as f64 * 0.5);
+        Segment::new(self.tile_centre_local(a), self.tile_centre_local(b), seed)
+    }
+
+    /// Position in global subpixels, shared by every embark on the same world, for sampling noise.
+    pub fn global(&self, lx: i32, ly: i32) -> (f64, f64) {
+        (
+            (self.x as i64 * WORLD_TILE_SUBPIXELS as i64 + (lx - WORLD_TILE_ORIGIN) as i64) as f64,
+            (self.y as i64 * WORLD_TILE_SUBPIXELS as i64 + (ly - WORLD_TILE_ORIGIN) as i64) as f64,
+        )
+    }
+
+    /// Biome of the world tile a local subpixel position falls in.
+    pub fn biome_at(&self, lx: i32, ly: i32) -> BiomeId {
+        let index = |l: i32| ((l - WORLD_TILE_ORIGIN).div_euclid(WORLD_TILE_SUBPIXELS) + 1).clamp(0, 2) as usize;
+        self.neighbours[index(lx)][index(ly)].biome
+    }
+
+    /// Inverse of [`Self::global`]: local subpixel position of a global one.
+    pub fn local(&self, gx: f64, gy: f64) -> (f64, f64) {
+        (
+            gx - (self.x as i64 * WORLD_TILE_SUBPIXELS as i64) as f64 + WORLD_TILE_ORIGIN as f64,
+            gy - (self.y as i64 * WORLD_TILE_SUBPIXELS as i64) as f64 + WORLD_TILE_ORIGIN as f64,
+        )
+    }
+
+    /// Bilinearly blend the surrounding tiles' terrain at a local subpixel position.
+    /// Tile centres carry their own profile; halfway to a neighbour the two are mixed evenly.
+    pub fn blend(&self, lx: f64, ly: f64) -> BlendedTerrain {
+        let span = WORLD_TILE_SUBPIXELS as f64;
+        // Offset from this tile's centre in tiles, roughly -0.5..0.5 inside the embark
+        let fx = ((lx - WORLD_TILE_ORIGIN as f64) / span - 0.5).clamp(-1.0, 1.0);
+        let fy = ((ly - WORLD_TILE_ORIGIN as f64) / span - 0.5).clamp(-1.0, 1.0);
+        let ix = if fx < 0.0 { 0 } else { 1 };
+        let iy = if fy < 0.0 { 0 } else { 1 };
+        let (tx, ty) = ((fx + 1.0 - ix as f64) as f32, (fy + 1.0 - iy as f64) as f32);
+
+        let corners = [
+            (ix, iy, (1.0 - tx) * (1.0 - ty)),
+            (ix + 1, iy, tx * (1.0 - ty)),
+            (ix, iy + 1, (1.0 - tx) * ty),
+            (ix + 1, iy + 1, tx * ty),
+        ];
+        let mut relief = 0.0;
+        let mut rock_line = 0.0;
+        let mut open_offset = 0.0;
+        let mut dunes = 0.0;
+        let mut wetness = 0.0;
+        let mut caves = 0.0;
+        let mut elevation = 0.0;
+        let mut sand_weight = 0.0;
+        // Stone types can't be mixed; take them from the dominant corner
+        let mut stones = (TerrainMaterial::Limestone, TerrainMaterial::Granite);
+        let mut stone_weight = -1.0;
+        for &(nx, ny, w) in &corners {
+            let sample = &self.neighbours[nx][ny];
+            let p = TerrainProfile::for_biome(sample.biome, sample.precipitation);
+            relief += p.relief * w;
+            rock_line += p.rock_line * w;
+            open_offset += p.open_offset * w;
+            dunes += p.dunes * w;
+            wetness += p.wetness * w;
+            caves += p.caves * w;
+            elevation += sample.water_elevation(self.sea_level) * w as f64;
+            if p.ground == TerrainMaterial::Sand {
+                sand_weight += w;
+            }
+            if w > stone_weight {
+                stones = (p.upper_stone, p.lower_stone);
+                stone_weight = w;
+            }
+        }
+        BlendedTerrain {
+            profile: TerrainProfile {
+                ground: if sand_weight >= 0.5 { TerrainMaterial::Sand } else { TerrainMaterial::Dirt },
+                relief,
+                rock_line,
+                open_offset,
+                dunes,
+                wetness,
+                caves,
+                upper_stone: stones.0,
+                lower_stone: stones.1,
+            },
+            sand: sand_weight,
+            elevation,
+        }
+    }}
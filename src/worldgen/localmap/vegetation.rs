@@ -0,0 +1,100 @@
+//! Places plants on the surface of freshly generated local map chunks
+use crate::plants::{Plant, Species};
+use crate::world::localmap::chunk::CHUNK_SIZE;
+use crate::world::localmap::terrain_material::TerrainMaterial;
+use crate::world::localmap::tile::TILE_SIZE;
+use crate::world::localmap::world::{World, SURFACE_Z};
+use crate::world::worldmap::biome::{BiomeId, VegetationType};
+use crate::worldgen::localmap::stages::caves::hash01;
+use crate::worldgen::localmap::tile_info::WorldTileInfo;
+use noise::{NoiseFn, Perlin};
+
+/// Spacing (in subpixels) of the jittered grid plants are placed on
+const GRID: i32 = 6;
+/// Frequency of the noise that gathers trees into groves
+const GROVE_FREQUENCY: f64 = 0.015;
+/// Below this temperature broadleaf trees give way to conifers
+const COLD: f64 = 0.3;
+
+/// Relative weights of the species growing in a biome.
+fn species_weights(biome: BiomeId) -> &'static [(Species, f64)] {
+    match biome {
+        BiomeId::Forest | BiomeId::TemperateForest => {
+            &[(Species::Oak, 5.0), (Species::Pine, 1.0), (Species::Shrub, 2.0), (Species::Grass, 3.0)]
+        }
+        BiomeId::BorealForest | BiomeId::Taiga => &[(Species::Pine, 6.0), (Species::Shrub, 2.0), (Species::Grass, 2.0)],
+        BiomeId::Jungle | BiomeId::Rainforest => &[(Species::JungleTree, 6.0), (Species::Shrub, 3.0), (Species::Grass, 1.0)],
+        BiomeId::Desert => &[(Species::Cactus, 3.0), (Species::Shrub, 1.0)],
+        BiomeId::Savanna => &[(Species::Grass, 8.0), (Species::Shrub, 2.0), (Species::Oak, 1.0)],
+        BiomeId::Swamp => &[(Species::Shrub, 4.0), (Species::Grass, 4.0), (Species::Oak, 2.0)],
+        BiomeId::Tundra | BiomeId::Snow | BiomeId::Mountain => &[(Species::Grass, 4.0), (Species::Shrub, 2.0), (Species::Pine, 1.0)],
+        BiomeId::Beach => &[(Species::Grass, 1.0)],
+        BiomeId::Hills => &[(Species::Grass, 6.0), (Species::Shrub, 3.0), (Species::Oak, 1.0), (Species::Pine, 1.0)],
+        _ => &[(Species::Grass, 8.0), (Species::Shrub, 2.0), (Species::Oak, 1.0)],
+    }
+}
+
+/// Share of grid cells that get a plant for a vegetation type.
+fn cover(vegetation: VegetationType) -> f64 {
+    match vegetation {
+        VegetationType::None => 0.03,
+        VegetationType::Sparse => 0.15,
+        VegetationType::Cacti => 0.12,
+        VegetationType::Grass => 0.5,
+        VegetationType::Shrubs => 0.55,
+        VegetationType::Forest => 0.65,
+        VegetationType::Jungle => 0.8,
+    }
+}
+
+/// Scatters plants over the surface ground of one chunk column. Trees gather in groves where
+/// the grove noise is high and give way to grass and shrubs in the clearings between.
+pub fn place_plants(world: &World, tile: &WorldTileInfo, seed: u32, (cx, cy): (i32, i32)) -> Vec<Plant> {
+    let perlin = Perlin::new(seed.wrapping_add(11));
+    let density = cover(tile.vegetation_type) * (0.4 + 0.6 * tile.vegetation);
+    let span = (CHUNK_SIZE * TILE_SIZE) as i32;
+    let (x0, y0) = (cx * span, cy * span);
+    // The grid is aligned in global coordinates so placement doesn't depend on the embark
+    let (ox, oy) = tile.global(0, 0);
+    let (ox, oy) = (ox as i64, oy as i64);
+    let first = |local: i32, offset: i64| (local as i64 + offset).div_euclid(GRID as i64);
+    let mut plants = Vec::new();
+    for hx in first(x0, ox)..first(x0 + span, ox) {
+        for hy in first(y0, oy)..first(y0 + span, oy) {
+            let (gx, gy) = ((hx * GRID as i64) as f64, (hy * GRID as i64) as f64);
+            let grove = (perlin.get([gx * GROVE_FREQUENCY, gy * GROVE_FREQUENCY]) + 1.0) / 2.0;
+            if hash01(seed, hx, hy, 20) >= density * (0.5 + grove) {
+                continue;
+            }
+            let lx = (hx * GRID as i64 - ox) as i32;
+            let ly = (hy * GRID as i64 - oy) as i32;
+            let x = (lx + (hash01(seed, hx, hy, 21) * GRID as f64) as i32).clamp(x0, x0 + span - 1);
+            let y = (ly + (hash01(seed, hx, hy, 22) * GRID as f64) as i32).clamp(y0, y0 + span - 1);
+            let ground = world.get_material_at(x, y, SURFACE_Z);
+            if ground == TerrainMaterial::Air || !world.materials.is_walkable(ground) {
+                continue;
+            }
+
+            let weights = species_weights(tile.biome_at(x, y));
+            let total: f64 = weights.iter().map(|(_, w)| w).sum();
+            let mut roll = hash01(seed, hx, hy, 23) * total;
+            let mut species = weights[0].0;
+            for &(candidate, weight) in weights {
+                if roll < weight {
+                    species = candidate;
+                    break;
+                }
+                roll -= weight;
+            }
+            if species.is_tree() && grove < 0.45 {
+                species = Species::Grass;
+            }
+            if species == Species::Oak && tile.temperature < COLD {
+                species = Species::Pine;
+            }
+            let growth = 0.3 + 0.7 * hash01(seed, hx, hy, 24) as f32;
+            plants.push(Plant::new(x, y, SURFACE_Z, species, growth));
+        }
+    }
+    plants
+}
@@ -1,2 +1,3 @@
 pub mod worldmap;
 pub mod localmap;
+pub mod regionmap;
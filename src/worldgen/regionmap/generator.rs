@@ -0,0 +1,255 @@
+use crate::world::regionmap::region_map::{RegionMap, CELLS_PER_TILE, REGION_RADIUS};
+use crate::world::worldmap::biome::BiomeId;
+use crate::world::worldmap::transport::EdgeKind;
+use crate::world::worldmap::world_map::WorldMap;
+use crate::worldgen::worldmap::utils::noise::fractal_noise;
+use noise::Perlin;
+
+/// Amplitude of the elevation detail added on top of the interpolated world elevation.
+const DETAIL_AMPLITUDE: f64 = 0.03;
+/// How far (in world tiles) biome and civ lookups are warped to break up square tile edges.
+const WARP_AMPLITUDE: f64 = 0.35;
+/// Maximum sideways wobble (in cells) of rivers and roads between tile centres.
+const LINE_WOBBLE: f64 = 6.0;
+
+pub struct RegionMapGenerator {
+    detail: Perlin,
+    warp_x: Perlin,
+    warp_y: Perlin,
+}
+
+fn is_water_biome(b: BiomeId) -> bool {
+    matches!(b, BiomeId::Ocean | BiomeId::Sea | BiomeId::Lake | BiomeId::River)
+}
+
+impl RegionMapGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            detail: Perlin::new(seed.wrapping_add(11)),
+            warp_x: Perlin::new(seed.wrapping_add(12)),
+            warp_y: Perlin::new(seed.wrapping_add(13)),
+        }
+    }
+
+    /// Generate the region around world tile `center` (plus `REGION_RADIUS` neighbours each way).
+    pub fn generate(&self, world: &WorldMap, center: (usize, usize)) -> RegionMap {
+        let origin = (
+            center.0.saturating_sub(REGION_RADIUS),
+            center.1.saturating_sub(REGION_RADIUS),
+        );
+        let end = (
+            (center.0 + REGION_RADIUS + 1).min(world.width),
+            (center.1 + REGION_RADIUS + 1).min(world.height),
+        );
+        let width = (end.0 - origin.0) * CELLS_PER_TILE;
+        let height = (end.1 - origin.1) * CELLS_PER_TILE;
+        let cell = 1.0 / CELLS_PER_TILE as f64;
+
+        let mut region = RegionMap {
+            center,
+            origin,
+            width,
+            height,
+            sea_level: world.sea_level,
+            elevation: vec![vec![0.0; height]; width],
+            biomes: vec![vec![BiomeId::Plains; height]; width],
+            rivers: vec![vec![false; height]; width],
+            roads: vec![vec![false; height]; width],
+            civ: vec![vec![None; height]; width],
+            civ_border: vec![vec![false; height]; width],
+        };
+
+        for x in 0..width {
+            for y in 0..height {
+                // Position in world-tile units (tile centres sit at +0.5)
+                let wx = origin.0 as f64 + (x as f64 + 0.5) * cell;
+                let wy = origin.1 as f64 + (y as f64 + 0.5) * cell;
+
+                let base = bilinear(&world.elevation, wx - 0.5, wy - 0.5);
+                let detail = fractal_noise(&self.detail, wx * 0.8, wy * 0.8, 5, 0.5);
+                let elev = base + detail * DETAIL_AMPLITUDE;
+                region.elevation[x][y] = elev;
+
+                let warp_dx = fractal_noise(&self.warp_x, wx * 1.5, wy * 1.5, 3, 0.5) * WARP_AMPLITUDE;
+                let warp_dy = fractal_noise(&self.warp_y, wx * 1.5, wy * 1.5, 3, 0.5) * WARP_AMPLITUDE;
+                let (tx, ty) = clamp_tile(world, wx + warp_dx, wy + warp_dy);
+
+                region.biomes[x][y] = if elev <= world.sea_level {
+                    if world.biomes[tx][ty] == BiomeId::Sea { BiomeId::Sea } else { BiomeId::Ocean }
+                } else if elev <= world.sea_level + 0.005 {
+                    BiomeId::Beach
+                } else {
+                    land_biome(world, tx, ty)
+                };
+                if elev > world.sea_level {
+                    region.civ[x][y] = world.civilization_map[tx][ty].as_ref().map(|c| c.civ_type);
+                }
+            }
+        }
+
+        self.trace_rivers(world, &mut region);
+        self.trace_roads(world, &mut region);
+        mark_borders(&mut region);
+        region
+    }
+
+    /// Connect every pair of adjacent river tiles with a wobbling line between their centres,
+    /// including tiles just outside the region so rivers enter and leave at the edges.
+    fn trace_rivers(&self, world: &WorldMap, region: &mut RegionMap) {
+        let (x0, y0) = (region.origin.0.saturating_sub(1), region.origin.1.saturating_sub(1));
+        let x1 = (region.origin.0 + region.width / CELLS_PER_TILE + 1).min(world.width);
+        let y1 = (region.origin.1 + region.height / CELLS_PER_TILE + 1).min(world.height);
+        for tx in x0..x1 {
+            for ty in y0..y1 {
+                if !world.rivers[tx][ty] {
+                    continue;
+                }
+                for (dx, dy) in [(1i32, 0i32), (0, 1), (1, 1), (1, -1)] {
+                    let nx = tx as i32 + dx;
+                    let ny = ty as i32 + dy;
+                    if nx < 0 || ny < 0 || nx as usize >= world.width || ny as usize >= world.height {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    let water_next = world.rivers[nx][ny] || world.elevation[nx][ny] <= world.sea_level;
+                    if water_next {
+                        self.stamp_line(region, (tx, ty), (nx, ny), 2.0, |r, x, y| r.rivers[x][y] = true);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rasterise the road edges of the transport network that pass through the region.
+    fn trace_roads(&self, world: &WorldMap, region: &mut RegionMap) {
+        let x_range = region.origin.0 as i64 - 1..=(region.origin.0 + region.width / CELLS_PER_TILE) as i64;
+        let y_range = region.origin.1 as i64 - 1..=(region.origin.1 + region.height / CELLS_PER_TILE) as i64;
+        for edge in world.transport.edges.iter().filter(|edge| edge.kind == EdgeKind::Road) {
+            for w in edge.path.windows(2) {
+                let (a, b) = (w[0], w[1]);
+                let near = |p: (usize, usize)| x_range.contains(&(p.0 as i64)) && y_range.contains(&(p.1 as i64));
+                if !near(a) && !near(b) {
+                    continue;
+                }
+                self.stamp_line(region, a, b, 1.0, |r, x, y| {
+                    if r.elevation[x][y] > r.sea_level && !r.rivers[x][y] {
+                        r.roads[x][y] = true;
+                    }
+                });
+            }
+        }
+    }
+
+    /// Walk from the centre of world tile `a` to the centre of `b`, wobbling sideways with noise
+    /// (pinned to zero at both ends so segments join up), and call `mark` for every covered cell.
+    fn stamp_line(
+        &self,
+        region: &mut RegionMap,
+        a: (usize, usize),
+        b: (usize, usize),
+        radius: f64,
+        mut mark: impl FnMut(&mut RegionMap, usize, usize),
+    ) {
+        let to_cell = |t: (usize, usize)| {
+            (
+                (t.0 as f64 - region.origin.0 as f64 + 0.5) * CELLS_PER_TILE as f64,
+                (t.1 as f64 - region.origin.1 as f64 + 0.5) * CELLS_PER_TILE as f64,
+            )
+        };
+        let (ax, ay) = to_cell(a);
+        let (bx, by) = to_cell(b);
+        let (dx, dy) = (bx - ax, by - ay);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return;
+        }
+        let (px, py) = (-dy / len, dx / len);
+        let steps = (len * 2.0) as usize;
+        // Seed the wobble from the segment midpoint so both directions agree
+        let (mx, my) = ((a.0 + b.0) as f64 * 0.5, (a.1 + b.1) as f64 * 0.5);
+        let r = radius.ceil() as i64;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            let wobble = fractal_noise(&self.warp_x, mx * 3.1 + t * 2.0, my * 3.1, 2, 0.5)
+                * LINE_WOBBLE
+                * (std::f64::consts::PI * t).sin();
+            let cx = ax + dx * t + px * wobble;
+            let cy = ay + dy * t + py * wobble;
+            for ox in -r..=r {
+                for oy in -r..=r {
+                    if ((ox * ox + oy * oy) as f64) > radius * radius {
+                        continue;
+                    }
+                    let x = cx as i64 + ox;
+                    let y = cy as i64 + oy;
+                    if x >= 0 && y >= 0 && (x as usize) < region.width && (y as usize) < region.height {
+                        mark(region, x as usize, y as usize);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn clamp_tile(world: &WorldMap, wx: f64, wy: f64) -> (usize, usize) {
+    (
+        (wx.floor().max(0.0) as usize).min(world.width - 1),
+        (wy.floor().max(0.0) as usize).min(world.height - 1),
+    )
+}
+
+/// Biome for a land cell; water biomes on the world map are replaced by the most common
+/// neighbouring land biome since rivers and coasts are drawn at region resolution instead.
+fn land_biome(world: &WorldMap, tx: usize, ty: usize) -> BiomeId {
+    let biome = world.biomes[tx][ty];
+    if !is_water_biome(biome) {
+        return biome;
+    }
+    let mut counts: Vec<(BiomeId, usize)> = Vec::new();
+    for dx in -1i32..=1 {
+        for dy in -1i32..=1 {
+            let nx = tx as i32 + dx;
+            let ny = ty as i32 + dy;
+            if nx < 0 || ny < 0 || nx as usize >= world.width || ny as usize >= world.height {
+                continue;
+            }
+            let b = world.biomes[nx as usize][ny as usize];
+            if is_water_biome(b) {
+                continue;
+            }
+            match counts.iter_mut().find(|(id, _)| *id == b) {
+                Some((_, c)) => *c += 1,
+                None => counts.push((b, 1)),
+            }
+        }
+    }
+    counts.into_iter().max_by_key(|&(_, c)| c).map(|(b, _)| b).unwrap_or(BiomeId::Plains)
+}
+
+/// Bilinear sample of a world layer at fractional tile coordinates (clamped to the map).
+fn bilinear(layer: &[Vec<f64>], x: f64, y: f64) -> f64 {
+    let w = layer.len();
+    let h = layer[0].len();
+    let x = x.clamp(0.0, (w - 1) as f64);
+    let y = y.clamp(0.0, (h - 1) as f64);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+    let top = layer[x0][y0] * (1.0 - fx) + layer[x1][y0] * fx;
+    let bottom = layer[x0][y1] * (1.0 - fx) + layer[x1][y1] * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+fn mark_borders(region: &mut RegionMap) {
+    for x in 0..region.width {
+        for y in 0..region.height {
+            let Some(civ) = region.civ[x][y] else { continue; };
+            let differs = |nx: usize, ny: usize| region.civ[nx][ny] != Some(civ) && region.elevation[nx][ny] > region.sea_level;
+            let border = (x > 0 && differs(x - 1, y))
+                || (x + 1 < region.width && differs(x + 1, y))
+                || (y > 0 && differs(x, y - 1))
+                || (y + 1 < region.height && differs(x, y + 1));
+            region.civ_border[x][y] = border;
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! Region map generation: upsampling world layers around one world tile with added detail.
+pub mod generator;
+
+pub use generator::RegionMapGenerator;
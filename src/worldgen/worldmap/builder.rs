@@ -7,6 +7,8 @@ use rand::SeedableRng;
 
 use super::biome;
 use super::civ;
+use super::resources;
+use super::sites;
 use super::climate::{precipitation, soil, temperature, vegetation, wind};
 use super::hydrology::{flow, rivers};
 use super::params::WorldGenParams;
@@ -182,9 +184,12 @@ impl WorldMapBuilder {
         }
 
         // === Civilisations & trade ===
-        let (civ_map, cities, relations, trade) =
+        let (civ_map, cities, relations, trade, transport) =
             civ::generate_all(self, &elevation, sea, &biomes, &river_mask);
 
+        // === Resource deposits ===
+        let resource_map = resources::place(self.seed, &biomes);
+
         // === Category maps ===
         let temperature_map = (0..self.width)
             .map(|x| {
@@ -239,7 +244,7 @@ impl WorldMapBuilder {
             })
             .collect();
 
-        WorldMap {
+        let mut world = WorldMap {
             width: self.width,
             height: self.height,
             biomes,
@@ -251,17 +256,24 @@ impl WorldMapBuilder {
             soil_fertility: soil,
             vegetation,
             wind_direction: wind,
-            resources: vec![vec![None; self.height]; self.width],
+            resources: resource_map,
             temperature_map,
             vegetation_map,
             precipitation_map,
             elevation_map,
             civilization_map: civ_map,
             cities,
+            sites: Vec::new(),
             civ_relations: relations,
             trade_routes: trade,
+            transport,
             sea_level: sea,
-        }
+        };
+
+        // === Points of interest ===
+        world.sites = sites::generate_sites(self.seed, &world);
+        println!("Placed {} sites", world.sites.len());
+        world
     }
 }
 
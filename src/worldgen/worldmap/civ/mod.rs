@@ -2,12 +2,15 @@ pub mod roads;
 pub mod seed;
 pub mod relations;
 pub mod trade;
+pub mod network;
+pub mod settlement;
 
 use crate::world::worldmap::biome::BiomeId;
 use crate::world::worldmap::city::City;
 use crate::world::worldmap::{CivilizationRelations, TradeRoute};
+use crate::world::worldmap::transport::TransportNetwork;
 
-/// Generate all civilization-related data: civ map, cities, relations, and trade routes.
+/// Generate all civilization-related data: civ map, cities, relations, trade routes and the transport network.
 pub fn generate_all(
     builder: &crate::worldgen::worldmap::builder::WorldMapBuilder,
     elevation: &[Vec<f64>],
@@ -19,13 +22,16 @@ pub fn generate_all(
     Vec<City>,
     CivilizationRelations,
     Vec<TradeRoute>,
+    TransportNetwork,
 ) {
     let (civ_map, cities, civ_seeds) = seed::generate_civilizations_and_cities(
         builder, elevation, sea_level, biomes, river_mask,
     );
     let relations = relations::generate_relations(&civ_seeds, builder.seed);
+    let mut network = network::NetworkBuilder::new(elevation, river_mask, sea_level);
     let trade = trade::generate_trade_routes(
-        &civ_seeds, &cities, &elevation.to_vec(), &river_mask.to_vec(), sea_level, &relations
+        &civ_seeds, &cities, &elevation.to_vec(), &river_mask.to_vec(), sea_level, &relations, &mut network
     );
-    (civ_map, cities, relations, trade)
+    let transport = network.build(&cities);
+    (civ_map, cities, relations, trade, transport)
 }
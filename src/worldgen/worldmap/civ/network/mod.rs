@@ -0,0 +1,159 @@
+use crate::world::worldmap::city::City;
+use crate::world::worldmap::transport::{EdgeKind, NodeKind, TransportNetwork};
+use std::collections::{HashMap, HashSet};
+
+/// A single leg of a generated route, before it is merged into the network.
+pub enum Leg {
+    Land(Vec<(usize, usize)>),
+    Water(Vec<(usize, usize)>),
+}
+
+type StepMap = HashMap<(usize, usize), Vec<((usize, usize), EdgeKind)>>;
+
+/// Collects route legs during trade generation and collapses them into a `TransportNetwork`.
+pub struct NetworkBuilder<'a> {
+    elevation: &'a [Vec<f64>],
+    rivers: &'a [Vec<bool>],
+    sea_level: f64,
+    steps: StepMap,
+    bridges: HashSet<(usize, usize)>,
+}
+
+impl<'a> NetworkBuilder<'a> {
+    pub fn new(elevation: &'a [Vec<f64>], rivers: &'a [Vec<bool>], sea_level: f64) -> Self {
+        Self {
+            elevation,
+            rivers,
+            sea_level,
+            steps: HashMap::new(),
+            bridges: HashSet::new(),
+        }
+    }
+
+    fn is_land(&self, (x, y): (usize, usize)) -> bool {
+        self.elevation[x][y] > self.sea_level
+    }
+
+    fn add_step(&mut self, from: (usize, usize), to: (usize, usize), kind: EdgeKind) {
+        if from == to {
+            return;
+        }
+        for (a, b) in [(from, to), (to, from)] {
+            let list = self.steps.entry(a).or_default();
+            if !list.contains(&(b, kind)) {
+                list.push((b, kind));
+            }
+        }
+    }
+
+    pub fn add_leg(&mut self, leg: Leg) {
+        match leg {
+            Leg::Land(path) => {
+                for w in path.windows(2) {
+                    self.add_step(w[0], w[1], EdgeKind::Road);
+                }
+                for &(x, y) in &path {
+                    if self.rivers[x][y] && self.is_land((x, y)) {
+                        self.bridges.insert((x, y));
+                    }
+                }
+            }
+            Leg::Water(path) => {
+                for w in path.windows(2) {
+                    let kind = if self.is_land(w[0]) && self.is_land(w[1]) {
+                        EdgeKind::River
+                    } else {
+                        EdgeKind::SeaLane
+                    };
+                    self.add_step(w[0], w[1], kind);
+                }
+            }
+        }
+    }
+
+    fn step_cost(&self, from: (usize, usize), to: (usize, usize), kind: EdgeKind) -> f64 {
+        let diagonal = from.0 != to.0 && from.1 != to.1;
+        let dist = if diagonal { std::f64::consts::SQRT_2 } else { 1.0 };
+        match kind {
+            EdgeKind::Road => {
+                let climb = (self.elevation[to.0][to.1] - self.elevation[from.0][from.1]).max(0.0);
+                dist * (1.0 + 10.0 * climb)
+            }
+            EdgeKind::River => dist * 0.6,
+            EdgeKind::SeaLane => dist * 0.8,
+        }
+    }
+
+    /// Collapse the tile-level step graph into nodes (cities, ports, junctions) and edges.
+    pub fn build(self, cities: &[City]) -> TransportNetwork {
+        let mut network = TransportNetwork::new();
+        let city_tiles: HashSet<(usize, usize)> = cities.iter().map(|c| (c.x, c.y)).collect();
+
+        // Decide which tiles become nodes
+        let mut node_tiles: Vec<((usize, usize), NodeKind)> = Vec::new();
+        for (&tile, list) in &self.steps {
+            let has_road = list.iter().any(|&(_, k)| k == EdgeKind::Road);
+            let has_river = list.iter().any(|&(_, k)| k == EdgeKind::River);
+            let has_sea = list.iter().any(|&(_, k)| k == EdgeKind::SeaLane);
+            let kind = if city_tiles.contains(&tile) {
+                Some(NodeKind::City)
+            } else if has_road && (has_river || has_sea) {
+                Some(NodeKind::Port)
+            } else if list.len() != 2 || (has_river && has_sea) {
+                Some(NodeKind::Junction)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                node_tiles.push((tile, kind));
+            }
+        }
+        // Deterministic node ids regardless of hash order
+        node_tiles.sort_by_key(|&(tile, _)| tile);
+        for &(tile, kind) in &node_tiles {
+            network.add_node(tile, kind);
+        }
+
+        // Walk every chain of degree-2 tiles between two nodes
+        let mut walked: HashSet<((usize, usize), (usize, usize))> = HashSet::new();
+        for &(start, _) in &node_tiles {
+            let start_id = network.node_at(start).unwrap();
+            for &(first, kind) in &self.steps[&start] {
+                if walked.contains(&(start, first)) {
+                    continue;
+                }
+                let mut path = vec![start];
+                let mut cost = 0.0;
+                let mut prev = start;
+                let mut current = first;
+                loop {
+                    walked.insert((prev, current));
+                    walked.insert((current, prev));
+                    cost += self.step_cost(prev, current, kind);
+                    path.push(current);
+                    if let Some(end_id) = network.node_at(current) {
+                        network.add_edge(start_id, end_id, kind, cost, path);
+                        break;
+                    }
+                    // Degree-2 tile: continue along the step we didn't come from
+                    let next = self.steps[&current]
+                        .iter()
+                        .find(|&&(n, k)| n != prev && k == kind)
+                        .map(|&(n, _)| n);
+                    match next {
+                        Some(n) => {
+                            prev = current;
+                            current = n;
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        let mut bridges: Vec<(usize, usize)> = self.bridges.into_iter().collect();
+        bridges.sort();
+        network.bridges = bridges;
+        network
+    }
+}
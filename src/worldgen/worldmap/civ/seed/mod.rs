@@ -4,6 +4,7 @@ use crate::world::worldmap::{Civilization, CivilizationInstance, Culture, Alignm
 use rand::Rng;
 use rand::SeedableRng;
 use rand::seq::SliceRandom;
+use super::settlement::SettlementTerrain;
 
 /// Returns (civ_map, cities, civ_seeds)
 pub fn generate_civilizations_and_cities(
@@ -11,7 +12,7 @@ pub fn generate_civilizations_and_cities(
     elevation: &[Vec<f64>],
     sea_level: f64,
     biomes: &[Vec<BiomeId>],
-    _river_mask: &[Vec<bool>],
+    river_mask: &[Vec<bool>],
 ) -> (
     Vec<Vec<Option<CivilizationInstance>>>,
     Vec<City>,
@@ -120,6 +121,7 @@ pub fn generate_civilizations_and_cities(
         }
     }
     // Place cities for each civilization instance
+    let terrain = SettlementTerrain { elevation, rivers: river_mask, sea_level };
     let mut cities = Vec::new();
     let city_names = [
         "Aldoria", "Brighthaven", "Stonehelm", "Rivermouth", "Frostford", "Sunspire", "Shadowfen", "Goldport", "Ironhold", "Starfall", "Mistwood", "Deepmere", "Windrest", "Moonwatch", "Thundertop"
@@ -136,14 +138,9 @@ pub fn generate_civilizations_and_cities(
                 && !cities.iter().any(|c: &City| (c.x as isize - x as isize).abs() < 5 && (c.y as isize - y as isize).abs() < 5)
             {
                 let name = city_names.choose(&mut rng).unwrap_or(&"Unnamed").to_string();
-                let population = rng.gen_range(1000..100_000);
-                cities.push(City {
-                    name,
-                    civ: civ_inst.civ_type,
-                    x,
-                    y,
-                    population,
-                });
+                // Skewed towards small settlements so every tier shows up
+                let population = (rng.gen_range(0.0f64..1.0).powi(3) * 100_000.0) as u32 + 200;
+                cities.push(terrain.found_city(&mut rng, name, civ_inst, (x, y), population, false));
                 placed += 1;
                 if placed >= max_cities { break; }
             }
@@ -161,13 +158,7 @@ pub fn generate_civilizations_and_cities(
             Civilization::Kobold => "Kobold",
         }, instance_id);
         let population = rng.gen_range(50_000..500_000);
-        cities.push(City {
-            name,
-            civ: civ_inst.civ_type,
-            x: *seed_x,
-            y: *seed_y,
-            population,
-        });
+        cities.push(terrain.found_city(&mut rng, name, civ_inst, (*seed_x, *seed_y), population, true));
     }
     (civilization_map, cities, civ_seeds)
 }
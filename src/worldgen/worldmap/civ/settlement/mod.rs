@@ -0,0 +1,131 @@
+use crate::world::worldmap::city::{Building, City, SettlementTier};
+use crate::world::worldmap::{CivilizationInstance, SocietalTrait};
+use rand::Rng;
+
+/// Year the generated world "starts" in; founding years lie before it.
+pub const CURRENT_YEAR: u32 = 1000;
+
+/// Terrain the settlement stage needs to derive port/river flags.
+pub struct SettlementTerrain<'a> {
+    pub elevation: &'a [Vec<f64>],
+    pub rivers: &'a [Vec<bool>],
+    pub sea_level: f64,
+}
+
+impl<'a> SettlementTerrain<'a> {
+    fn any_neighbor(&self, x: usize, y: usize, pred: impl Fn(usize, usize) -> bool) -> bool {
+        let (w, h) = (self.elevation.len() as i32, self.elevation[0].len() as i32);
+        for dx in -1i32..=1 {
+            for dy in -1i32..=1 {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx >= 0 && ny >= 0 && nx < w && ny < h && pred(nx as usize, ny as usize) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn is_coastal(&self, x: usize, y: usize) -> bool {
+        self.any_neighbor(x, y, |nx, ny| self.elevation[nx][ny] <= self.sea_level)
+    }
+
+    pub fn is_riverside(&self, x: usize, y: usize) -> bool {
+        self.any_neighbor(x, y, |nx, ny| self.rivers[nx][ny])
+    }
+
+    /// Build a city at `pos`, deriving tier, flags, age and buildings.
+    pub fn found_city<R: Rng>(
+        &self,
+        rng: &mut R,
+        name: String,
+        civ_inst: &CivilizationInstance,
+        (x, y): (usize, usize),
+        population: u32,
+        capital: bool,
+    ) -> City {
+        let tier = if capital {
+            SettlementTier::Capital
+        } else {
+            SettlementTier::from_population(population)
+        };
+        let is_port = self.is_coastal(x, y);
+        let is_river_town = self.is_riverside(x, y);
+        let militaristic = civ_inst.culture.trait_ == SocietalTrait::Militaristic;
+        let has_walls = match tier {
+            SettlementTier::Hamlet => false,
+            SettlementTier::Village => militaristic,
+            SettlementTier::Town => militaristic || rng.gen_bool(0.5),
+            SettlementTier::City | SettlementTier::Capital => true,
+        };
+        let is_fortress = has_walls && (militaristic || rng.gen_bool(0.1));
+        // Capitals are the oldest settlements of their civ
+        let founding_year = if capital {
+            rng.gen_range(0..CURRENT_YEAR / 4)
+        } else {
+            rng.gen_range(CURRENT_YEAR / 4..CURRENT_YEAR)
+        };
+        let buildings = notable_buildings(rng, civ_inst, tier, is_port, is_river_town, is_fortress);
+        City {
+            name,
+            civ: civ_inst.civ_type,
+            x,
+            y,
+            population,
+            tier,
+            is_port,
+            is_river_town,
+            has_walls,
+            is_fortress,
+            founding_year,
+            governor: civ_inst.clone(),
+            buildings,
+        }
+    }
+}
+
+/// Notable buildings reflecting the governing culture and the city's size and site.
+fn notable_buildings<R: Rng>(
+    rng: &mut R,
+    civ_inst: &CivilizationInstance,
+    tier: SettlementTier,
+    is_port: bool,
+    is_river_town: bool,
+    is_fortress: bool,
+) -> Vec<Building> {
+    let culture = &civ_inst.culture;
+    let mut buildings = Vec::new();
+    // Every settlement worships; only towns and up can afford a full temple
+    if tier >= SettlementTier::Town {
+        buildings.push(Building::Temple(culture.religion.clone()));
+    } else {
+        buildings.push(Building::Shrine);
+    }
+    match culture.trait_ {
+        SocietalTrait::Mercantile => {
+            buildings.push(Building::GuildHall);
+            buildings.push(Building::Market);
+        }
+        SocietalTrait::Militaristic => buildings.push(Building::Barracks),
+        SocietalTrait::Scholarly if tier >= SettlementTier::Town => buildings.push(Building::Library),
+        SocietalTrait::Spiritual if tier >= SettlementTier::Town => buildings.push(Building::Shrine),
+        _ => {}
+    }
+    if tier >= SettlementTier::Town && !buildings.contains(&Building::Market) {
+        buildings.push(Building::Market);
+    }
+    if is_port && tier >= SettlementTier::Village {
+        buildings.push(Building::Shipyard);
+        if tier >= SettlementTier::City && rng.gen_bool(0.5) {
+            buildings.push(Building::Lighthouse);
+        }
+    }
+    if is_river_town {
+        buildings.push(Building::Mill);
+    }
+    if is_fortress || tier == SettlementTier::Capital {
+        buildings.push(Building::Keep);
+    }
+    buildings
+}
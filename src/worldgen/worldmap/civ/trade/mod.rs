@@ -1,6 +1,7 @@
 use crate::world::worldmap::{CivilizationInstance, City, CivilizationRelations, TradeRoute, Civilization, Relation};
 use std::collections::{HashMap, HashSet};
 pub use super::roads;
+use super::network::{Leg, NetworkBuilder};
 
 pub fn generate_trade_routes(
     civ_seeds: &Vec<(usize, usize, CivilizationInstance, usize)>,
@@ -9,6 +10,7 @@ pub fn generate_trade_routes(
     rivers: &Vec<Vec<bool>>,
     sea_level: f64,
     relations: &CivilizationRelations,
+    network: &mut NetworkBuilder,
 ) -> Vec<TradeRoute> {
     let mut trade_routes = Vec::new();
     let mut road_tiles = HashSet::new();
@@ -59,10 +61,13 @@ pub fn generate_trade_routes(
                     let a_water = !a_land;
                     let b_water = !b_land;
                     let mut path = None;
+                    let mut legs = Vec::new();
                     if a_land && b_land {
                         path = roads::astar_land_with_roads(elevation, sea_level, mountain_level, (ax, ay), (bx, by), &road_tiles);
+                        if let Some(p) = &path { legs.push(Leg::Land(p.clone())); }
                     } else if a_water && b_water {
                         path = roads::astar_water_with_roads(elevation, rivers, sea_level, (ax, ay), (bx, by), &road_tiles);
+                        if let Some(p) = &path { legs.push(Leg::Water(p.clone())); }
                     } else {
                         let (land_city, water_city) = if a_land { ((ax, ay), (bx, by)) } else { ((bx, by), (ax, ay)) };
                         if let Some(water_entry) = roads::nearest_water(elevation, rivers, sea_level, land_city) {
@@ -70,6 +75,9 @@ pub fn generate_trade_routes(
                                 if let Some(water_exit) = roads::nearest_water(elevation, rivers, sea_level, water_city) {
                                     if let Some(sea_path) = roads::astar_water_with_roads(elevation, rivers, sea_level, water_entry, water_exit, &road_tiles) {
                                         if let Some(final_leg) = roads::astar_land_with_roads(elevation, sea_level, mountain_level, water_exit, water_city, &road_tiles) {
+                                            legs.push(Leg::Land(water_path.clone()));
+                                            legs.push(Leg::Water(sea_path.clone()));
+                                            legs.push(Leg::Land(final_leg.clone()));
                                             let mut full = water_path;
                                             full.pop();
                                             full.extend(sea_path);
@@ -84,6 +92,7 @@ pub fn generate_trade_routes(
                     }
                     let path = path.unwrap_or_else(|| vec![(ax, ay), (bx, by)]);
                     for &pt in &path { road_tiles.insert(pt); }
+                    for leg in legs { network.add_leg(leg); }
                     trade_routes.push(TradeRoute {
                         from: (ax, ay),
                         to: (bx, by),
@@ -147,10 +156,13 @@ pub fn generate_trade_routes(
             let a_water = !a_land;
             let b_water = !b_land;
             let mut path = None;
+            let mut legs = Vec::new();
             if a_land && b_land {
                 path = roads::astar_land_with_roads(elevation, sea_level, mountain_level, (a.x, a.y), (b.x, b.y), &road_tiles);
+                if let Some(p) = &path { legs.push(Leg::Land(p.clone())); }
             } else if a_water && b_water {
                 path = roads::astar_water_with_roads(elevation, rivers, sea_level, (a.x, a.y), (b.x, b.y), &road_tiles);
+                if let Some(p) = &path { legs.push(Leg::Water(p.clone())); }
             } else {
                 let (land_city, water_city) = if a_land { ((a.x, a.y), (b.x, b.y)) } else { ((b.x, b.y), (a.x, a.y)) };
                 if let Some(water_entry) = roads::nearest_water(elevation, rivers, sea_level, land_city) {
@@ -158,6 +170,9 @@ pub fn generate_trade_routes(
                         if let Some(water_exit) = roads::nearest_water(elevation, rivers, sea_level, water_city) {
                             if let Some(sea_path) = roads::astar_water_with_roads(elevation, rivers, sea_level, water_entry, water_exit, &road_tiles) {
                                 if let Some(final_leg) = roads::astar_land_with_roads(elevation, sea_level, mountain_level, water_exit, water_city, &road_tiles) {
+                                    legs.push(Leg::Land(water_path.clone()));
+                                    legs.push(Leg::Water(sea_path.clone()));
+                                    legs.push(Leg::Land(final_leg.clone()));
                                     let mut full = water_path;
                                     full.pop();
                                     full.extend(sea_path);
@@ -172,6 +187,7 @@ pub fn generate_trade_routes(
             }
             let path = path.unwrap_or_else(|| vec![(a.x, a.y), (b.x, b.y)]);
             for &pt in &path { road_tiles.insert(pt); }
+            for leg in legs { network.add_leg(leg); }
             trade_routes.push(TradeRoute {
                 from: (a.x, a.y),
                 to: (b.x, b.y),
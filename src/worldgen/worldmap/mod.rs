@@ -7,6 +7,8 @@ pub mod climate;
 pub mod hydrology;
 pub mod biome;
 pub mod civ;
+pub mod resources;
+pub mod sites;
 pub mod builder;          // thin orchestrator
 
 // Only keep the builder-based alias for backward compatibility
@@ -0,0 +1,33 @@
+use crate::world::worldmap::biome::BiomeId;
+use crate::world::worldmap::world_map::ResourceType;
+use rand::{Rng, SeedableRng};
+
+/// Scatter resource deposits according to biome: metals and gems in high ground, oil in lowlands.
+pub fn place(seed: u32, biomes: &[Vec<BiomeId>]) -> Vec<Vec<Option<ResourceType>>> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed as u64 + 3000);
+    let width = biomes.len();
+    let height = if width > 0 { biomes[0].len() } else { 0 };
+    let mut resources = vec![vec![None; height]; width];
+    for x in 0..width {
+        for y in 0..height {
+            let roll = rng.gen_range(0.0..1.0);
+            resources[x][y] = match biomes[x][y] {
+                BiomeId::Mountain | BiomeId::Snow if roll < 0.06 => Some(match rng.gen_range(0..10) {
+                    0..=3 => ResourceType::Iron,
+                    4..=5 => ResourceType::Copper,
+                    6..=7 => ResourceType::Gold,
+                    _ => ResourceType::Gems,
+                }),
+                BiomeId::Hills if roll < 0.04 => Some(match rng.gen_range(0..10) {
+                    0..=3 => ResourceType::Coal,
+                    4..=6 => ResourceType::Iron,
+                    _ => ResourceType::Copper,
+                }),
+                BiomeId::Desert | BiomeId::Swamp if roll < 0.03 => Some(ResourceType::Oil),
+                BiomeId::Taiga | BiomeId::BorealForest if roll < 0.02 => Some(ResourceType::Coal),
+                _ => None,
+            };
+        }
+    }
+    resources
+}
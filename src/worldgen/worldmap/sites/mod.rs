@@ -0,0 +1,206 @@
+use crate::world::worldmap::biome::BiomeId;
+use crate::world::worldmap::site::{Site, SiteType};
+use crate::world::worldmap::world_map::WorldMap;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+const LOST_NAMES: [&str; 12] = [
+    "Old Karn", "Vel'tharas", "Ashmoor", "Gravenholt", "Eldermarch", "Duskhollow",
+    "Thrennic", "Mourngate", "Saltreach", "Orvane", "Cinderfall", "Hollowmere",
+];
+const MOUNTAIN_BEASTS: [&str; 4] = ["Dragon", "Wyvern", "Troll", "Hill Giant"];
+const SWAMP_BEASTS: [&str; 4] = ["Hag", "Hydra", "Bog Lurker", "Basilisk"];
+
+struct Placer<'a> {
+    world: &'a WorldMap,
+    occupied: HashSet<(usize, usize)>,
+    sites: Vec<Site>,
+}
+
+impl<'a> Placer<'a> {
+    fn is_land(&self, x: usize, y: usize) -> bool {
+        self.world.elevation[x][y] > self.world.sea_level
+    }
+
+    fn dist_to_nearest_city(&self, x: usize, y: usize) -> f64 {
+        self.world.cities.iter()
+            .map(|c| ((c.x as f64 - x as f64).powi(2) + (c.y as f64 - y as f64).powi(2)).sqrt())
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn dist_to_nearest_site(&self, x: usize, y: usize, kind: SiteType) -> f64 {
+        self.sites.iter()
+            .filter(|s| s.kind == kind)
+            .map(|s| ((s.x as f64 - x as f64).powi(2) + (s.y as f64 - y as f64).powi(2)).sqrt())
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn is_wilderness(&self, x: usize, y: usize) -> bool {
+        self.world.civilization_map[x][y].is_none()
+    }
+
+    fn is_border(&self, x: usize, y: usize) -> bool {
+        let Some(inst) = &self.world.civilization_map[x][y] else { return false; };
+        let (w, h) = (self.world.width as i32, self.world.height as i32);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            if nx < 0 || ny < 0 || nx >= w || ny >= h || !self.is_land(nx as usize, ny as usize) {
+                continue;
+            }
+            match &self.world.civilization_map[nx as usize][ny as usize] {
+                Some(other) if other.civ_type == inst.civ_type => {}
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    fn push(&mut self, name: String, kind: SiteType, (x, y): (usize, usize), description: String) {
+        let civ = self.world.civilization_map[x][y].as_ref().map(|c| c.civ_type);
+        self.occupied.insert((x, y));
+        self.sites.push(Site {
+            name,
+            kind,
+            x,
+            y,
+            description,
+            local_hint: kind.local_hint(),
+            civ,
+        });
+    }
+
+    /// Random land tiles (not already used) that satisfy `pred`, up to `count` sites.
+    fn scatter<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        count: usize,
+        pred: impl Fn(&Self, usize, usize) -> bool,
+        mut make: impl FnMut(&mut R, &Self, usize, usize) -> (String, SiteType, String),
+    ) {
+        let (w, h) = (self.world.width, self.world.height);
+        let mut placed = 0;
+        for _ in 0..count * 200 {
+            if placed >= count {
+                break;
+            }
+            let x = rng.gen_range(0..w);
+            let y = rng.gen_range(0..h);
+            if !self.is_land(x, y) || self.occupied.contains(&(x, y)) || !pred(self, x, y) {
+                continue;
+            }
+            let (name, kind, description) = make(rng, self, x, y);
+            self.push(name, kind, (x, y), description);
+            placed += 1;
+        }
+    }
+}
+
+/// Place ruins, lairs, dungeons, shrines, mines and watchtowers on a finished world map.
+pub fn generate_sites(seed: u32, world: &WorldMap) -> Vec<Site> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed as u64 + 4000);
+    let area = world.width * world.height;
+    let mut placer = Placer {
+        world,
+        occupied: world.cities.iter().map(|c| (c.x, c.y)).collect(),
+        sites: Vec::new(),
+    };
+
+    // Ancient ruins, away from living settlements
+    placer.scatter(&mut rng, area / 1500, |p, x, y| p.dist_to_nearest_city(x, y) > 6.0, |rng, _, _, _| {
+        let lost = LOST_NAMES.choose(rng).unwrap();
+        (
+            format!("Ruins of {}", lost),
+            SiteType::Ruins,
+            format!("Crumbling walls are all that remain of {}, abandoned long ago.", lost),
+        )
+    });
+
+    // Monster lairs in remote mountains and swamps
+    placer.scatter(&mut rng, area / 2000, |p, x, y| {
+        matches!(p.world.biomes[x][y], BiomeId::Mountain | BiomeId::Snow | BiomeId::Swamp)
+            && p.dist_to_nearest_city(x, y) > 6.0
+    }, |rng, p, x, y| {
+        let swamp = p.world.biomes[x][y] == BiomeId::Swamp;
+        let beast = if swamp { SWAMP_BEASTS.choose(rng) } else { MOUNTAIN_BEASTS.choose(rng) }.unwrap();
+        let place = if swamp { "Hollow" } else { "Roost" };
+        (
+            format!("{}'s {}", beast, place),
+            SiteType::Lair,
+            format!("A {} makes its home here, far from any road.", beast.to_lowercase()),
+        )
+    });
+
+    // Dungeons dug into hills and mountains
+    placer.scatter(&mut rng, area / 3000, |p, x, y| {
+        matches!(p.world.biomes[x][y], BiomeId::Hills | BiomeId::Mountain) && p.dist_to_nearest_city(x, y) > 5.0
+    }, |rng, _, _, _| {
+        let lost = LOST_NAMES.choose(rng).unwrap();
+        (
+            format!("Halls of {}", lost),
+            SiteType::Dungeon,
+            format!("Tunnels and vaults beneath {} descend into darkness.", lost),
+        )
+    });
+
+    // Shrines to the religion of the local civilization
+    placer.scatter(&mut rng, area / 1500, |p, x, y| {
+        !p.is_wilderness(x, y) && p.dist_to_nearest_city(x, y) > 3.0
+    }, |_, p, x, y| {
+        let inst = p.world.civilization_map[x][y].as_ref().unwrap();
+        (
+            format!("Shrine of {}", inst.culture.religion),
+            SiteType::Shrine,
+            format!("A {:?} wayside shrine devoted to {}.", inst.civ_type, inst.culture.religion),
+        )
+    });
+
+    // Mines on resource deposits: worked inside territory, sometimes abandoned outside it
+    for x in 0..world.width {
+        for y in 0..world.height {
+            let Some(resource) = world.resources[x][y] else { continue; };
+            if !placer.is_land(x, y) || placer.occupied.contains(&(x, y)) {
+                continue;
+            }
+            if placer.is_wilderness(x, y) {
+                if rng.gen_bool(0.2) {
+                    placer.push(
+                        format!("Abandoned {:?} Mine", resource),
+                        SiteType::Mine,
+                        (x, y),
+                        format!("Collapsed shafts over a {:?} deposit, long since left.", resource),
+                    );
+                }
+            } else if rng.gen_bool(0.5) {
+                placer.push(
+                    format!("{:?} Mine", resource),
+                    SiteType::Mine,
+                    (x, y),
+                    format!("A working mine extracting {:?}.", resource),
+                );
+            }
+        }
+    }
+
+    // Watchtowers along civilization borders, spaced apart
+    for x in 0..world.width {
+        for y in 0..world.height {
+            if !placer.is_land(x, y) || placer.occupied.contains(&(x, y)) || !placer.is_border(x, y) {
+                continue;
+            }
+            if placer.dist_to_nearest_site(x, y, SiteType::Watchtower) < 16.0 || !rng.gen_bool(0.3) {
+                continue;
+            }
+            let civ = world.civilization_map[x][y].as_ref().unwrap().civ_type;
+            placer.push(
+                format!("{:?} Watchtower", civ),
+                SiteType::Watchtower,
+                (x, y),
+                format!("A {:?} tower keeping watch over the border.", civ),
+            );
+        }
+    }
+
+    placer.sites
+}